@@ -0,0 +1,182 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, anyhow};
+use scheduler_core::dsl::ActionDef;
+use scheduler_executor::{ActionContext, ActionOutcome, AsyncActionComponent};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpSocket, lookup_host};
+
+use crate::http_client::{HttpRequest, HttpResponse};
+use crate::{PreparedRequest, content_length, find_header_end};
+
+/// 单次请求（连接、发送与接收）的时间上限
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 非阻塞 HTTP Action 组件（基于 tokio socket）
+///
+/// 与 [`crate::HttpActionComponent`] 使用相同的请求参数与结果格式，等待 I/O
+/// 时让出运行时线程，大量虚拟用户可共享少量 OS 线程
+#[derive(Debug, Default)]
+pub struct AsyncHttpActionComponent {}
+
+impl AsyncHttpActionComponent {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl AsyncActionComponent for AsyncHttpActionComponent {
+    async fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    async fn do_action(
+        &mut self,
+        action: &ActionDef,
+        _ctx: &mut ActionContext<'_>,
+    ) -> Result<ActionOutcome> {
+        let prepared = PreparedRequest::new(action)?;
+
+        let started = Instant::now();
+        let response = tokio::time::timeout(
+            REQUEST_TIMEOUT,
+            send_http_request(&prepared.request, prepared.bind_ip.as_deref()),
+        )
+        .await
+        .unwrap_or_else(|_| Err(anyhow!("no response within {:?}", REQUEST_TIMEOUT)))
+        .with_context(|| prepared.failure_context())?;
+        Ok(prepared.outcome(response, started.elapsed()))
+    }
+
+    async fn release(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 发送请求并读取响应（`Connection: close`，读到 Content-Length 或连接关闭为止）
+async fn send_http_request(request: &HttpRequest, bind_ip: Option<&str>) -> Result<HttpResponse> {
+    let (host, port, _path, is_https) = request.parse_url()?;
+    if is_https {
+        return Err(anyhow!(
+            "HTTPS not yet supported in socket-based implementation"
+        ));
+    }
+
+    let bind_ip = bind_ip
+        .map(|ip| {
+            ip.parse::<IpAddr>()
+                .with_context(|| format!("invalid bind_ip `{}`", ip))
+        })
+        .transpose()?;
+    // 绑定源 IP 时只连接同一地址族的目标地址
+    let remote = lookup_host((host.as_str(), port))
+        .await
+        .with_context(|| format!("Failed to resolve {}", host))?
+        .find(|addr| bind_ip.is_none_or(|ip| ip.is_ipv4() == addr.is_ipv4()))
+        .ok_or_else(|| anyhow!("No usable address for {}", host))?;
+
+    let socket = match remote {
+        SocketAddr::V4(_) => TcpSocket::new_v4(),
+        SocketAddr::V6(_) => TcpSocket::new_v6(),
+    }
+    .context("Failed to create socket")?;
+    if let Some(ip) = bind_ip {
+        socket
+            .bind(SocketAddr::new(ip, 0))
+            .with_context(|| format!("Failed to bind to {}", ip))?;
+    }
+    let mut stream = socket
+        .connect(remote)
+        .await
+        .with_context(|| format!("Failed to connect to {}:{}", host, port))?;
+
+    stream
+        .write_all(&request.build_request_bytes()?)
+        .await
+        .context("Failed to send request")?;
+
+    let mut response_data = Vec::new();
+    let mut expected_len: Option<usize> = None;
+    let mut chunk = [0u8; 8192];
+    loop {
+        let read = stream
+            .read(&mut chunk)
+            .await
+            .context("Failed to receive response")?;
+        if read == 0 {
+            break;
+        }
+        response_data.extend_from_slice(&chunk[..read]);
+
+        if expected_len.is_none()
+            && let Some(idx) = find_header_end(&response_data)
+        {
+            expected_len = content_length(&response_data[..idx]).map(|len| idx + 4 + len);
+        }
+        if expected_len.is_some_and(|total| response_data.len() >= total) {
+            break;
+        }
+    }
+
+    if find_header_end(&response_data).is_none() {
+        return Err(anyhow!(
+            "incomplete HTTP response ({} bytes): {:?}",
+            response_data.len(),
+            String::from_utf8_lossy(&response_data)
+        ));
+    }
+    HttpResponse::parse(&response_data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indexmap::IndexMap;
+    use scheduler_core::wbs::WbsTree;
+    use scheduler_executor::ActionStatus;
+    use serde_yaml::Value;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_get_against_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 1024];
+            while find_header_end(&request).is_none() {
+                let read = stream.read(&mut chunk).await.unwrap();
+                request.extend_from_slice(&chunk[..read]);
+            }
+            stream
+                .write_all(b"HTTP/1.1 201 Created\r\nContent-Length: 2\r\nX-Id: 7\r\n\r\nok")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let action = ActionDef {
+            id: "create".to_string(),
+            call: "get".to_string(),
+            with: IndexMap::from([(
+                "url".to_string(),
+                Value::String(format!("http://{}/items", addr)),
+            )]),
+            export: Vec::new(),
+        };
+        let wbs = WbsTree::new_empty();
+        let outcome = AsyncHttpActionComponent::new()
+            .do_action(&action, &mut ActionContext::new(&wbs))
+            .await
+            .unwrap();
+
+        assert!(server.await.unwrap().starts_with("GET /items HTTP/1.1\r\n"));
+        assert_eq!(outcome.status, ActionStatus::Success);
+        let response = outcome.response.unwrap();
+        assert_eq!(response.status_code, Some(201));
+        assert_eq!(response.body, "ok");
+        assert_eq!(response.headers.get("x-id").map(String::as_str), Some("7"));
+    }
+}
@@ -155,6 +155,7 @@ mod bindings {
                         name: e.name,
                         scope: e.scope,
                         default: e.default_value.and_then(|v| serde_json::from_str(&v).ok()),
+                        ..Default::default()
                     })
                     .collect(),
             };
@@ -181,6 +182,8 @@ mod bindings {
             // Build HTTP request
             let method = native_action.call.to_uppercase();
             let mut request = HttpRequest::new(&method, &url);
+            // Tracked secrets are masked in the reported detail
+            let url = scheduler_core::secrets::redact(&url);
 
             // Add headers
             let headers = crate::extract_headers(&native_action);
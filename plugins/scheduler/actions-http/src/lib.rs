@@ -1,16 +1,28 @@
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result, anyhow};
 use scheduler_core::dsl::ActionDef;
+use scheduler_core::secrets;
 use scheduler_core::socket::{self, AddressFamily, SocketAddress, SocketProtocol};
-use scheduler_executor::{ActionComponent, ActionContext, ActionOutcome, ActionStatus};
+use scheduler_executor::{
+    ActionComponent, ActionContext, ActionOutcome, ActionResponse, ActionStatus,
+};
 use serde_json::Value as JsonValue;
 use serde_yaml::Value;
 
 // HTTP client using raw sockets
 pub mod http_client;
 
+// Non-blocking HTTP client on tokio sockets
+#[cfg(not(target_arch = "wasm32"))]
+pub mod async_http;
+
 #[cfg(target_arch = "wasm32")]
 pub mod component;
 
+#[cfg(not(target_arch = "wasm32"))]
+pub use async_http::AsyncHttpActionComponent;
+
 /// HTTP Action 组件（基于 core-libs socket）
 ///
 /// 使用 core-libs 的 socket API 执行 HTTP 请求
@@ -40,6 +52,31 @@ impl ActionComponent for HttpActionComponent {
         action: &ActionDef,
         _ctx: &mut ActionContext<'_>,
     ) -> Result<ActionOutcome> {
+        let prepared = PreparedRequest::new(action)?;
+
+        // 发送请求（使用 core-libs socket）
+        let started = Instant::now();
+        let response = send_http_request(&prepared.request, prepared.bind_ip.as_deref())
+            .with_context(|| prepared.failure_context())?;
+        Ok(prepared.outcome(response, started.elapsed()))
+    }
+
+    fn release(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// 由动作定义构建的 HTTP 请求（同步与异步组件共用）
+pub(crate) struct PreparedRequest {
+    pub(crate) request: http_client::HttpRequest,
+    pub(crate) bind_ip: Option<String>,
+    method: String,
+    /// 日志、detail 与错误信息中的 URL 均遮蔽密钥
+    shown_url: String,
+}
+
+impl PreparedRequest {
+    pub(crate) fn new(action: &ActionDef) -> Result<Self> {
         // 提取请求参数
         let url = extract_url(action)?;
         let headers = extract_headers(action);
@@ -47,41 +84,51 @@ impl ActionComponent for HttpActionComponent {
         let bind_ip = extract_bind_ip(action);
 
         // 构建 HTTP 请求
-        let mut http_request = http_client::HttpRequest::new(&action.call, &url);
+        let mut request = http_client::HttpRequest::new(&action.call, &url);
 
         // 添加请求头
         for (key, value) in headers {
-            http_request = http_request.header(key, value);
+            request = request.header(key, value);
         }
 
         // 添加请求体
         if let Some(body_str) = body {
-            http_request = http_request.body(body_str.into_bytes());
+            request = request.body(body_str.into_bytes());
         }
 
+        let method = action.call.to_uppercase();
+        let shown_url = secrets::redact(&url);
+
         // 如果指定了 bind_ip，在日志中显示
         if let Some(ip) = &bind_ip {
-            println!(
-                "[HTTP] {} {} (bind_ip: {})",
-                action.call.to_uppercase(),
-                url,
-                ip
-            );
+            println!("[HTTP] {} {} (bind_ip: {})", method, shown_url, ip);
         } else {
-            println!("[HTTP] {} {}", action.call.to_uppercase(), url);
+            println!("[HTTP] {} {}", method, shown_url);
         }
 
-        // 发送请求（使用 core-libs socket）
-        let response = send_http_request(&http_request, bind_ip.as_deref())
-            .with_context(|| format!("Failed to send {} request to {}", action.call, url))?;
+        Ok(Self {
+            request,
+            bind_ip,
+            method,
+            shown_url,
+        })
+    }
 
-        let status_code = response.status_code;
-        let success = response.is_success();
-        let _response_body = response
-            .body_string()
-            .unwrap_or_else(|_| format!("<binary data: {} bytes>", response.body.len()));
+    pub(crate) fn failure_context(&self) -> String {
+        format!(
+            "Failed to send {} request to {}",
+            self.method.to_lowercase(),
+            self.shown_url
+        )
+    }
 
-        let status = if success {
+    /// 把响应转换为动作结果；状态码、响应头与响应体交给 export 提取
+    pub(crate) fn outcome(
+        &self,
+        response: http_client::HttpResponse,
+        elapsed: Duration,
+    ) -> ActionOutcome {
+        let status = if response.is_success() {
             ActionStatus::Success
         } else {
             ActionStatus::Failed
@@ -89,20 +136,28 @@ impl ActionComponent for HttpActionComponent {
 
         let detail = format!(
             "{} {} -> {} ({} bytes)",
-            action.call.to_uppercase(),
-            url,
-            status_code,
+            self.method,
+            self.shown_url,
+            response.status_code,
             response.body.len()
         );
 
-        Ok(ActionOutcome {
+        let body = response
+            .body_string()
+            .unwrap_or_else(|_| format!("<binary data: {} bytes>", response.body.len()));
+        let response = ActionResponse {
+            status_code: Some(response.status_code),
+            headers: response.headers.into_iter().collect(),
+            body,
+            elapsed: Some(elapsed),
+        };
+
+        ActionOutcome {
             status,
             detail: Some(detail),
-        })
-    }
-
-    fn release(&mut self) -> Result<()> {
-        Ok(())
+            response: Some(response),
+            assertions: Vec::new(),
+        }
     }
 }
 
@@ -242,7 +297,7 @@ pub fn extract_headers(action: &ActionDef) -> Vec<(String, String)> {
             map.iter()
                 .filter_map(|(k, v)| {
                     let key = k.as_str()?.to_string();
-                    let value = v.as_str()?.to_string();
+                    let value = scalar_to_string(v)?;
                     Some((key, value))
                 })
                 .collect()
@@ -260,11 +315,22 @@ pub fn extract_body(action: &ActionDef) -> Result<Option<String>> {
         return Ok(Some(raw.to_string()));
     }
 
+    // 结构化 body（包括由单个占位符渲染出的导出对象/数组）序列化为 JSON
     let json_value: JsonValue = serde_yaml::from_value(body.clone()).context("body to json")?;
     let body_str = serde_json::to_string(&json_value).context("json to string")?;
     Ok(Some(body_str))
 }
 
+/// Header values may be rendered as numbers or bools by typed templates.
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
 /// Extract bind_ip from action definition
 pub fn extract_bind_ip(action: &ActionDef) -> Option<String> {
     action
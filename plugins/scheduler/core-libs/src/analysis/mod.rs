@@ -0,0 +1,1179 @@
+/// Scenario Graph Analysis
+///
+/// Collects every structural problem of a scenario in one pass instead of
+/// stopping at the first one:
+/// - DSL level: duplicate node/action ids, unknown action or edge targets,
+///   malformed edge conditions and exports, action nodes without an action
+/// - Graph level (over the built `WbsTree`): missing `start` node, nodes
+///   unreachable from `start`, action nodes without outgoing edges, cycles
+///   that can never reach an `end` node, and edges shadowed by an earlier
+///   unconditional edge
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+use crate::dsl::{ExportMode, Scenario, SourceLocation, SourceMap, WorkflowNode, WorkflowNodeType};
+use crate::export::{Assertion, Export};
+use crate::wbs::{WbsTaskKind, WbsTree};
+
+/// Entry node every workflow execution starts from.
+pub const START_NODE: &str = "start";
+
+/// `with` keys the action components currently read.
+pub const SUPPORTED_WITH_KEYS: &[&str] = &["url", "headers", "body", "bind_ip"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => f.write_str("error"),
+            Severity::Warning => f.write_str("warning"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// DSL path of the offending element, e.g. `workflows.nodes[2].edges[0]`.
+    pub path: String,
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(
+                f,
+                "{} at {} ({}): {}",
+                self.severity, location, self.path, self.message
+            ),
+            None => write!(f, "{} ({}): {}", self.severity, self.path, self.message),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ValidationReport {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl ValidationReport {
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diag| diag.severity == Severity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diag| diag.severity == Severity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.diagnostics.is_empty()
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, diag) in self.errors().chain(self.warnings()).enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", diag)?;
+        }
+        Ok(())
+    }
+}
+
+struct Reporter<'a> {
+    source: Option<&'a SourceMap>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Reporter<'_> {
+    fn push(&mut self, severity: Severity, path: String, message: String) {
+        let location = self.source.and_then(|map| map.locate(&path));
+        self.diagnostics.push(Diagnostic {
+            severity,
+            message,
+            path,
+            location,
+        });
+    }
+
+    fn error(&mut self, path: String, message: String) {
+        self.push(Severity::Error, path, message);
+    }
+
+    fn warning(&mut self, path: String, message: String) {
+        self.push(Severity::Warning, path, message);
+    }
+}
+
+/// Runs every check against `scenario`. When `source` is given, diagnostics
+/// carry the YAML line/column of the element they refer to.
+pub fn analyze(scenario: &Scenario, source: Option<&SourceMap>) -> ValidationReport {
+    let mut reporter = Reporter {
+        source,
+        diagnostics: Vec::new(),
+    };
+
+    check_definitions(scenario, &mut reporter);
+    check_ignored_settings(scenario, &mut reporter);
+    check_iteration_limits(scenario, &mut reporter);
+    check_load_model(scenario, &mut reporter);
+    check_concurrency(scenario, &mut reporter);
+    check_think_times(scenario, &mut reporter);
+
+    // Paths are reported against the first declaration of each node.
+    let mut node_index: HashMap<&str, usize> = HashMap::new();
+    for (idx, node) in scenario.workflows.nodes.iter().enumerate() {
+        node_index.entry(node.id.as_str()).or_insert(idx);
+    }
+
+    match WbsTree::build(scenario) {
+        Ok(tree) => check_graph(&tree, &node_index, &mut reporter),
+        Err(_) => {
+            // Malformed conditions were already reported per edge above.
+        }
+    }
+
+    ValidationReport {
+        diagnostics: reporter.diagnostics,
+    }
+}
+
+/// Diagnostics for keys that the DSL does not know about at all.
+pub fn unknown_field_diagnostics(
+    paths: &[String],
+    severity: Severity,
+    source: Option<&SourceMap>,
+) -> Vec<Diagnostic> {
+    let mut reporter = Reporter {
+        source,
+        diagnostics: Vec::new(),
+    };
+    for path in paths {
+        reporter.push(
+            severity,
+            path.clone(),
+            format!("unknown field `{}` is ignored", path),
+        );
+    }
+    reporter.diagnostics
+}
+
+/// Warns about settings that parse fine but the engine does not act on yet.
+fn check_ignored_settings(scenario: &Scenario, reporter: &mut Reporter<'_>) {
+    for (idx, pool) in scenario.workbook.ip_pools.iter().enumerate() {
+        if pool.allocation_strategy.is_some() {
+            reporter.warning(
+                format!("workbook.ip_pools[{}].allocation_strategy", idx),
+                format!(
+                    "ip pool `{}`: allocation_strategy is not honoured; addresses are allocated sequentially",
+                    pool.id
+                ),
+            );
+        }
+    }
+
+    for (idx, action) in scenario.actions.actions.iter().enumerate() {
+        for key in action.with.keys() {
+            if !SUPPORTED_WITH_KEYS.contains(&key.as_str()) {
+                reporter.warning(
+                    format!("actions.actions[{}].with.{}", idx, key),
+                    format!(
+                        "action `{}`: `with.{}` is not supported by the action components and is ignored",
+                        action.id, key
+                    ),
+                );
+            }
+        }
+        for (export_idx, export) in action.export.iter().enumerate() {
+            let path = format!("actions.actions[{}].export[{}]", idx, export_idx);
+            if export.export_type != "assert" {
+                let assert_keys = [
+                    ("status", export.status.is_some()),
+                    ("contains", export.contains.is_some()),
+                    ("equals", export.equals.is_some()),
+                    ("header", export.header.is_some()),
+                    ("max_ms", export.max_ms.is_some()),
+                ];
+                for (key, _) in assert_keys.iter().filter(|(_, set)| *set) {
+                    reporter.warning(
+                        format!("{}.{}", path, key),
+                        format!(
+                            "action `{}`: `{}` only applies to `type: assert` exports and is ignored",
+                            action.id, key
+                        ),
+                    );
+                }
+            }
+            if export.mode != ExportMode::Overwrite && export.scope.as_deref() != Some("workbook") {
+                reporter.warning(
+                    format!("{}.mode", path),
+                    format!(
+                        "action `{}`: `mode` only applies to `scope: workbook` exports and is ignored",
+                        action.id
+                    ),
+                );
+            }
+        }
+    }
+
+    let Some(load) = &scenario.load else {
+        return;
+    };
+
+    for (idx, phase) in load.ramp_up.phases.iter().enumerate() {
+        if phase.ip_pool_override.is_some() {
+            reporter.warning(
+                format!("load.ramp_up.phases[{}].ip_pool_override", idx),
+                "ip_pool_override is not honoured; users always bind from `ip_binding.pool_id`"
+                    .to_string(),
+            );
+        }
+    }
+
+    if load.user_resources.ip_binding.enabled {
+        reporter.warning(
+            "load.user_resources.ip_binding.release_on".to_string(),
+            "release_on is not honoured; release timing follows `strategy`".to_string(),
+        );
+    }
+}
+
+/// Iteration limits must allow at least one action; `max_visits` is only
+/// checked on action nodes.
+fn check_iteration_limits(scenario: &Scenario, reporter: &mut Reporter<'_>) {
+    for (idx, node) in scenario.workflows.nodes.iter().enumerate() {
+        let Some(max_visits) = node.max_visits else {
+            continue;
+        };
+        if node.node_type != WorkflowNodeType::Action {
+            reporter.warning(
+                format!("{}.max_visits", node_path(idx)),
+                format!(
+                    "max_visits of node `{}` is ignored; only action nodes are counted",
+                    node.id
+                ),
+            );
+        } else if max_visits == 0 {
+            reporter.error(
+                format!("{}.max_visits", node_path(idx)),
+                format!("max_visits of node `{}` must be at least 1", node.id),
+            );
+        }
+    }
+
+    if let Some(load) = &scenario.load
+        && load.user_lifetime.max_steps_per_iteration == Some(0)
+    {
+        reporter.error(
+            "load.user_lifetime.max_steps_per_iteration".to_string(),
+            "max_steps_per_iteration must be at least 1".to_string(),
+        );
+    }
+}
+
+/// A concurrency cap of zero would queue every user forever.
+fn check_concurrency(scenario: &Scenario, reporter: &mut Reporter<'_>) {
+    if let Some(load) = &scenario.load
+        && let Some(concurrency) = &load.concurrency
+        && concurrency.max_concurrent_users == 0
+    {
+        reporter.error(
+            "load.concurrency.max_concurrent_users".to_string(),
+            "max_concurrent_users must be at least 1".to_string(),
+        );
+    }
+}
+
+/// Only action nodes pause after running.
+fn check_think_times(scenario: &Scenario, reporter: &mut Reporter<'_>) {
+    for (idx, node) in scenario.workflows.nodes.iter().enumerate() {
+        if node.think_time.is_some() && node.node_type != WorkflowNodeType::Action {
+            reporter.warning(
+                format!("{}.think_time", node_path(idx)),
+                format!(
+                    "think_time of node `{}` is ignored; only action nodes pause",
+                    node.id
+                ),
+            );
+        }
+    }
+}
+
+/// A load section spawns users in one way only: `ramp_up` phases or `stages`
+/// (closed model), or at an `arrival_rate` (open model).
+fn check_load_model(scenario: &Scenario, reporter: &mut Reporter<'_>) {
+    let Some(load) = &scenario.load else {
+        return;
+    };
+    if !load.stages.is_empty() {
+        if load.arrival_rate.is_some() || !load.ramp_up.phases.is_empty() {
+            reporter.error(
+                "load.stages".to_string(),
+                "stages cannot be combined with ramp_up phases or arrival_rate".to_string(),
+            );
+        }
+        return;
+    }
+    match (&load.arrival_rate, load.ramp_up.phases.is_empty()) {
+        (Some(_), false) => reporter.error(
+            "load.arrival_rate".to_string(),
+            "arrival_rate cannot be combined with ramp_up phases".to_string(),
+        ),
+        (Some(_), true) if load.user_lifetime.iterations != 1 => reporter.warning(
+            "load.user_lifetime.iterations".to_string(),
+            "iterations is ignored with arrival_rate; each arrival runs one iteration".to_string(),
+        ),
+        (None, true) => reporter.warning(
+            "load.ramp_up.phases".to_string(),
+            "no ramp_up phases, stages or arrival_rate; the load test spawns no users".to_string(),
+        ),
+        _ => {}
+    }
+}
+
+fn node_path(idx: usize) -> String {
+    format!("workflows.nodes[{}]", idx)
+}
+
+fn edge_path(node_idx: usize, edge_idx: usize) -> String {
+    format!("workflows.nodes[{}].edges[{}]", node_idx, edge_idx)
+}
+
+fn check_definitions(scenario: &Scenario, reporter: &mut Reporter<'_>) {
+    for (idx, entry) in scenario.include.iter().enumerate() {
+        reporter.error(
+            format!("include[{}]", idx),
+            format!(
+                "include `{}` is not resolved; load the scenario from its file",
+                entry
+            ),
+        );
+    }
+
+    for (name, def) in &scenario.secrets {
+        if def.env.is_some() == def.file.is_some() {
+            reporter.error(
+                format!("secrets.{}", name),
+                format!("secret `{}`: set exactly one of `env` and `file`", name),
+            );
+        }
+        if scenario.variables.contains_key(name) {
+            reporter.error(
+                format!("secrets.{}", name),
+                format!("secret `{}` is also declared under `variables`", name),
+            );
+        }
+    }
+
+    let mut action_ids = HashSet::new();
+    for (idx, action) in scenario.actions.actions.iter().enumerate() {
+        if !action_ids.insert(action.id.as_str()) {
+            reporter.error(
+                format!("actions.actions[{}].id", idx),
+                format!("duplicate action id `{}`", action.id),
+            );
+        }
+        for (export_idx, export) in action.export.iter().enumerate() {
+            let compiled = Export::compile(&action.id, export)
+                .and_then(|_| Assertion::compile(&action.id, export));
+            if let Err(err) = compiled {
+                reporter.error(
+                    format!("actions.actions[{}].export[{}]", idx, export_idx),
+                    err.to_string(),
+                );
+            }
+        }
+    }
+
+    let mut node_ids = HashSet::new();
+    for (idx, node) in scenario.workflows.nodes.iter().enumerate() {
+        if !node_ids.insert(node.id.as_str()) {
+            reporter.error(
+                format!("{}.id", node_path(idx)),
+                format!("duplicate node id `{}`", node.id),
+            );
+        }
+    }
+
+    let node_types: HashMap<&str, &WorkflowNodeType> = scenario
+        .workflows
+        .nodes
+        .iter()
+        .map(|node| (node.id.as_str(), &node.node_type))
+        .collect();
+    let parents: HashMap<&str, &str> = scenario
+        .workflows
+        .nodes
+        .iter()
+        .filter_map(|node| Some((node.id.as_str(), node.parent.as_deref()?)))
+        .collect();
+
+    for (idx, node) in scenario.workflows.nodes.iter().enumerate() {
+        check_hierarchy(idx, node, &node_types, &parents, reporter);
+
+        match (&node.node_type, &node.action) {
+            (WorkflowNodeType::Group, Some(_)) => reporter.error(
+                format!("{}.action", node_path(idx)),
+                format!("group node `{}` cannot reference an action", node.id),
+            ),
+            (WorkflowNodeType::Action, None) => reporter.error(
+                node_path(idx),
+                format!("action node `{}` does not reference an action", node.id),
+            ),
+            (_, Some(action_id)) if !action_ids.contains(action_id.as_str()) => reporter.error(
+                format!("{}.action", node_path(idx)),
+                format!(
+                    "unknown action `{}` referenced by workflow node `{}`",
+                    action_id, node.id
+                ),
+            ),
+            _ => {}
+        }
+
+        for (edge_idx, edge) in node.edges.iter().enumerate() {
+            if !node_ids.contains(edge.to.as_str()) {
+                reporter.error(
+                    format!("{}.to", edge_path(idx, edge_idx)),
+                    format!("edge from `{}` targets unknown node `{}`", node.id, edge.to),
+                );
+            } else if node_types.get(edge.to.as_str()) == Some(&&WorkflowNodeType::Group) {
+                reporter.error(
+                    format!("{}.to", edge_path(idx, edge_idx)),
+                    format!("edge from `{}` targets group node `{}`", node.id, edge.to),
+                );
+            }
+            if let Err(err) = edge.compile_condition(&node.id) {
+                reporter.error(
+                    format!("{}.trigger.condition", edge_path(idx, edge_idx)),
+                    err.to_string(),
+                );
+            }
+        }
+    }
+}
+
+/// `parent` must name a group, the group chain must not loop, and `weight`
+/// must be a non-negative number. Groups never carry edges.
+fn check_hierarchy(
+    idx: usize,
+    node: &WorkflowNode,
+    node_types: &HashMap<&str, &WorkflowNodeType>,
+    parents: &HashMap<&str, &str>,
+    reporter: &mut Reporter<'_>,
+) {
+    if node.node_type == WorkflowNodeType::Group && !node.edges.is_empty() {
+        reporter.error(
+            format!("{}.edges", node_path(idx)),
+            format!("group node `{}` cannot have edges", node.id),
+        );
+    }
+
+    if let Some(weight) = node.weight
+        && !(weight.is_finite() && weight >= 0.0)
+    {
+        reporter.error(
+            format!("{}.weight", node_path(idx)),
+            format!("weight of node `{}` must be a non-negative number", node.id),
+        );
+    }
+
+    let Some(parent) = node.parent.as_deref() else {
+        return;
+    };
+    match node_types.get(parent) {
+        None => reporter.error(
+            format!("{}.parent", node_path(idx)),
+            format!("node `{}` has unknown parent `{}`", node.id, parent),
+        ),
+        Some(WorkflowNodeType::Group) => {
+            let mut seen = HashSet::from([node.id.as_str()]);
+            let mut current = Some(parent);
+            while let Some(id) = current {
+                if !seen.insert(id) {
+                    if id == node.id {
+                        reporter.error(
+                            format!("{}.parent", node_path(idx)),
+                            format!("group `{}` is nested in itself", node.id),
+                        );
+                    }
+                    break;
+                }
+                current = parents.get(id).copied();
+            }
+        }
+        Some(_) => reporter.error(
+            format!("{}.parent", node_path(idx)),
+            format!(
+                "parent `{}` of node `{}` is not a group node",
+                parent, node.id
+            ),
+        ),
+    }
+}
+
+fn check_graph(tree: &WbsTree, node_index: &HashMap<&str, usize>, reporter: &mut Reporter<'_>) {
+    let path_of = |task_id: &str| {
+        node_index
+            .get(task_id)
+            .map(|idx| node_path(*idx))
+            .unwrap_or_else(|| "workflows.nodes".to_string())
+    };
+
+    let successors = |task_id: &str| -> Vec<&str> {
+        tree.get_task(task_id)
+            .map(|task| {
+                task.outgoing
+                    .iter()
+                    .map(|edge| edge.target.as_str())
+                    .filter(|target| tree.tasks.contains_key(*target))
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    for task in tree.tasks.values() {
+        if task.kind == WbsTaskKind::Action && task.outgoing.is_empty() {
+            reporter.error(
+                path_of(&task.id),
+                format!(
+                    "action node `{}` has no outgoing edges; execution cannot continue after it",
+                    task.id
+                ),
+            );
+        }
+
+        let node_idx = node_index.get(task.id.as_str()).copied().unwrap_or(0);
+        let mut shadowed_by: Option<usize> = None;
+        for (edge_idx, edge) in task.outgoing.iter().enumerate() {
+            if let Some(always_idx) = shadowed_by {
+                reporter.warning(
+                    edge_path(node_idx, edge_idx),
+                    format!(
+                        "edge `{}` -> `{}` can never fire: edge #{} is unconditional",
+                        task.id,
+                        edge.target,
+                        always_idx + 1
+                    ),
+                );
+            } else if edge
+                .condition
+                .as_ref()
+                .is_none_or(|condition| condition.is_always_true())
+            {
+                shadowed_by = Some(edge_idx);
+            }
+        }
+    }
+
+    if !tree.tasks.contains_key(START_NODE) {
+        reporter.error(
+            "workflows.nodes".to_string(),
+            format!("workflow has no `{}` node", START_NODE),
+        );
+        return;
+    }
+
+    let reachable = reachable_from(START_NODE, &successors);
+    for task in tree.tasks.values() {
+        if task.kind != WbsTaskKind::Group && !reachable.contains(task.id.as_str()) {
+            reporter.warning(
+                path_of(&task.id),
+                format!("node `{}` is unreachable from `{}`", task.id, START_NODE),
+            );
+        }
+    }
+
+    // Nodes from which some `end` node is reachable (reverse BFS).
+    let mut predecessors: HashMap<&str, Vec<&str>> = HashMap::new();
+    for task in tree.tasks.values() {
+        for target in successors(&task.id) {
+            predecessors
+                .entry(target)
+                .or_default()
+                .push(task.id.as_str());
+        }
+    }
+    let mut reaches_end: HashSet<&str> = HashSet::new();
+    let mut queue: VecDeque<&str> = tree
+        .tasks
+        .values()
+        .filter(|task| task.kind == WbsTaskKind::End)
+        .map(|task| task.id.as_str())
+        .collect();
+    while let Some(id) = queue.pop_front() {
+        if reaches_end.insert(id)
+            && let Some(preds) = predecessors.get(id)
+        {
+            queue.extend(preds.iter().copied());
+        }
+    }
+
+    let mut reported: HashSet<&str> = HashSet::new();
+    for task in tree.tasks.values() {
+        let id = task.id.as_str();
+        if !reachable.contains(id) || reaches_end.contains(id) || reported.contains(id) {
+            continue;
+        }
+        let forward = reachable_from(id, &successors);
+        let cycle: Vec<&str> = tree
+            .tasks
+            .keys()
+            .map(String::as_str)
+            .filter(|other| {
+                forward.contains(other)
+                    && reachable_from(other, &successors).contains(id)
+                    && (*other != id || successors(id).contains(&id))
+            })
+            .collect();
+        if cycle.is_empty() {
+            continue;
+        }
+        reported.extend(cycle.iter().copied());
+        reporter.error(
+            path_of(id),
+            format!(
+                "cycle [{}] has no path to an `end` node",
+                cycle.join(" -> ")
+            ),
+        );
+    }
+}
+
+fn reachable_from<'a, F>(start: &'a str, successors: &F) -> HashSet<&'a str>
+where
+    F: Fn(&str) -> Vec<&'a str>,
+{
+    let mut seen = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(id) = queue.pop_front() {
+        if seen.insert(id) {
+            queue.extend(successors(id));
+        }
+    }
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze_yaml(yaml: &str) -> ValidationReport {
+        let scenario = Scenario::from_yaml_str(yaml).expect("parse scenario");
+        scenario.analyze_source(yaml)
+    }
+
+    fn messages(report: &ValidationReport, severity: Severity) -> Vec<String> {
+        report
+            .diagnostics
+            .iter()
+            .filter(|diag| diag.severity == severity)
+            .map(|diag| diag.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn sample_scenarios_have_no_errors() {
+        for sample in [
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../res/http_scenario.yaml"
+            )),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../res/load_test_advanced.yaml"
+            )),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../res/simple_scenario.yaml"
+            )),
+        ] {
+            let report = analyze_yaml(sample);
+            assert!(!report.has_errors(), "unexpected errors:\n{report}");
+        }
+    }
+
+    #[test]
+    fn reports_all_problems_with_locations() {
+        let yaml = "\
+version: \"1.0\"
+name: broken
+actions:
+  actions:
+    - id: ping
+      call: get
+    - id: ping
+      call: get
+workflows:
+  nodes:
+    - id: begin
+      type: action
+      action: ping
+      edges:
+        - to: loop-a
+        - to: end
+    - id: loop-a
+      type: action
+      action: ping
+      edges:
+        - to: loop-b
+    - id: loop-b
+      type: action
+      action: ping
+      edges:
+        - to: loop-a
+    - id: dangling
+      type: action
+      action: ping
+    - id: end
+      type: end
+    - id: end
+      type: end
+";
+        let report = analyze_yaml(yaml);
+        let errors = messages(&report, Severity::Error);
+        let warnings = messages(&report, Severity::Warning);
+
+        assert!(errors.iter().any(|m| m == "duplicate action id `ping`"));
+        assert!(errors.iter().any(|m| m == "duplicate node id `end`"));
+        assert!(errors.iter().any(|m| m == "workflow has no `start` node"));
+        assert!(
+            errors
+                .iter()
+                .any(|m| m.contains("`dangling` has no outgoing edges"))
+        );
+        assert!(!errors.iter().any(|m| m.contains("cycle")));
+        assert!(
+            warnings
+                .iter()
+                .any(|m| m.contains("`begin` -> `end` can never fire"))
+        );
+
+        let duplicate = report
+            .errors()
+            .find(|diag| diag.message.starts_with("duplicate action"))
+            .expect("duplicate diagnostic");
+        assert_eq!(duplicate.path, "actions.actions[1].id");
+        assert_eq!(
+            duplicate.location,
+            Some(SourceLocation {
+                line: 7,
+                column: 7,
+                file: None
+            })
+        );
+
+        let shadowed = report.warnings().next().expect("shadowed edge warning");
+        assert_eq!(shadowed.location.as_ref().map(|loc| loc.line), Some(16));
+    }
+
+    #[test]
+    fn reports_unreachable_nodes_and_endless_cycles() {
+        let yaml = "\
+version: \"1.0\"
+name: cyclic
+actions:
+  actions:
+    - id: ping
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: ping
+      edges:
+        - to: retry
+          trigger:
+            condition: \"{{ping.status_code != 200}}\"
+        - to: end
+    - id: retry
+      type: action
+      action: ping
+      edges:
+        - to: retry
+    - id: orphan
+      type: action
+      action: ping
+      edges:
+        - to: end
+    - id: end
+      type: end
+";
+        let report = analyze_yaml(yaml);
+        let errors = messages(&report, Severity::Error);
+        let warnings = messages(&report, Severity::Warning);
+
+        assert!(
+            errors
+                .iter()
+                .any(|m| m == "cycle [retry] has no path to an `end` node")
+        );
+        assert!(
+            warnings
+                .iter()
+                .any(|m| m == "node `orphan` is unreachable from `start`")
+        );
+
+        let cycle = report
+            .errors()
+            .find(|diag| diag.message.starts_with("cycle"))
+            .expect("cycle diagnostic");
+        assert_eq!(cycle.path, "workflows.nodes[1]");
+        assert_eq!(cycle.location.as_ref().map(|loc| loc.line), Some(17));
+    }
+
+    #[test]
+    fn reports_invalid_exports() {
+        let yaml = "\
+version: \"1.0\"
+name: exports
+actions:
+  actions:
+    - id: ping
+      call: get
+      export:
+        - type: status
+          name: code
+        - type: jsonpath
+          name: token
+        - type: xpath
+          name: id
+          expr: //id
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: ping
+      edges:
+        - to: end
+    - id: end
+      type: end
+";
+        let report = analyze_yaml(yaml);
+        let paths: Vec<_> = report.errors().map(|diag| diag.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "actions.actions[0].export[1]",
+                "actions.actions[0].export[2]"
+            ]
+        );
+        let unknown = report.errors().nth(1).expect("unknown type");
+        assert!(unknown.message.contains("unknown export type `xpath`"));
+        assert_eq!(unknown.location.as_ref().map(|loc| loc.line), Some(12));
+    }
+
+    #[test]
+    fn reports_invalid_secrets() {
+        let yaml = "\
+version: \"1.0\"
+name: secrets
+variables:
+  api_token: x
+secrets:
+  api_token:
+    env: API_TOKEN
+  db_password:
+    env: DB_PASSWORD
+    file: db_password.txt
+  empty: {}
+";
+        let report = analyze_yaml(yaml);
+        let messages: Vec<_> = report
+            .errors()
+            .filter(|diag| diag.path.starts_with("secrets."))
+            .map(|diag| diag.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "secret `api_token` is also declared under `variables`",
+                "secret `db_password`: set exactly one of `env` and `file`",
+                "secret `empty`: set exactly one of `env` and `file`",
+            ]
+        );
+        assert_eq!(
+            report
+                .errors()
+                .find(|diag| diag.path == "secrets.db_password")
+                .and_then(|diag| diag.location.as_ref())
+                .map(|loc| loc.line),
+            Some(8)
+        );
+    }
+
+    #[test]
+    fn reports_invalid_groups() {
+        let yaml = "\
+version: \"1.0\"
+name: groups
+actions:
+  actions:
+    - id: a
+      call: get
+workflows:
+  nodes:
+    - id: outer
+      type: group
+      parent: inner
+    - id: inner
+      type: group
+      parent: outer
+      action: a
+    - id: start
+      type: action
+      action: a
+      parent: end
+      weight: -1
+      edges:
+        - to: outer
+        - to: end
+    - id: end
+      type: end
+      parent: missing
+";
+        let report = analyze_yaml(yaml);
+        let messages: Vec<_> = report.errors().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "group `outer` is nested in itself",
+                "group `inner` is nested in itself",
+                "group node `inner` cannot reference an action",
+                "weight of node `start` must be a non-negative number",
+                "parent `end` of node `start` is not a group node",
+                "edge from `start` targets group node `outer`",
+                "node `end` has unknown parent `missing`",
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_invalid_iteration_limits() {
+        let yaml = "\
+version: \"1.0\"
+name: limits
+load:
+  ramp_up:
+    phases:
+      - at_second: 0
+        spawn_users: 1
+  user_lifetime:
+    mode: loop
+    iterations: 1
+    think_time: 0s
+    max_steps_per_iteration: 0
+  user_resources:
+    ip_binding:
+      enabled: false
+      pool_id: none
+      strategy: per_user
+      release_on: user_exit
+actions:
+  actions:
+    - id: a
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: a
+      max_visits: 0
+      edges:
+        - to: end
+    - id: end
+      type: end
+      max_visits: 2
+";
+        let report = analyze_yaml(yaml);
+        assert_eq!(
+            messages(&report, Severity::Error),
+            vec![
+                "max_visits of node `start` must be at least 1",
+                "max_steps_per_iteration must be at least 1",
+            ]
+        );
+        assert_eq!(
+            messages(&report, Severity::Warning),
+            vec!["max_visits of node `end` is ignored; only action nodes are counted"]
+        );
+    }
+
+    #[test]
+    fn reports_conflicting_load_models() {
+        let yaml = |load: &str| {
+            format!(
+                "\
+version: \"1.0\"
+name: arrivals
+load:
+{load}
+  user_lifetime:
+    mode: once
+    iterations: 3
+    think_time: 0s
+  user_resources:
+    ip_binding:
+      enabled: false
+      pool_id: none
+      strategy: per_user
+      release_on: user_exit
+actions:
+  actions:
+    - id: a
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: a
+      edges:
+        - to: end
+    - id: end
+      type: end
+"
+            )
+        };
+        let arrival = "  arrival_rate:\n    rate: 10/s\n    duration: 1m";
+        let phases = "  ramp_up:\n    phases:\n      - at_second: 0\n        spawn_users: 1";
+
+        let report = analyze_yaml(&yaml(&format!("{arrival}\n{phases}")));
+        assert_eq!(
+            messages(&report, Severity::Error),
+            vec!["arrival_rate cannot be combined with ramp_up phases"]
+        );
+
+        let report = analyze_yaml(&yaml(arrival));
+        assert!(!report.has_errors());
+        assert_eq!(
+            messages(&report, Severity::Warning),
+            vec!["iterations is ignored with arrival_rate; each arrival runs one iteration"]
+        );
+
+        let report = analyze_yaml(&yaml("  ramp_up:\n    phases: []"));
+        assert_eq!(
+            messages(&report, Severity::Warning),
+            vec!["no ramp_up phases, stages or arrival_rate; the load test spawns no users"]
+        );
+
+        let stages = "  stages:\n    - target_users: 2\n      duration: 10s";
+        assert!(analyze_yaml(&yaml(stages)).diagnostics.is_empty());
+        let report = analyze_yaml(&yaml(&format!("{stages}\n{phases}")));
+        assert_eq!(
+            messages(&report, Severity::Error),
+            vec!["stages cannot be combined with ramp_up phases or arrival_rate"]
+        );
+    }
+
+    #[test]
+    fn reports_zero_concurrency_cap() {
+        let yaml = "\
+version: \"1.0\"
+name: capped
+load:
+  ramp_up:
+    phases:
+      - at_second: 0
+        spawn_users: 5
+  user_lifetime:
+    mode: once
+    iterations: 1
+    think_time: 0s
+  user_resources:
+    ip_binding:
+      enabled: false
+      pool_id: none
+      strategy: per_user
+      release_on: user_exit
+  concurrency:
+    max_concurrent_users: 0
+    spawn_rate_limit: 10/s
+    action_rate_limit: 100/s
+actions:
+  actions:
+    - id: a
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: a
+      edges:
+        - to: end
+    - id: end
+      type: end
+";
+        let report = analyze_yaml(yaml);
+        assert_eq!(
+            messages(&report, Severity::Error),
+            vec!["max_concurrent_users must be at least 1"]
+        );
+        assert!(report.warnings().next().is_none());
+    }
+
+    #[test]
+    fn warns_about_think_time_on_end_nodes() {
+        let yaml = "\
+version: \"1.0\"
+name: paced
+load:
+  ramp_up:
+    phases:
+      - at_second: 0
+        spawn_users: 1
+  user_lifetime:
+    mode: loop
+    iterations: 3
+    think_time: 1s..3s
+    pacing: 10s
+  user_resources:
+    ip_binding:
+      enabled: false
+      pool_id: none
+      strategy: per_user
+      release_on: user_exit
+actions:
+  actions:
+    - id: a
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: a
+      think_time:
+        distribution: normal
+        mean: 2s
+        stddev: 500ms
+      edges:
+        - to: end
+    - id: end
+      type: end
+      think_time: 1s
+";
+        let report = analyze_yaml(yaml);
+        assert!(messages(&report, Severity::Error).is_empty());
+        assert_eq!(
+            messages(&report, Severity::Warning),
+            vec!["think_time of node `end` is ignored; only action nodes pause"]
+        );
+        Scenario::from_yaml_str_with_mode(yaml, crate::dsl::ParseMode::Strict)
+            .expect("think times are known fields");
+    }
+}
@@ -0,0 +1,772 @@
+/// Workflow Edge Condition Language
+///
+/// Edge conditions are compiled once into an [`Expr`] tree and evaluated
+/// against the variables of the running workflow. Supported syntax:
+/// - Literals: numbers (`200`, `-1.5`), strings (`"ok"`, `'ok'`), `true`,
+///   `false`, `null` and lists (`[200, 201]`)
+/// - Variables: `health-check.status_code` or `{{health-check.status_code}}`
+/// - Comparison: `==`, `!=`, `<`, `<=`, `>`, `>=`
+/// - Membership: `x in [1, 2]`, `body contains "id"`
+/// - Regex match: `body matches "^ok"` (or `body =~ "^ok"`)
+/// - Logic: `!`, `&&`, `||` and parentheses
+///
+/// `{{ ... }}` behaves like a pair of parentheses, so both
+/// `{{a.status == 200}}` and `{{a.status}} == 200` are accepted.
+use std::fmt;
+
+use indexmap::IndexMap;
+use regex::Regex;
+use thiserror::Error;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message} at offset {offset}")]
+pub struct ConditionError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl ConditionError {
+    fn new(offset: usize, message: impl Into<String>) -> Self {
+        Self {
+            offset,
+            message: message.into(),
+        }
+    }
+}
+
+/// Source of variable values used when evaluating a condition.
+pub trait VariableResolver {
+    fn resolve(&self, name: &str) -> Option<ConditionValue>;
+}
+
+impl VariableResolver for IndexMap<String, String> {
+    fn resolve(&self, name: &str) -> Option<ConditionValue> {
+        self.get(name).cloned().map(ConditionValue::String)
+    }
+}
+
+impl<F> VariableResolver for F
+where
+    F: Fn(&str) -> Option<ConditionValue>,
+{
+    fn resolve(&self, name: &str) -> Option<ConditionValue> {
+        self(name)
+    }
+}
+
+/// A compiled edge condition together with its original source text.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    source: String,
+    expr: Expr,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, ConditionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.len(),
+        };
+        let expr = parser.parse_or()?;
+        if let Some(token) = parser.peek() {
+            return Err(ConditionError::new(
+                token.offset,
+                format!("unexpected token `{}`", token.kind),
+            ));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    /// True for constant conditions such as `"true"` that never block an edge.
+    pub fn is_always_true(&self) -> bool {
+        matches!(&self.expr, Expr::Literal(value) if value.is_truthy())
+    }
+
+    pub fn evaluate<R>(&self, vars: &R) -> bool
+    where
+        R: VariableResolver + ?Sized,
+    {
+        self.expr.eval(vars).is_truthy()
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Literal(ConditionValue),
+    Var(String),
+    List(Vec<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare {
+        op: CompareOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    In {
+        value: Box<Expr>,
+        list: Box<Expr>,
+    },
+    Contains {
+        haystack: Box<Expr>,
+        needle: Box<Expr>,
+    },
+    Matches {
+        value: Box<Expr>,
+        pattern: Regex,
+    },
+}
+
+impl Expr {
+    pub fn eval<R>(&self, vars: &R) -> ConditionValue
+    where
+        R: VariableResolver + ?Sized,
+    {
+        match self {
+            Expr::Literal(value) => value.clone(),
+            Expr::Var(name) => vars.resolve(name).unwrap_or(ConditionValue::Null),
+            Expr::List(items) => {
+                ConditionValue::List(items.iter().map(|item| item.eval(vars)).collect())
+            }
+            Expr::Not(inner) => ConditionValue::Bool(!inner.eval(vars).is_truthy()),
+            Expr::And(left, right) => {
+                ConditionValue::Bool(left.eval(vars).is_truthy() && right.eval(vars).is_truthy())
+            }
+            Expr::Or(left, right) => {
+                ConditionValue::Bool(left.eval(vars).is_truthy() || right.eval(vars).is_truthy())
+            }
+            Expr::Compare { op, left, right } => {
+                let left = left.eval(vars);
+                let right = right.eval(vars);
+                ConditionValue::Bool(left.compare(*op, &right))
+            }
+            Expr::In { value, list } => {
+                let value = value.eval(vars);
+                let found = match list.eval(vars) {
+                    ConditionValue::List(items) => items.iter().any(|item| value.loose_eq(item)),
+                    ConditionValue::Null => false,
+                    other => other.to_string().contains(&value.to_string()),
+                };
+                ConditionValue::Bool(found)
+            }
+            Expr::Contains { haystack, needle } => {
+                let needle = needle.eval(vars);
+                let found = match haystack.eval(vars) {
+                    ConditionValue::List(items) => items.iter().any(|item| item.loose_eq(&needle)),
+                    ConditionValue::Null => false,
+                    other => other.to_string().contains(&needle.to_string()),
+                };
+                ConditionValue::Bool(found)
+            }
+            Expr::Matches { value, pattern } => match value.eval(vars) {
+                ConditionValue::Null => ConditionValue::Bool(false),
+                other => ConditionValue::Bool(pattern.is_match(&other.to_string())),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionValue {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    List(Vec<ConditionValue>),
+}
+
+impl ConditionValue {
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            ConditionValue::Null => false,
+            ConditionValue::Bool(b) => *b,
+            ConditionValue::Number(n) => *n != 0.0,
+            ConditionValue::String(s) => !s.is_empty() && s != "false" && s != "0",
+            ConditionValue::List(items) => !items.is_empty(),
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            ConditionValue::Number(n) => Some(*n),
+            ConditionValue::String(s) => s.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConditionValue::Bool(b) => Some(*b),
+            ConditionValue::String(s) => match s.trim() {
+                "true" => Some(true),
+                "false" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Equality that coerces numeric and boolean strings, so values that
+    /// were rendered to text (e.g. `"200"`) still compare equal to literals.
+    fn loose_eq(&self, other: &ConditionValue) -> bool {
+        match (self, other) {
+            (ConditionValue::Null, ConditionValue::Null) => true,
+            (ConditionValue::Null, _) | (_, ConditionValue::Null) => false,
+            (ConditionValue::List(a), ConditionValue::List(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| x.loose_eq(y))
+            }
+            (ConditionValue::Number(_), _) | (_, ConditionValue::Number(_)) => {
+                match (self.as_number(), other.as_number()) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => false,
+                }
+            }
+            (ConditionValue::Bool(_), _) | (_, ConditionValue::Bool(_)) => {
+                match (self.as_bool(), other.as_bool()) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => false,
+                }
+            }
+            (ConditionValue::String(a), ConditionValue::String(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    fn compare(&self, op: CompareOp, other: &ConditionValue) -> bool {
+        match op {
+            CompareOp::Eq => self.loose_eq(other),
+            CompareOp::Ne => !self.loose_eq(other),
+            _ => {
+                let ordering = match (self, other) {
+                    (ConditionValue::String(a), ConditionValue::String(b))
+                        if self.as_number().is_none() || other.as_number().is_none() =>
+                    {
+                        Some(a.cmp(b))
+                    }
+                    _ => match (self.as_number(), other.as_number()) {
+                        (Some(a), Some(b)) => a.partial_cmp(&b),
+                        _ => None,
+                    },
+                };
+                let Some(ordering) = ordering else {
+                    return false;
+                };
+                match op {
+                    CompareOp::Lt => ordering.is_lt(),
+                    CompareOp::Le => ordering.is_le(),
+                    CompareOp::Gt => ordering.is_gt(),
+                    CompareOp::Ge => ordering.is_ge(),
+                    CompareOp::Eq | CompareOp::Ne => unreachable!(),
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for ConditionValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionValue::Null => Ok(()),
+            ConditionValue::Bool(b) => write!(f, "{}", b),
+            ConditionValue::Number(n) if n.fract() == 0.0 && n.abs() < 1e15 => {
+                write!(f, "{}", *n as i64)
+            }
+            ConditionValue::Number(n) => write!(f, "{}", n),
+            ConditionValue::String(s) => f.write_str(s),
+            ConditionValue::List(items) => {
+                f.write_str("[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Tokenizer
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Str(String),
+    Ident(String),
+    True,
+    False,
+    Null,
+    In,
+    Contains,
+    Matches,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+    Not,
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::Number(n) => write!(f, "{}", n),
+            TokenKind::Str(s) => write!(f, "{:?}", s),
+            TokenKind::Ident(name) => f.write_str(name),
+            TokenKind::True => f.write_str("true"),
+            TokenKind::False => f.write_str("false"),
+            TokenKind::Null => f.write_str("null"),
+            TokenKind::In => f.write_str("in"),
+            TokenKind::Contains => f.write_str("contains"),
+            TokenKind::Matches => f.write_str("matches"),
+            TokenKind::Eq => f.write_str("=="),
+            TokenKind::Ne => f.write_str("!="),
+            TokenKind::Lt => f.write_str("<"),
+            TokenKind::Le => f.write_str("<="),
+            TokenKind::Gt => f.write_str(">"),
+            TokenKind::Ge => f.write_str(">="),
+            TokenKind::And => f.write_str("&&"),
+            TokenKind::Or => f.write_str("||"),
+            TokenKind::Not => f.write_str("!"),
+            TokenKind::LParen => f.write_str("("),
+            TokenKind::RParen => f.write_str(")"),
+            TokenKind::LBracket => f.write_str("["),
+            TokenKind::RBracket => f.write_str("]"),
+            TokenKind::Comma => f.write_str(","),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    offset: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ConditionError> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        let start = pos;
+
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let two = bytes.get(pos..pos + 2);
+        let kind = match two {
+            Some(b"{{") => Some(TokenKind::LParen),
+            Some(b"}}") => Some(TokenKind::RParen),
+            Some(b"==") => Some(TokenKind::Eq),
+            Some(b"!=") => Some(TokenKind::Ne),
+            Some(b"<=") => Some(TokenKind::Le),
+            Some(b">=") => Some(TokenKind::Ge),
+            Some(b"&&") => Some(TokenKind::And),
+            Some(b"||") => Some(TokenKind::Or),
+            Some(b"=~") => Some(TokenKind::Matches),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            tokens.push(Token {
+                kind,
+                offset: start,
+            });
+            pos += 2;
+            continue;
+        }
+
+        let kind = match c {
+            b'<' => Some(TokenKind::Lt),
+            b'>' => Some(TokenKind::Gt),
+            b'!' => Some(TokenKind::Not),
+            b'(' => Some(TokenKind::LParen),
+            b')' => Some(TokenKind::RParen),
+            b'[' => Some(TokenKind::LBracket),
+            b']' => Some(TokenKind::RBracket),
+            b',' => Some(TokenKind::Comma),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            tokens.push(Token {
+                kind,
+                offset: start,
+            });
+            pos += 1;
+            continue;
+        }
+
+        if c == b'"' || c == b'\'' {
+            let (value, next) = read_string(source, pos)?;
+            tokens.push(Token {
+                kind: TokenKind::Str(value),
+                offset: start,
+            });
+            pos = next;
+            continue;
+        }
+
+        let is_negative_number =
+            c == b'-' && bytes.get(pos + 1).is_some_and(|b| b.is_ascii_digit());
+        if c.is_ascii_digit() || is_negative_number {
+            pos += 1;
+            while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+                pos += 1;
+            }
+            let text = &source[start..pos];
+            let number = text
+                .parse::<f64>()
+                .map_err(|_| ConditionError::new(start, format!("invalid number `{}`", text)))?;
+            tokens.push(Token {
+                kind: TokenKind::Number(number),
+                offset: start,
+            });
+            continue;
+        }
+
+        if c.is_ascii_alphabetic() || c == b'_' {
+            while pos < bytes.len()
+                && (bytes[pos].is_ascii_alphanumeric() || matches!(bytes[pos], b'_' | b'-' | b'.'))
+            {
+                pos += 1;
+            }
+            let word = &source[start..pos];
+            let kind = match word {
+                "true" => TokenKind::True,
+                "false" => TokenKind::False,
+                "null" => TokenKind::Null,
+                "in" => TokenKind::In,
+                "contains" => TokenKind::Contains,
+                "matches" => TokenKind::Matches,
+                _ => TokenKind::Ident(word.to_string()),
+            };
+            tokens.push(Token {
+                kind,
+                offset: start,
+            });
+            continue;
+        }
+
+        let ch = source[start..].chars().next().unwrap_or('?');
+        return Err(ConditionError::new(
+            start,
+            format!("unexpected character `{}`", ch),
+        ));
+    }
+
+    Ok(tokens)
+}
+
+fn read_string(source: &str, start: usize) -> Result<(String, usize), ConditionError> {
+    let mut chars = source[start..].char_indices();
+    let (_, quote) = chars.next().expect("caller checked opening quote");
+    let mut value = String::new();
+
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '\\' => match chars.next() {
+                Some((_, 'n')) => value.push('\n'),
+                Some((_, 't')) => value.push('\t'),
+                Some((_, escaped)) => value.push(escaped),
+                None => break,
+            },
+            c if c == quote => return Ok((value, start + idx + c.len_utf8())),
+            c => value.push(c),
+        }
+    }
+
+    Err(ConditionError::new(start, "unterminated string literal"))
+}
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn peek_kind(&self) -> Option<&TokenKind> {
+        self.peek().map(|token| &token.kind)
+    }
+
+    fn offset(&self) -> usize {
+        self.peek().map(|token| token.offset).unwrap_or(self.end)
+    }
+
+    fn advance(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        if token.is_some() {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, kind: &TokenKind) -> bool {
+        if self.peek_kind() == Some(kind) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, kind: TokenKind) -> Result<(), ConditionError> {
+        if self.eat(&kind) {
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("expected `{}`", kind)))
+        }
+    }
+
+    fn unexpected(&self, expectation: &str) -> ConditionError {
+        match self.peek() {
+            Some(token) => ConditionError::new(
+                token.offset,
+                format!("{}, found `{}`", expectation, token.kind),
+            ),
+            None => ConditionError::new(self.end, format!("{}, found end of input", expectation)),
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ConditionError> {
+        let mut left = self.parse_and()?;
+        while self.eat(&TokenKind::Or) {
+            let right = self.parse_and()?;
+            left = Expr::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ConditionError> {
+        let mut left = self.parse_unary()?;
+        while self.eat(&TokenKind::And) {
+            let right = self.parse_unary()?;
+            left = Expr::And(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ConditionError> {
+        if self.eat(&TokenKind::Not) {
+            let inner = self.parse_unary()?;
+            return Ok(Expr::Not(Box::new(inner)));
+        }
+        self.parse_comparison()
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, ConditionError> {
+        let left = self.parse_primary()?;
+
+        let op = match self.peek_kind() {
+            Some(TokenKind::Eq) => CompareOp::Eq,
+            Some(TokenKind::Ne) => CompareOp::Ne,
+            Some(TokenKind::Lt) => CompareOp::Lt,
+            Some(TokenKind::Le) => CompareOp::Le,
+            Some(TokenKind::Gt) => CompareOp::Gt,
+            Some(TokenKind::Ge) => CompareOp::Ge,
+            Some(TokenKind::In) => {
+                self.pos += 1;
+                let list = self.parse_primary()?;
+                return Ok(Expr::In {
+                    value: Box::new(left),
+                    list: Box::new(list),
+                });
+            }
+            Some(TokenKind::Contains) => {
+                self.pos += 1;
+                let needle = self.parse_primary()?;
+                return Ok(Expr::Contains {
+                    haystack: Box::new(left),
+                    needle: Box::new(needle),
+                });
+            }
+            Some(TokenKind::Matches) => {
+                self.pos += 1;
+                let offset = self.offset();
+                let Some(Token {
+                    kind: TokenKind::Str(raw),
+                    ..
+                }) = self.advance()
+                else {
+                    return Err(ConditionError::new(
+                        offset,
+                        "regex pattern must be a string literal",
+                    ));
+                };
+                let pattern = Regex::new(&raw).map_err(|err| {
+                    ConditionError::new(offset, format!("invalid regex `{}`: {}", raw, err))
+                })?;
+                return Ok(Expr::Matches {
+                    value: Box::new(left),
+                    pattern,
+                });
+            }
+            _ => return Ok(left),
+        };
+
+        self.pos += 1;
+        let right = self.parse_primary()?;
+        Ok(Expr::Compare {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        })
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ConditionError> {
+        let Some(token) = self.advance() else {
+            return Err(self.unexpected("expected expression"));
+        };
+
+        match token.kind {
+            TokenKind::Number(n) => Ok(Expr::Literal(ConditionValue::Number(n))),
+            TokenKind::Str(s) => Ok(Expr::Literal(ConditionValue::String(s))),
+            TokenKind::True => Ok(Expr::Literal(ConditionValue::Bool(true))),
+            TokenKind::False => Ok(Expr::Literal(ConditionValue::Bool(false))),
+            TokenKind::Null => Ok(Expr::Literal(ConditionValue::Null)),
+            TokenKind::Ident(name) => Ok(Expr::Var(name)),
+            TokenKind::LParen => {
+                let inner = self.parse_or()?;
+                self.expect(TokenKind::RParen)?;
+                Ok(inner)
+            }
+            TokenKind::LBracket => {
+                let mut items = Vec::new();
+                if !self.eat(&TokenKind::RBracket) {
+                    loop {
+                        items.push(self.parse_primary()?);
+                        if self.eat(&TokenKind::RBracket) {
+                            break;
+                        }
+                        self.expect(TokenKind::Comma)?;
+                    }
+                }
+                Ok(Expr::List(items))
+            }
+            other => Err(ConditionError::new(
+                token.offset,
+                format!("expected expression, found `{}`", other),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> IndexMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    fn eval(source: &str, pairs: &[(&str, &str)]) -> bool {
+        Condition::parse(source)
+            .unwrap_or_else(|err| panic!("parse `{source}`: {err}"))
+            .evaluate(&vars(pairs))
+    }
+
+    #[test]
+    fn evaluates_comparisons_inside_placeholders() {
+        let ctx = [("health-check.status_code", "503")];
+        assert!(eval("{{health-check.status_code != 200}}", &ctx));
+        assert!(!eval("{{health-check.status_code == 200}}", &ctx));
+        assert!(eval("{{health-check.status_code}} >= 500", &ctx));
+        assert!(eval("{{health-check.status_code < 600}}", &ctx));
+        assert!(!eval("health-check.status_code <= 499", &ctx));
+        assert!(eval("true", &[]));
+    }
+
+    #[test]
+    fn coerces_rendered_strings_to_literals() {
+        let ctx = [("verify-result.verified", "true"), ("a.count", "3.0")];
+        assert!(eval("{{verify-result.verified == true}}", &ctx));
+        assert!(eval("a.count == 3", &ctx));
+        assert!(eval("verify-result.verified", &ctx));
+        assert!(!eval("missing.var == 0", &ctx));
+        assert!(eval("missing.var != 0", &ctx));
+    }
+
+    #[test]
+    fn supports_logic_membership_and_regex() {
+        let ctx = [("a.status", "201"), ("a.body", r#"{"id": "abc-42"}"#)];
+        assert!(eval(
+            "a.status in [200, 201] && a.body contains 'abc'",
+            &ctx
+        ));
+        assert!(eval("!(a.status in [500, 503]) || false", &ctx));
+        assert!(eval(r#"a.body matches "abc-\\d+""#, &ctx));
+        assert!(eval(r#"a.body =~ '"id"'"#, &ctx));
+        assert!(!eval("a.status == 200 || a.status > 300 && true", &ctx));
+        assert!(eval("(a.status == 200 || a.status > 200) && true", &ctx));
+    }
+
+    #[test]
+    fn reports_parse_errors_with_offsets() {
+        let err = Condition::parse("{{a.status ==}}").expect_err("missing operand");
+        assert_eq!(err.offset, 13);
+
+        let err = Condition::parse("a.status == 200 )").expect_err("trailing paren");
+        assert_eq!(err.offset, 16);
+
+        let err = Condition::parse("a.body matches '('").expect_err("bad regex");
+        assert!(err.message.contains("invalid regex"));
+
+        assert!(Condition::parse("a.status == 'open").is_err());
+        assert!(Condition::parse("a.status = 200").is_err());
+    }
+}
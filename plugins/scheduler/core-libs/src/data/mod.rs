@@ -0,0 +1,325 @@
+/// Data Feeders
+///
+/// Loads the CSV / JSON-lines files declared under `data.feeders` and hands
+/// their rows to virtual users according to each feeder's strategy. A row
+/// is exposed to templates and conditions as `{{feeder_name.column}}`.
+///
+/// A [`FeederSet`] is shared by all users of a run (wrap it in an `Arc`);
+/// the shared cursors of the `circular` and `unique` strategies are atomic.
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use indexmap::IndexMap;
+
+use crate::dsl::{DataSection, FeederAssign, FeederDef, FeederFormat, FeederStrategy};
+use crate::error::SchedulerError;
+
+/// Guest path at which the host preopens the data directory for the WASI
+/// component.
+pub const GUEST_DATA_DIR: &str = "/data";
+
+pub type Row = IndexMap<String, String>;
+
+#[derive(Debug)]
+pub struct Feeder {
+    name: String,
+    strategy: FeederStrategy,
+    assign: FeederAssign,
+    rows: Vec<Row>,
+    cursor: AtomicUsize,
+}
+
+impl Feeder {
+    pub fn new(
+        name: impl Into<String>,
+        strategy: FeederStrategy,
+        assign: FeederAssign,
+        rows: Vec<Row>,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            strategy,
+            assign,
+            rows,
+            cursor: AtomicUsize::new(0),
+        }
+    }
+
+    /// Reads `def.file` from `data_dir`.
+    pub fn load(def: &FeederDef, data_dir: &Path) -> Result<Self, SchedulerError> {
+        let path = data_dir.join(&def.file);
+        let feeder_error = |message: String| SchedulerError::Feeder {
+            feeder: def.name.clone(),
+            message,
+        };
+
+        let format = match def.format {
+            Some(format) => format,
+            None => match path.extension().and_then(|ext| ext.to_str()) {
+                Some("csv") => FeederFormat::Csv,
+                Some("jsonl") | Some("ndjson") => FeederFormat::Jsonl,
+                _ => {
+                    return Err(feeder_error(format!(
+                        "cannot infer the format of `{}`; set `format: csv|jsonl`",
+                        def.file
+                    )));
+                }
+            },
+        };
+        let raw = std::fs::read_to_string(&path)
+            .map_err(|err| feeder_error(format!("{}: {}", path.display(), err)))?;
+        let rows = match format {
+            FeederFormat::Csv => parse_csv(&raw),
+            FeederFormat::Jsonl => parse_jsonl(&raw),
+        }
+        .map_err(feeder_error)?;
+        if rows.is_empty() {
+            return Err(feeder_error(format!("{} has no rows", path.display())));
+        }
+
+        Ok(Self::new(def.name.clone(), def.strategy, def.assign, rows))
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn assign(&self) -> FeederAssign {
+        self.assign
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Row for the given consumer. `ordinal` is the zero-based user index
+    /// for `per_user` feeders and the iteration for `per_iteration` ones; it
+    /// only matters to the `sequential` strategy.
+    pub fn next_row(&self, ordinal: usize) -> Result<&Row, SchedulerError> {
+        if self.rows.is_empty() {
+            return Err(SchedulerError::FeederExhausted(self.name.clone()));
+        }
+        let index = match self.strategy {
+            FeederStrategy::Sequential => ordinal % self.rows.len(),
+            FeederStrategy::Random => fastrand::usize(..self.rows.len()),
+            FeederStrategy::Circular => {
+                self.cursor.fetch_add(1, Ordering::Relaxed) % self.rows.len()
+            }
+            FeederStrategy::Unique => {
+                let index = self.cursor.fetch_add(1, Ordering::Relaxed);
+                if index >= self.rows.len() {
+                    return Err(SchedulerError::FeederExhausted(self.name.clone()));
+                }
+                index
+            }
+        };
+        Ok(&self.rows[index])
+    }
+}
+
+/// All feeders of a scenario.
+#[derive(Debug, Default)]
+pub struct FeederSet {
+    feeders: Vec<Feeder>,
+}
+
+impl FeederSet {
+    pub fn new(feeders: Vec<Feeder>) -> Self {
+        Self { feeders }
+    }
+
+    /// Loads every feeder of `section` from `data_dir`, which is the
+    /// resolved `data.dir` natively and [`GUEST_DATA_DIR`] in the component.
+    pub fn load(section: Option<&DataSection>, data_dir: &Path) -> Result<Self, SchedulerError> {
+        let feeders = section
+            .map(|section| section.feeders.as_slice())
+            .unwrap_or_default()
+            .iter()
+            .map(|def| Feeder::load(def, data_dir))
+            .collect::<Result<_, _>>()?;
+        Ok(Self { feeders })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.feeders.is_empty()
+    }
+
+    pub fn feeders(&self) -> &[Feeder] {
+        &self.feeders
+    }
+
+    /// Variables of the `per_user` feeders for the zero-based user `ordinal`.
+    pub fn user_vars(&self, ordinal: usize) -> Result<Row, SchedulerError> {
+        self.draw(FeederAssign::PerUser, ordinal)
+    }
+
+    /// Variables of the `per_iteration` feeders for `iteration`.
+    pub fn iteration_vars(&self, iteration: usize) -> Result<Row, SchedulerError> {
+        self.draw(FeederAssign::PerIteration, iteration)
+    }
+
+    fn draw(&self, assign: FeederAssign, ordinal: usize) -> Result<Row, SchedulerError> {
+        let mut vars = Row::new();
+        for feeder in self.feeders.iter().filter(|f| f.assign == assign) {
+            for (column, value) in feeder.next_row(ordinal)? {
+                vars.insert(format!("{}.{}", feeder.name, column), value.clone());
+            }
+        }
+        Ok(vars)
+    }
+}
+
+fn parse_csv(raw: &str) -> Result<Vec<Row>, String> {
+    let mut reader = csv::ReaderBuilder::new()
+        .trim(csv::Trim::All)
+        .from_reader(raw.as_bytes());
+    let headers = reader.headers().map_err(|err| err.to_string())?.clone();
+    reader
+        .records()
+        .map(|record| {
+            let record = record.map_err(|err| err.to_string())?;
+            Ok(headers
+                .iter()
+                .zip(record.iter())
+                .map(|(column, value)| (column.to_string(), value.to_string()))
+                .collect())
+        })
+        .collect()
+}
+
+fn parse_jsonl(raw: &str) -> Result<Vec<Row>, String> {
+    raw.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(idx, line)| {
+            let value: serde_json::Value =
+                serde_json::from_str(line).map_err(|err| format!("line {}: {}", idx + 1, err))?;
+            let serde_json::Value::Object(object) = value else {
+                return Err(format!("line {}: expected a JSON object", idx + 1));
+            };
+            Ok(object
+                .into_iter()
+                .map(|(column, value)| {
+                    let value = match value {
+                        serde_json::Value::String(s) => s,
+                        other => other.to_string(),
+                    };
+                    (column, value)
+                })
+                .collect())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(n: usize) -> Vec<Row> {
+        (0..n)
+            .map(|i| Row::from([("id".to_string(), i.to_string())]))
+            .collect()
+    }
+
+    fn ids(feeder: &Feeder, ordinals: std::ops::Range<usize>) -> Vec<String> {
+        ordinals
+            .map(|ordinal| feeder.next_row(ordinal).expect("row")["id"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn strategies_hand_out_rows() {
+        let sequential = Feeder::new(
+            "s",
+            FeederStrategy::Sequential,
+            FeederAssign::PerUser,
+            rows(3),
+        );
+        assert_eq!(ids(&sequential, 0..4), vec!["0", "1", "2", "0"]);
+
+        let circular = Feeder::new(
+            "c",
+            FeederStrategy::Circular,
+            FeederAssign::PerUser,
+            rows(2),
+        );
+        assert_eq!(ids(&circular, 7..10), vec!["0", "1", "0"]);
+
+        let unique = Feeder::new("u", FeederStrategy::Unique, FeederAssign::PerUser, rows(2));
+        assert_eq!(ids(&unique, 0..2), vec!["0", "1"]);
+        assert!(matches!(
+            unique.next_row(2),
+            Err(SchedulerError::FeederExhausted(name)) if name == "u"
+        ));
+
+        let random = Feeder::new("r", FeederStrategy::Random, FeederAssign::PerUser, rows(4));
+        for id in ids(&random, 0..20) {
+            assert!(id.parse::<usize>().unwrap() < 4);
+        }
+    }
+
+    #[test]
+    fn loads_csv_and_jsonl_files() {
+        let dir = std::env::temp_dir().join(format!("scheduler-data-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        std::fs::write(
+            dir.join("users.csv"),
+            "username, password\nalice,\"p,1\"\nbob, p2\n",
+        )
+        .expect("write csv");
+        std::fs::write(
+            dir.join("payloads.jsonl"),
+            "{\"sku\": \"A-1\", \"qty\": 2}\n\n{\"sku\": \"B-2\", \"qty\": 5}\n",
+        )
+        .expect("write jsonl");
+
+        let scenario = crate::dsl::Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: feeders
+data:
+  feeders:
+    - name: users
+      file: users.csv
+    - name: payload
+      file: payloads.jsonl
+      strategy: circular
+      assign: per_iteration
+"#,
+        )
+        .expect("parse");
+        let feeders = FeederSet::load(scenario.data.as_ref(), &dir).expect("load");
+        std::fs::remove_dir_all(&dir).ok();
+
+        let user = feeders.user_vars(1).expect("user row");
+        assert_eq!(user["users.username"], "bob");
+        assert_eq!(user["users.password"], "p2");
+        assert!(!user.contains_key("payload.sku"));
+
+        let first = feeders.iteration_vars(0).expect("iteration row");
+        assert_eq!(first["payload.sku"], "A-1");
+        assert_eq!(first["payload.qty"], "2");
+        assert_eq!(
+            feeders.iteration_vars(1).expect("row")["payload.sku"],
+            "B-2"
+        );
+        assert_eq!(feeders.user_vars(0).expect("row")["users.password"], "p,1");
+    }
+
+    #[test]
+    fn missing_file_names_the_feeder() {
+        let def = FeederDef {
+            name: "users".into(),
+            file: "missing.csv".into(),
+            format: None,
+            strategy: FeederStrategy::Sequential,
+            assign: FeederAssign::PerUser,
+        };
+        let err = Feeder::load(&def, Path::new("/nonexistent")).expect_err("missing");
+        assert!(err.to_string().contains("users"), "{err}");
+    }
+}
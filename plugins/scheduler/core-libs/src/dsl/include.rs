@@ -0,0 +1,436 @@
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use super::{ActionsSection, ParseMode, Scenario, SourceMap, WorkbookSection, WorkflowSection};
+use crate::error::SchedulerError;
+
+/// What an included file may contribute. `version` and `name` are accepted so
+/// that a complete scenario can double as a library, but they are ignored.
+#[derive(Debug, Default, Deserialize)]
+struct Fragment {
+    #[serde(default)]
+    #[allow(dead_code)]
+    version: Option<String>,
+    #[serde(default)]
+    #[allow(dead_code)]
+    name: Option<String>,
+    #[serde(default, alias = "imports")]
+    include: Vec<String>,
+    #[serde(default)]
+    workbook: WorkbookSection,
+    #[serde(default)]
+    actions: ActionsSection,
+    #[serde(default)]
+    workflows: WorkflowSection,
+}
+
+/// Merges every `include` entry of `scenario` (recursively) into it.
+///
+/// Included items are appended after the scenario's own ones, so DSL paths
+/// of the including file stay valid. `root` is the file `scenario` was read
+/// from, if any; it takes part in cycle detection.
+///
+/// Returns where each included item was read from, keyed by its DSL path in
+/// the merged scenario.
+pub(super) fn resolve(
+    scenario: &mut Scenario,
+    root: Option<&Path>,
+    base_dir: &Path,
+    mode: ParseMode,
+) -> Result<SourceMap, SchedulerError> {
+    let includes = std::mem::take(&mut scenario.include);
+    if includes.is_empty() {
+        return Ok(SourceMap::default());
+    }
+
+    let root_label = root
+        .map(|path| normalize(path).display().to_string())
+        .unwrap_or_else(|| "<scenario>".to_string());
+    let mut resolver = Resolver {
+        mode,
+        stack: root.map(normalize).into_iter().collect(),
+        visited: HashSet::new(),
+        origins: IndexMap::new(),
+        sources: SourceMap::default(),
+    };
+    resolver.claim_own(&root_label, scenario);
+
+    for entry in includes {
+        resolver.include(scenario, &base_dir.join(entry))?;
+    }
+    Ok(resolver.sources)
+}
+
+struct Resolver {
+    mode: ParseMode,
+    /// Files currently being included, outermost first.
+    stack: Vec<PathBuf>,
+    /// Files already merged; including one twice (e.g. a diamond) is a no-op.
+    visited: HashSet<PathBuf>,
+    /// `(kind, id)` -> file that first defined it.
+    origins: IndexMap<(&'static str, String), String>,
+    /// Locations of the merged items in their own files.
+    sources: SourceMap,
+}
+
+impl Resolver {
+    fn include(&mut self, scenario: &mut Scenario, path: &Path) -> Result<(), SchedulerError> {
+        let path = normalize(path);
+        if let Some(start) = self.stack.iter().position(|open| *open == path) {
+            let mut chain: Vec<String> = self.stack[start..]
+                .iter()
+                .map(|open| open.display().to_string())
+                .collect();
+            chain.push(path.display().to_string());
+            return Err(SchedulerError::IncludeCycle(chain));
+        }
+        if !self.visited.insert(path.clone()) {
+            return Ok(());
+        }
+
+        let label = path.display().to_string();
+        let (mut fragment, source) = self.load(&path, &label)?;
+        let nested = std::mem::take(&mut fragment.include);
+        self.merge(&label, scenario, fragment, &source)?;
+
+        let base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.stack.push(path);
+        for entry in nested {
+            self.include(scenario, &base_dir.join(entry))?;
+        }
+        self.stack.pop();
+        Ok(())
+    }
+
+    fn load(&self, path: &Path, label: &str) -> Result<(Fragment, SourceMap), SchedulerError> {
+        let include_error = |message: String| SchedulerError::Include {
+            path: label.to_string(),
+            message,
+        };
+
+        let raw = std::fs::read_to_string(path).map_err(|err| include_error(err.to_string()))?;
+        let (fragment, unknown) = super::parse_tracking_unknown::<Fragment>(&raw)
+            .map_err(|err| include_error(err.to_string()))?;
+        if self.mode == ParseMode::Strict && !unknown.is_empty() {
+            return Err(include_error(format!(
+                "unknown fields: {}",
+                unknown.join(", ")
+            )));
+        }
+        Ok((fragment, SourceMap::from_yaml_str(&raw).with_file(label)))
+    }
+
+    /// Records the including scenario's own ids. Duplicates within one file
+    /// are left to `Scenario::analyze`.
+    fn claim_own(&mut self, label: &str, scenario: &Scenario) {
+        for key in ids_of(&scenario.workbook, &scenario.actions, &scenario.workflows) {
+            self.origins.entry(key).or_insert_with(|| label.to_string());
+        }
+    }
+
+    /// Appends the items of `fragment` to `scenario`, failing on any id
+    /// already defined by another file. The items' locations in `source` are
+    /// recorded under their merged indices.
+    fn merge(
+        &mut self,
+        label: &str,
+        scenario: &mut Scenario,
+        fragment: Fragment,
+        source: &SourceMap,
+    ) -> Result<(), SchedulerError> {
+        for key in ids_of(&fragment.workbook, &fragment.actions, &fragment.workflows) {
+            if let Some(existing) = self.origins.get(&key) {
+                return Err(SchedulerError::IncludeCollision {
+                    kind: key.0,
+                    id: key.1,
+                    path: label.to_string(),
+                    existing: existing.clone(),
+                });
+            }
+            self.origins.insert(key, label.to_string());
+        }
+
+        let offsets = [
+            ("workbook.resources", scenario.workbook.resources.len()),
+            ("workbook.ip_pools", scenario.workbook.ip_pools.len()),
+            ("actions.actions", scenario.actions.actions.len()),
+            ("workflows.nodes", scenario.workflows.nodes.len()),
+        ];
+        for (path, location) in source.iter() {
+            if let Some(merged) = rebase(path, &offsets) {
+                self.sources.insert(merged, location.clone());
+            }
+        }
+
+        let Fragment {
+            workbook,
+            actions,
+            workflows,
+            ..
+        } = fragment;
+        scenario.workbook.resources.extend(workbook.resources);
+        scenario.workbook.ip_pools.extend(workbook.ip_pools);
+        scenario.actions.actions.extend(actions.actions);
+        scenario.workflows.nodes.extend(workflows.nodes);
+        Ok(())
+    }
+}
+
+fn ids_of(
+    workbook: &WorkbookSection,
+    actions: &ActionsSection,
+    workflows: &WorkflowSection,
+) -> Vec<(&'static str, String)> {
+    let resources = workbook
+        .resources
+        .iter()
+        .map(|resource| ("resource", resource.id.clone()));
+    let pools = workbook
+        .ip_pools
+        .iter()
+        .map(|pool| ("ip pool", pool.id.clone()));
+    let actions = actions
+        .actions
+        .iter()
+        .map(|action| ("action", action.id.clone()));
+    let nodes = workflows
+        .nodes
+        .iter()
+        .map(|node| ("workflow node", node.id.clone()));
+    resources.chain(pools).chain(actions).chain(nodes).collect()
+}
+
+/// Shifts the item index of `path` (e.g. `actions.actions[0].with`) by the
+/// number of items its section held before the merge. Paths outside the
+/// merged sections, such as the fragment's own `include`, are dropped.
+fn rebase(path: &str, offsets: &[(&str, usize)]) -> Option<String> {
+    offsets.iter().find_map(|(section, offset)| {
+        let rest = path.strip_prefix(section)?.strip_prefix('[')?;
+        let (index, tail) = rest.split_once(']')?;
+        let index: usize = index.parse().ok()?;
+        Some(format!("{}[{}]{}", section, index + offset, tail))
+    })
+}
+
+/// Lexically resolves `.` and `..` so the same file reached through
+/// different relative paths is recognised. `fs::canonicalize` is not
+/// available inside the WASI component.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir if normalized.file_name().is_some() => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixture {
+        dir: PathBuf,
+    }
+
+    impl Fixture {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "scheduler-include-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            std::fs::create_dir_all(&dir).expect("create fixture dir");
+            Self { dir }
+        }
+
+        fn write(&self, name: &str, contents: &str) -> PathBuf {
+            let path = self.dir.join(name);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).expect("create fixture subdir");
+            }
+            std::fs::write(&path, contents).expect("write fixture");
+            path
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.dir);
+        }
+    }
+
+    const ROOT: &str = r#"
+version: "1.0"
+name: composed
+include:
+  - lib/common.yaml
+actions:
+  actions:
+    - id: local
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: health-check
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#;
+
+    #[test]
+    fn merges_nested_includes_relative_to_each_file() {
+        let fixture = Fixture::new("nested");
+        fixture.write(
+            "lib/common.yaml",
+            r#"
+imports:
+  - shared/endpoints.yaml
+actions:
+  actions:
+    - id: health-check
+      call: get
+      with:
+        url: "http://{{resource.ip}}:{{resource.port}}/health"
+"#,
+        );
+        fixture.write(
+            "lib/shared/endpoints.yaml",
+            r#"
+workbook:
+  resources:
+    - id: resource
+      type: http_endpoint
+      properties:
+        ip: "127.0.0.1"
+        port: "8080"
+"#,
+        );
+        let root = fixture.write("scenario.yaml", ROOT);
+
+        let scenario = Scenario::from_yaml_file(&root, ParseMode::Strict).expect("resolve");
+
+        assert!(scenario.include.is_empty());
+        let actions: Vec<_> = scenario
+            .actions
+            .actions
+            .iter()
+            .map(|a| a.id.as_str())
+            .collect();
+        assert_eq!(actions, vec!["local", "health-check"]);
+        assert_eq!(scenario.workbook.resources[0].id, "resource");
+        scenario.validate().expect("resolved scenario validates");
+    }
+
+    #[test]
+    fn diagnostics_for_included_items_point_into_their_file() {
+        let fixture = Fixture::new("locations");
+        let common = fixture.write(
+            "lib/common.yaml",
+            r#"
+actions:
+  actions:
+    - id: health-check
+      call: get
+workflows:
+  nodes:
+    - id: probe
+      type: action
+      action: health-check
+      edges:
+        - to: missing
+"#,
+        );
+        let root = fixture.write("scenario.yaml", ROOT);
+        let input = std::fs::read_to_string(&root).expect("read root");
+
+        let (scenario, included) =
+            Scenario::from_yaml_str_at_with_includes(&input, &root, ParseMode::Strict)
+                .expect("resolve");
+        let report = scenario.analyze_source_with_includes(&input, &included);
+
+        let dangling = report
+            .errors()
+            .find(|diag| diag.path == "workflows.nodes[2].edges[0].to")
+            .expect("dangling edge diagnostic");
+        let location = dangling.location.clone().expect("located");
+        assert_eq!(
+            location.file.as_deref(),
+            Some(normalize(&common).display().to_string().as_str())
+        );
+        assert_eq!((location.line, location.column), (12, 11));
+        assert!(
+            dangling.to_string().contains("common.yaml:12:11"),
+            "{dangling}"
+        );
+
+        let own = included.get("actions.actions[1]").expect("included action");
+        assert_eq!(own.line, 4);
+        assert!(included.get("actions.actions[0]").is_none());
+    }
+
+    #[test]
+    fn reports_include_cycles() {
+        let fixture = Fixture::new("cycle");
+        fixture.write("lib/common.yaml", "include: [../other.yaml]\n");
+        fixture.write("other.yaml", "include: [lib/common.yaml]\n");
+        let root = fixture.write("scenario.yaml", ROOT);
+
+        let err = Scenario::from_yaml_file(&root, ParseMode::Lenient).expect_err("cycle");
+        let SchedulerError::IncludeCycle(chain) = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(chain.len(), 3);
+        assert!(chain[0].ends_with("common.yaml"));
+        assert!(chain[1].ends_with("other.yaml"));
+        assert_eq!(chain[0], chain[2]);
+    }
+
+    #[test]
+    fn reports_colliding_ids_with_both_files() {
+        let fixture = Fixture::new("collision");
+        fixture.write(
+            "lib/common.yaml",
+            "actions:\n  actions:\n    - id: local\n      call: post\n",
+        );
+        let root = fixture.write("scenario.yaml", ROOT);
+
+        let err = Scenario::from_yaml_file(&root, ParseMode::Lenient).expect_err("collision");
+        let message = err.to_string();
+        assert!(
+            matches!(
+                err,
+                SchedulerError::IncludeCollision { kind: "action", ref id, .. } if id == "local"
+            ),
+            "{message}"
+        );
+        assert!(message.contains("common.yaml"), "{message}");
+        assert!(message.contains("scenario.yaml"), "{message}");
+    }
+
+    #[test]
+    fn diamond_includes_merge_once() {
+        let fixture = Fixture::new("diamond");
+        fixture.write("lib/common.yaml", "include: [a.yaml, b.yaml]\n");
+        fixture.write("lib/a.yaml", "include: [shared.yaml]\n");
+        fixture.write("lib/b.yaml", "include: [./shared.yaml]\n");
+        fixture.write(
+            "lib/shared.yaml",
+            "actions:\n  actions:\n    - id: health-check\n      call: get\n",
+        );
+        let root = fixture.write("scenario.yaml", ROOT);
+
+        let scenario = Scenario::from_yaml_file(&root, ParseMode::Lenient).expect("resolve");
+        assert_eq!(scenario.actions.actions.len(), 2);
+    }
+}
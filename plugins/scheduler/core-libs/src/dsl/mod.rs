@@ -1,18 +1,44 @@
+mod include;
+mod source_map;
+
 use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
 
 use indexmap::IndexMap;
-use serde::Deserialize;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 
+use crate::analysis::{self, Severity, ValidationReport};
+use crate::condition::Condition;
 use crate::error::SchedulerError;
 
+pub use source_map::{SourceLocation, SourceMap};
+
 pub type NodeId = String;
 pub type ResourceId = String;
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct Scenario {
     pub version: String,
     pub name: String,
+    /// Files whose actions, resources, ip_pools and workflow nodes are merged
+    /// into this scenario, relative to the including file. Emptied once
+    /// resolved.
+    #[serde(default, alias = "imports")]
+    pub include: Vec<String>,
+    /// Template variables with their defaults; see [`crate::variables`].
+    #[serde(default)]
+    pub variables: IndexMap<String, VariableDef>,
+    /// Credentials read from the environment or from files; see
+    /// [`crate::secrets`].
+    #[serde(default)]
+    pub secrets: IndexMap<String, SecretDef>,
+    /// Test data feeders bound to virtual users; see [`crate::data`].
+    #[serde(default)]
+    pub data: Option<DataSection>,
     #[serde(default)]
     pub workbook: WorkbookSection,
     #[serde(default)]
@@ -23,12 +49,90 @@ pub struct Scenario {
     pub load: Option<LoadSection>,
 }
 
+/// How [`Scenario::from_yaml_str_with_mode`] treats keys that no DSL field
+/// consumes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ParseMode {
+    /// Unknown keys are dropped; `analyze_source` reports them as warnings.
+    #[default]
+    Lenient,
+    /// Unknown keys fail parsing, each listed with its DSL path.
+    Strict,
+}
+
 impl Scenario {
     pub fn from_yaml_str(input: &str) -> Result<Self, SchedulerError> {
-        let scenario: Scenario = serde_yaml::from_str(input)?;
+        Self::from_yaml_str_with_mode(input, ParseMode::Lenient)
+    }
+
+    pub fn from_yaml_str_with_mode(input: &str, mode: ParseMode) -> Result<Self, SchedulerError> {
+        let (scenario, unknown) = parse_tracking_unknown::<Scenario>(input)?;
+        if mode == ParseMode::Strict && !unknown.is_empty() {
+            let source = SourceMap::from_yaml_str(input);
+            let diagnostics =
+                analysis::unknown_field_diagnostics(&unknown, Severity::Error, Some(&source));
+            return Err(SchedulerError::InvalidScenario(ValidationReport {
+                diagnostics,
+            }));
+        }
         Ok(scenario)
     }
 
+    /// Lists the DSL path (e.g. `load.user_resources.capacity_management`)
+    /// of every key in `input` that is not part of the scenario DSL.
+    pub fn unknown_fields(input: &str) -> Result<Vec<String>, SchedulerError> {
+        parse_tracking_unknown::<Scenario>(input).map(|(_, unknown)| unknown)
+    }
+
+    /// Reads and parses the scenario at `path`, resolving its includes.
+    pub fn from_yaml_file(path: &Path, mode: ParseMode) -> Result<Self, SchedulerError> {
+        let input = std::fs::read_to_string(path)?;
+        Self::from_yaml_str_at(&input, path, mode)
+    }
+
+    /// Parses `input`, which was read from `path`, and resolves its includes
+    /// relative to that file.
+    pub fn from_yaml_str_at(
+        input: &str,
+        path: &Path,
+        mode: ParseMode,
+    ) -> Result<Self, SchedulerError> {
+        Self::from_yaml_str_at_with_includes(input, path, mode).map(|(scenario, _)| scenario)
+    }
+
+    /// Like [`Scenario::from_yaml_str_at`], also returning where the included
+    /// items were read from, for [`Scenario::analyze_source_with_includes`].
+    pub fn from_yaml_str_at_with_includes(
+        input: &str,
+        path: &Path,
+        mode: ParseMode,
+    ) -> Result<(Self, SourceMap), SchedulerError> {
+        let mut scenario = Self::from_yaml_str_with_mode(input, mode)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        let included = include::resolve(&mut scenario, Some(path), base_dir, mode)?;
+        Ok((scenario, included))
+    }
+
+    /// Resolves includes of a scenario that was not read from a file, taking
+    /// relative paths from `base_dir`. Returns where the included items were
+    /// read from, for [`Scenario::analyze_source_with_includes`].
+    pub fn resolve_includes(
+        &mut self,
+        base_dir: &Path,
+        mode: ParseMode,
+    ) -> Result<SourceMap, SchedulerError> {
+        include::resolve(self, None, base_dir, mode)
+    }
+
+    /// JSON Schema of the scenario DSL, pretty-printed as checked into
+    /// `res/scenario.schema.json`.
+    pub fn json_schema() -> String {
+        let schema = schemars::schema_for!(Scenario);
+        let mut json = serde_json::to_string_pretty(&schema).expect("schema serializes to JSON");
+        json.push('\n');
+        json
+    }
+
     pub fn validate(&self) -> Result<(), SchedulerError> {
         let action_ids: HashSet<&str> = self
             .actions
@@ -57,14 +161,178 @@ impl Scenario {
                 if !node_ids.contains(edge.to.as_str()) {
                     return Err(SchedulerError::UnknownNode(edge.to.clone()));
                 }
+                edge.compile_condition(&node.id)?;
             }
         }
 
+        let report = self.analyze();
+        if report.has_errors() {
+            return Err(SchedulerError::InvalidScenario(report));
+        }
+
         Ok(())
     }
+
+    /// Runs the full structural analysis and returns every error and warning.
+    pub fn analyze(&self) -> ValidationReport {
+        analysis::analyze(self, None)
+    }
+
+    /// Like [`Scenario::analyze`], but resolves each diagnostic to its
+    /// line/column in `input`, the YAML this scenario was parsed from.
+    ///
+    /// Keys unknown to the DSL are included as warnings.
+    pub fn analyze_source(&self, input: &str) -> ValidationReport {
+        self.analyze_source_with_includes(input, &SourceMap::default())
+    }
+
+    /// Like [`Scenario::analyze_source`] for a scenario with resolved
+    /// includes: diagnostics for included items point into their own file
+    /// through `included`.
+    pub fn analyze_source_with_includes(
+        &self,
+        input: &str,
+        included: &SourceMap,
+    ) -> ValidationReport {
+        let mut source = SourceMap::from_yaml_str(input);
+        source.extend(included);
+        let mut report = analysis::analyze(self, Some(&source));
+        if let Ok(unknown) = Self::unknown_fields(input) {
+            report
+                .diagnostics
+                .extend(analysis::unknown_field_diagnostics(
+                    &unknown,
+                    Severity::Warning,
+                    Some(&source),
+                ));
+        }
+        report
+    }
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+fn parse_tracking_unknown<T: DeserializeOwned>(
+    input: &str,
+) -> Result<(T, Vec<String>), SchedulerError> {
+    let mut unknown = Vec::new();
+    let deserializer = serde_yaml::Deserializer::from_str(input);
+    let value: T = serde_ignored::deserialize(deserializer, |path| {
+        unknown.push(dsl_path(&path));
+    })?;
+    Ok((value, unknown))
+}
+
+/// Renders a `serde_ignored` path in the `a.b[0].c` form used by `SourceMap`.
+fn dsl_path(path: &serde_ignored::Path<'_>) -> String {
+    use serde_ignored::Path;
+
+    match path {
+        Path::Root => String::new(),
+        Path::Seq { parent, index } => format!("{}[{}]", dsl_path(parent), index),
+        Path::Map { parent, key } => {
+            let parent = dsl_path(parent);
+            if parent.is_empty() {
+                key.clone()
+            } else {
+                format!("{}.{}", parent, key)
+            }
+        }
+        Path::Some { parent }
+        | Path::NewtypeStruct { parent }
+        | Path::NewtypeVariant { parent } => dsl_path(parent),
+    }
+}
+
+/// A `variables:` entry: either a bare default value or the detailed form.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum VariableDef {
+    Detailed {
+        #[serde(default)]
+        #[schemars(with = "Option<serde_json::Value>")]
+        default: Option<Value>,
+        /// Masked whenever effective values are printed.
+        #[serde(default)]
+        secret: bool,
+        #[serde(default)]
+        description: Option<String>,
+    },
+    Value(#[schemars(with = "serde_json::Value")] Value),
+}
+
+/// A `secrets:` entry. Exactly one of `env` and `file` must be set.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+pub struct SecretDef {
+    /// Environment variable holding the value.
+    #[serde(default)]
+    pub env: Option<String>,
+    /// File holding the value, relative to the scenario file. A trailing
+    /// newline is dropped.
+    #[serde(default)]
+    pub file: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct DataSection {
+    /// Directory holding the feeder files, relative to the scenario file.
+    #[serde(default = "default_data_dir")]
+    pub dir: String,
+    #[serde(default)]
+    pub feeders: Vec<FeederDef>,
+}
+
+fn default_data_dir() -> String {
+    "data".to_string()
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct FeederDef {
+    /// Rows are exposed as `{{name.column}}`.
+    pub name: String,
+    /// File inside the data directory.
+    pub file: String,
+    /// Inferred from the file extension when omitted.
+    #[serde(default)]
+    pub format: Option<FeederFormat>,
+    #[serde(default)]
+    pub strategy: FeederStrategy,
+    #[serde(default)]
+    pub assign: FeederAssign,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FeederFormat {
+    Csv,
+    Jsonl,
+}
+
+/// How rows are handed out.
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeederStrategy {
+    /// Row picked by position: user N (or iteration N) gets row N, wrapping.
+    #[default]
+    Sequential,
+    /// A uniformly random row each time.
+    Random,
+    /// Each row is handed out once; consumers stop when rows run out.
+    Unique,
+    /// A cursor shared by all users that wraps around.
+    Circular,
+}
+
+/// When a consumer draws a new row.
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeederAssign {
+    /// Once per virtual user, kept for all of its iterations.
+    #[default]
+    PerUser,
+    /// At the start of every iteration.
+    PerIteration,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
 pub struct WorkbookSection {
     #[serde(default)]
     pub resources: Vec<ResourceDef>,
@@ -72,7 +340,7 @@ pub struct WorkbookSection {
     pub ip_pools: Vec<IpPoolDef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct IpPoolDef {
     pub id: String,
     pub name: String,
@@ -81,49 +349,105 @@ pub struct IpPoolDef {
     pub allocation_strategy: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ResourceDef {
     pub id: ResourceId,
     #[serde(rename = "type")]
     pub resource_type: String,
     #[serde(default)]
+    #[schemars(with = "IndexMap<String, serde_json::Value>")]
     pub properties: IndexMap<String, Value>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
 pub struct ActionsSection {
     #[serde(default)]
     pub actions: Vec<ActionDef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema)]
 pub struct ActionDef {
     pub id: String,
     pub call: String,
     #[serde(default)]
+    #[schemars(with = "IndexMap<String, serde_json::Value>")]
     pub with: IndexMap<String, Value>,
     #[serde(default)]
     pub export: Vec<ExportDef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, JsonSchema)]
 pub struct ExportDef {
     #[serde(rename = "type")]
     pub export_type: String,
     pub name: String,
+    /// JSONPath, regex or header name, depending on `type`.
+    #[serde(default)]
+    pub expr: Option<String>,
+    /// `user` (default) or `workbook`, which shares the value with every
+    /// virtual user of the run.
     #[serde(default)]
     pub scope: Option<String>,
+    /// How a `workbook`-scoped value combines with one already shared.
     #[serde(default)]
+    pub mode: ExportMode,
+    #[serde(default)]
+    #[schemars(with = "Option<serde_json::Value>")]
     pub default: Option<Value>,
+    /// `assert`: expected status code, or the list of accepted codes.
+    #[serde(default)]
+    pub status: Option<StatusExpectation>,
+    /// `assert`: text the response body must contain.
+    #[serde(default)]
+    pub contains: Option<String>,
+    /// `assert`: value the JSONPath `expr` must match. Without it the path
+    /// only has to match something.
+    #[serde(default)]
+    #[schemars(with = "Option<serde_json::Value>")]
+    pub equals: Option<Value>,
+    /// `assert`: response header that must be present.
+    #[serde(default)]
+    pub header: Option<String>,
+    /// `assert`: upper bound for the response time, in milliseconds.
+    #[serde(default)]
+    pub max_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportMode {
+    /// The latest write wins.
+    #[default]
+    Overwrite,
+    /// Only the first write is kept; later ones are ignored.
+    FirstWriterWins,
+    /// Every write is appended to a list.
+    Append,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum StatusExpectation {
+    Code(u16),
+    AnyOf(Vec<u16>),
+}
+
+impl StatusExpectation {
+    pub fn codes(&self) -> Vec<u16> {
+        match self {
+            StatusExpectation::Code(code) => vec![*code],
+            StatusExpectation::AnyOf(codes) => codes.clone(),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
 pub struct WorkflowSection {
     #[serde(default)]
     pub nodes: Vec<WorkflowNode>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct WorkflowNode {
     pub id: NodeId,
     #[serde(rename = "type")]
@@ -131,9 +455,23 @@ pub struct WorkflowNode {
     pub action: Option<String>,
     #[serde(default)]
     pub edges: Vec<WorkflowEdge>,
+    /// `group` node this node belongs to; top-level nodes have none.
+    #[serde(default)]
+    pub parent: Option<NodeId>,
+    /// Share of this node in its parent's progress relative to its siblings
+    /// (default 1).
+    #[serde(default)]
+    pub weight: Option<f64>,
+    /// Times an action node may run within one iteration; one more visit
+    /// aborts the iteration.
+    #[serde(default)]
+    pub max_visits: Option<usize>,
+    /// Pause after this node's action, before the next node runs.
+    #[serde(default)]
+    pub think_time: Option<ThinkTimeDef>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct WorkflowEdge {
     pub to: NodeId,
     #[serde(default)]
@@ -142,17 +480,41 @@ pub struct WorkflowEdge {
     pub label: Option<String>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize)]
+impl WorkflowEdge {
+    /// Parses the edge trigger condition, if any, into its compiled form.
+    pub fn compile_condition(&self, from: &str) -> Result<Option<Condition>, SchedulerError> {
+        let Some(source) = self
+            .trigger
+            .as_ref()
+            .and_then(|trigger| trigger.condition.as_deref())
+        else {
+            return Ok(None);
+        };
+
+        Condition::parse(source)
+            .map(Some)
+            .map_err(|source_err| SchedulerError::InvalidCondition {
+                from: from.to_string(),
+                to: self.to.clone(),
+                condition: source.to_string(),
+                source: source_err,
+            })
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
 pub struct TriggerDef {
     #[serde(default)]
     pub condition: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "kebab-case")]
 pub enum WorkflowNodeType {
     Action,
     End,
+    /// Groups other nodes (via their `parent`) for progress roll-up; never executed.
+    Group,
 }
 
 impl Default for WorkflowNodeType {
@@ -165,21 +527,71 @@ impl Default for WorkflowNodeType {
 // Load Testing Configuration
 // ============================================================================
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct LoadSection {
+    /// Closed model: batches of looping users spawned at fixed offsets.
+    #[serde(default)]
     pub ramp_up: RampUpConfig,
+    /// Open model: users arrive at a target rate, each running one iteration.
+    /// Replaces `ramp_up`.
+    #[serde(default)]
+    pub arrival_rate: Option<ArrivalRateConfig>,
+    /// Closed model driven by a target user count per stage; users are
+    /// spawned and retired to follow it. Replaces `ramp_up`.
+    #[serde(default)]
+    pub stages: Vec<StageDef>,
     pub user_lifetime: UserLifetimeConfig,
     pub user_resources: UserResourcesConfig,
     #[serde(default)]
     pub concurrency: Option<ConcurrencyConfig>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
 pub struct RampUpConfig {
     pub phases: Vec<RampUpPhase>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+/// Arrivals are scheduled up front from the rate curve, so a slow server
+/// does not lower the rate; it only raises the number of users in flight.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct ArrivalRateConfig {
+    /// Arrivals per unit of time at the start, e.g. `50/s` or `600/m`.
+    pub rate: String,
+    /// Rate reached at the end of `duration`, ramping linearly from `rate`.
+    /// Defaults to `rate`.
+    #[serde(default)]
+    pub target_rate: Option<String>,
+    /// How long arrivals are generated, e.g. `5m`.
+    pub duration: String,
+    #[serde(default)]
+    pub distribution: ArrivalDistribution,
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// The target moves linearly from the previous stage's `target_users` (0
+/// before the first stage) to this one's over `duration`; equal targets hold.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+pub struct StageDef {
+    pub target_users: usize,
+    /// e.g. `2m`.
+    pub duration: String,
+    /// Tenant of the users spawned during this stage.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrivalDistribution {
+    /// Evenly spaced arrivals.
+    #[default]
+    Constant,
+    /// Exponentially distributed inter-arrival times.
+    Poisson,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct RampUpPhase {
     pub at_second: u64,
     pub spawn_users: usize,
@@ -189,26 +601,76 @@ pub struct RampUpPhase {
     pub ip_pool_override: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct UserLifetimeConfig {
     pub mode: UserLifetimeMode,
     pub iterations: usize,
-    pub think_time: String,
+    /// Pause between iterations.
+    pub think_time: ThinkTimeDef,
+    /// Interval between iteration starts (e.g. `10s`). The pause after an
+    /// iteration fills the rest of the interval; `think_time` is then
+    /// ignored.
+    #[serde(default)]
+    pub pacing: Option<String>,
+    /// Actions an iteration may run before it is aborted.
+    #[serde(default)]
+    pub max_steps_per_iteration: Option<usize>,
+    /// Wall-clock limit of one iteration (e.g. `30s`), checked before each
+    /// action; an iteration past it is aborted.
+    #[serde(default)]
+    pub iteration_timeout: Option<String>,
+}
+
+/// A pause: a fixed duration (`2s`), a uniform range (`1s..3s`) or a random
+/// distribution.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(untagged)]
+pub enum ThinkTimeDef {
+    Duration(String),
+    Distribution(ThinkTimeDistribution),
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, JsonSchema)]
+#[serde(tag = "distribution", rename_all = "snake_case")]
+pub enum ThinkTimeDistribution {
+    /// Any duration in `min..=max` is equally likely.
+    Uniform { min: String, max: String },
+    /// Normally distributed around `mean`; negative draws become zero.
+    Normal { mean: String, stddev: String },
+    /// Exponentially distributed with the given `mean`.
+    Exponential { mean: String },
+}
+
+impl fmt::Display for ThinkTimeDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ThinkTimeDef::Duration(duration) => f.write_str(duration),
+            ThinkTimeDef::Distribution(ThinkTimeDistribution::Uniform { min, max }) => {
+                write!(f, "uniform {}..{}", min, max)
+            }
+            ThinkTimeDef::Distribution(ThinkTimeDistribution::Normal { mean, stddev }) => {
+                write!(f, "normal, mean {}, stddev {}", mean, stddev)
+            }
+            ThinkTimeDef::Distribution(ThinkTimeDistribution::Exponential { mean }) => {
+                write!(f, "exponential, mean {}", mean)
+            }
+        }
+    }
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum UserLifetimeMode {
     Once,
     Loop,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct UserResourcesConfig {
     pub ip_binding: IpBindingConfig,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct IpBindingConfig {
     pub enabled: bool,
     pub pool_id: String,
@@ -216,7 +678,7 @@ pub struct IpBindingConfig {
     pub release_on: ReleaseOn,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum IpBindingStrategy {
     PerUser,
@@ -224,17 +686,22 @@ pub enum IpBindingStrategy {
     PerTask,
 }
 
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ReleaseOn {
     TaskEnd,
     UserExit,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
 pub struct ConcurrencyConfig {
+    /// Users running at once; further spawns wait for a running user to end.
     pub max_concurrent_users: usize,
+    /// Users started per unit of time, e.g. `100/s`.
     pub spawn_rate_limit: String,
+    /// Actions dispatched per unit of time across all users, e.g. `1000/s`.
+    #[serde(default)]
+    pub action_rate_limit: Option<String>,
 }
 
 #[cfg(test)]
@@ -262,6 +729,10 @@ mod tests {
         let scenario = Scenario {
             version: "1.0".into(),
             name: "invalid_action".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
             workbook: WorkbookSection::default(),
             actions: ActionsSection {
                 actions: vec![ActionDef {
@@ -275,10 +746,15 @@ mod tests {
                 nodes: vec![WorkflowNode {
                     id: "start".into(),
                     node_type: WorkflowNodeType::Action,
+                    parent: None,
+                    weight: None,
+                    max_visits: None,
+                    think_time: None,
                     action: Some("missing".into()),
                     edges: vec![],
                 }],
             },
+            load: None,
         };
         let err = scenario
             .validate()
@@ -297,6 +773,10 @@ mod tests {
         let scenario = Scenario {
             version: "1.0".into(),
             name: "invalid_edge".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
             workbook: WorkbookSection::default(),
             actions: ActionsSection {
                 actions: vec![ActionDef {
@@ -310,6 +790,10 @@ mod tests {
                 nodes: vec![WorkflowNode {
                     id: "start".into(),
                     node_type: WorkflowNodeType::Action,
+                    parent: None,
+                    weight: None,
+                    max_visits: None,
+                    think_time: None,
                     action: Some("ping".into()),
                     edges: vec![WorkflowEdge {
                         to: "unknown".into(),
@@ -318,8 +802,177 @@ mod tests {
                     }],
                 }],
             },
+            load: None,
         };
         let err = scenario.validate().expect_err("should report missing node");
         assert!(matches!(err, SchedulerError::UnknownNode(target) if target == "unknown"));
     }
+
+    #[test]
+    fn strict_mode_lists_unknown_fields() {
+        const ADVANCED: &str = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../res/load_test_advanced.yaml"
+        ));
+
+        Scenario::from_yaml_str(ADVANCED).expect("lenient parse succeeds");
+
+        let unknown = Scenario::unknown_fields(ADVANCED).expect("parse");
+        assert_eq!(
+            unknown,
+            vec!["load.user_resources.capacity_management", "monitoring",]
+        );
+
+        let err = Scenario::from_yaml_str_with_mode(ADVANCED, ParseMode::Strict)
+            .expect_err("strict parse rejects unknown keys");
+        let SchedulerError::InvalidScenario(report) = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        let paths: Vec<_> = report.errors().map(|diag| diag.path.as_str()).collect();
+        assert_eq!(paths, unknown);
+        assert!(report.errors().all(|diag| diag.location.is_some()));
+    }
+
+    #[test]
+    fn analyze_warns_about_ignored_settings() {
+        const ADVANCED: &str = include_str!(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../res/load_test_advanced.yaml"
+        ));
+
+        let scenario = Scenario::from_yaml_str(ADVANCED).expect("parse");
+        let report = scenario.analyze_source(ADVANCED);
+        let warned: Vec<_> = report.warnings().map(|diag| diag.path.as_str()).collect();
+
+        assert!(warned.contains(&"monitoring"));
+        assert!(warned.contains(&"actions.actions[0].with.timeout"));
+        assert!(!warned.contains(&"load.concurrency"));
+        assert!(warned.contains(&"load.ramp_up.phases[0].ip_pool_override"));
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn checked_in_schema_matches_dsl_types() {
+        let path = res_dir().join("scenario.schema.json");
+        let generated = Scenario::json_schema();
+        let checked_in = std::fs::read_to_string(&path).expect("read res/scenario.schema.json");
+        assert!(
+            checked_in == generated,
+            "res/scenario.schema.json is stale; regenerate it with `Ntx schema --write` \
+             from the repository root"
+        );
+    }
+
+    #[test]
+    fn res_scenarios_validate_against_schema() {
+        let schema: serde_json::Value =
+            serde_json::from_str(&Scenario::json_schema()).expect("schema is JSON");
+        let validator = jsonschema::validator_for(&schema).expect("valid schema");
+
+        let mut checked = 0;
+        for entry in std::fs::read_dir(res_dir()).expect("read res dir") {
+            let path = entry.expect("dir entry").path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("yaml") {
+                continue;
+            }
+            let raw = std::fs::read_to_string(&path).expect("read scenario");
+            let instance: serde_json::Value = serde_yaml::from_str(&raw).expect("yaml");
+            let errors: Vec<_> = validator
+                .iter_errors(&instance)
+                .map(|err| format!("{} at {}", err, err.instance_path()))
+                .collect();
+            assert!(errors.is_empty(), "{}: {:#?}", path.display(), errors);
+            checked += 1;
+        }
+        assert!(checked > 0, "no scenarios found in res/");
+    }
+
+    fn res_dir() -> std::path::PathBuf {
+        std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../res")
+    }
+
+    #[test]
+    fn validate_rejects_malformed_condition() {
+        let scenario = Scenario {
+            version: "1.0".into(),
+            name: "invalid_condition".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
+            workbook: WorkbookSection::default(),
+            actions: ActionsSection {
+                actions: vec![ActionDef {
+                    id: "ping".into(),
+                    call: "get".into(),
+                    with: IndexMap::new(),
+                    export: vec![],
+                }],
+            },
+            workflows: WorkflowSection {
+                nodes: vec![
+                    WorkflowNode {
+                        id: "start".into(),
+                        node_type: WorkflowNodeType::Action,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
+                        action: Some("ping".into()),
+                        edges: vec![WorkflowEdge {
+                            to: "end".into(),
+                            trigger: Some(TriggerDef {
+                                condition: Some("{{ping.status_code >> 200}}".into()),
+                            }),
+                            label: None,
+                        }],
+                    },
+                    WorkflowNode {
+                        id: "end".into(),
+                        node_type: WorkflowNodeType::End,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
+                        action: None,
+                        edges: vec![],
+                    },
+                ],
+            },
+            load: None,
+        };
+        let err = scenario
+            .validate()
+            .expect_err("should report malformed condition");
+        match err {
+            SchedulerError::InvalidCondition { from, to, .. } => {
+                assert_eq!(from, "start");
+                assert_eq!(to, "end");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn sample_scenarios_have_valid_conditions() {
+        for sample in [
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../res/http_load_medium.yaml"
+            )),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../res/load_test_advanced.yaml"
+            )),
+            include_str!(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/../res/load_test_simple.yaml"
+            )),
+        ] {
+            let scenario = Scenario::from_yaml_str(sample).expect("parse sample");
+            scenario
+                .validate()
+                .expect("sample conditions should compile");
+        }
+    }
 }
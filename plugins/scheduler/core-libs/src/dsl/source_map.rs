@@ -0,0 +1,262 @@
+use std::fmt;
+
+use indexmap::IndexMap;
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+/// 1-based line/column of a node in the scenario YAML.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+    /// Included file the node was read from; `None` for the scenario itself.
+    pub file: Option<String>,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}:", file)?;
+        }
+        write!(f, "{}:{}", self.line, self.column)
+    }
+}
+
+/// Maps DSL paths such as `workflows.nodes[2].edges[0]` or
+/// `load.user_resources.ip_binding` to their position in the YAML source.
+///
+/// Mapping entries point at their key, sequence items at the item start.
+#[derive(Debug, Clone, Default)]
+pub struct SourceMap {
+    locations: IndexMap<String, SourceLocation>,
+}
+
+impl SourceMap {
+    /// Builds the map from raw YAML. Unparseable input yields an empty map;
+    /// syntax errors are reported by the regular deserializer instead.
+    pub fn from_yaml_str(input: &str) -> Self {
+        let mut builder = SourceMapBuilder::default();
+        let mut parser = Parser::new_from_str(input);
+        if parser.load(&mut builder, false).is_err() {
+            return Self::default();
+        }
+        Self {
+            locations: builder.locations,
+        }
+    }
+
+    /// Marks every location as read from the included `file`.
+    pub fn with_file(mut self, file: &str) -> Self {
+        for location in self.locations.values_mut() {
+            location.file = Some(file.to_string());
+        }
+        self
+    }
+
+    /// Adds the locations of `other` for paths not recorded yet.
+    pub fn extend(&mut self, other: &SourceMap) {
+        for (path, location) in &other.locations {
+            self.insert(path.clone(), location.clone());
+        }
+    }
+
+    pub(crate) fn insert(&mut self, path: String, location: SourceLocation) {
+        self.locations.entry(path).or_insert(location);
+    }
+
+    pub fn get(&self, path: &str) -> Option<SourceLocation> {
+        self.locations.get(path).cloned()
+    }
+
+    /// Returns the location of `path`, falling back to its closest recorded
+    /// ancestor (e.g. `workflows` when `workflows.nodes` is absent).
+    pub fn locate(&self, path: &str) -> Option<SourceLocation> {
+        let mut current = path;
+        loop {
+            if let Some(location) = self.get(current) {
+                return Some(location);
+            }
+            let cut = current.rfind(['.', '['])?;
+            current = &current[..cut];
+        }
+    }
+
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.locations.keys().map(String::as_str)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&str, &SourceLocation)> {
+        self.locations
+            .iter()
+            .map(|(path, location)| (path.as_str(), location))
+    }
+}
+
+enum Frame {
+    Mapping {
+        path: String,
+        key: Option<String>,
+        /// Sequence item mappings are located at their first key, because
+        /// the parser marks block mappings after that key.
+        locate_at_first_key: bool,
+    },
+    Sequence {
+        path: String,
+        index: usize,
+    },
+}
+
+#[derive(Default)]
+struct SourceMapBuilder {
+    stack: Vec<Frame>,
+    locations: IndexMap<String, SourceLocation>,
+}
+
+impl SourceMapBuilder {
+    /// Path of the value about to be read, or `None` when the next scalar is
+    /// a mapping key. Sequence items are recorded at `mark` when `record`.
+    fn value_path(&mut self, mark: Marker, record: bool) -> Option<String> {
+        match self.stack.last_mut() {
+            None => Some(String::new()),
+            Some(Frame::Mapping { path, key, .. }) => key.take().map(|key| join(path, &key)),
+            Some(Frame::Sequence { path, index }) => {
+                let item = format!("{}[{}]", path, index);
+                *index += 1;
+                if record {
+                    self.locations.entry(item.clone()).or_insert(location(mark));
+                }
+                Some(item)
+            }
+        }
+    }
+
+    fn record(&mut self, path: String, mark: Marker) {
+        self.locations.entry(path).or_insert(location(mark));
+    }
+}
+
+impl MarkedEventReceiver for SourceMapBuilder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        match event {
+            Event::Scalar(value, ..) => {
+                if self.value_path(mark, true).is_some() {
+                    return;
+                }
+                let Some(Frame::Mapping {
+                    path,
+                    key,
+                    locate_at_first_key,
+                }) = self.stack.last_mut()
+                else {
+                    return;
+                };
+                let child = join(path, &value);
+                let item = std::mem::take(locate_at_first_key).then(|| path.clone());
+                *key = Some(value);
+                if let Some(item) = item {
+                    self.record(item, mark);
+                }
+                self.record(child, mark);
+            }
+            Event::Alias(_) => {
+                self.value_path(mark, true);
+            }
+            Event::MappingStart(..) => {
+                let in_sequence = matches!(self.stack.last(), Some(Frame::Sequence { .. }));
+                let path = self.value_path(mark, false).unwrap_or_default();
+                self.stack.push(Frame::Mapping {
+                    path,
+                    key: None,
+                    locate_at_first_key: in_sequence,
+                });
+            }
+            Event::SequenceStart(..) => {
+                let path = self.value_path(mark, true).unwrap_or_default();
+                self.stack.push(Frame::Sequence { path, index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn join(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+fn location(mark: Marker) -> SourceLocation {
+    SourceLocation {
+        line: mark.line(),
+        column: mark.col() + 1,
+        file: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+version: \"1.0\"
+workflows:
+  nodes:
+    - id: start
+      edges:
+        - to: end
+        - to: other
+    - id: end
+";
+
+    #[test]
+    fn records_keys_and_sequence_items() {
+        let map = SourceMap::from_yaml_str(SAMPLE);
+        assert_eq!(
+            map.get("workflows"),
+            Some(SourceLocation {
+                line: 2,
+                column: 1,
+                file: None
+            })
+        );
+        assert_eq!(
+            map.get("workflows.nodes[0]"),
+            Some(SourceLocation {
+                line: 4,
+                column: 7,
+                file: None
+            })
+        );
+        assert_eq!(
+            map.get("workflows.nodes[0].edges[1]"),
+            Some(SourceLocation {
+                line: 7,
+                column: 11,
+                file: None
+            })
+        );
+        assert_eq!(
+            map.get("workflows.nodes[1].id"),
+            Some(SourceLocation {
+                line: 8,
+                column: 7,
+                file: None
+            })
+        );
+    }
+
+    #[test]
+    fn locate_falls_back_to_ancestors() {
+        let map = SourceMap::from_yaml_str(SAMPLE);
+        assert_eq!(
+            map.locate("workflows.nodes[0].edges[5].trigger"),
+            map.get("workflows.nodes[0].edges")
+        );
+        assert!(map.locate("missing").is_none());
+    }
+}
@@ -3,6 +3,9 @@ use anyhow::Error as AnyError;
 use ctrlc::Error as CtrlcError;
 use thiserror::Error;
 
+use crate::analysis::ValidationReport;
+use crate::condition::ConditionError;
+
 #[derive(Debug, Error)]
 pub enum SchedulerError {
     #[error("failed to parse scenario: {0}")]
@@ -11,18 +14,81 @@ pub enum SchedulerError {
     #[error("io error: {0}")]
     Io(#[from] std::io::Error),
 
+    #[error("failed to include `{path}`: {message}")]
+    Include { path: String, message: String },
+
+    #[error("include cycle: {}", .0.join(" -> "))]
+    IncludeCycle(Vec<String>),
+
+    #[error("{kind} `{id}` included from `{path}` is already defined in `{existing}`")]
+    IncludeCollision {
+        kind: &'static str,
+        id: String,
+        path: String,
+        existing: String,
+    },
+
+    #[error("variable `{0}` has no default and was not set via --var or NTX_VAR_*")]
+    MissingVariable(String),
+
+    #[error("invalid variable override `{0}`, expected key=value")]
+    InvalidVariableOverride(String),
+
+    #[error("secret `{name}`: {message}")]
+    Secret { name: String, message: String },
+
+    #[error("data feeder `{feeder}`: {message}")]
+    Feeder { feeder: String, message: String },
+
+    #[error("data feeder `{0}` has no rows left")]
+    FeederExhausted(String),
+
     #[error("unknown action `{action}` referenced by workflow node `{node}`")]
     UnknownAction { action: String, node: String },
 
     #[error("unknown workflow node `{0}` referenced by edge")]
     UnknownNode(String),
 
+    #[error("invalid condition `{condition}` on edge `{from}` -> `{to}`: {source}")]
+    InvalidCondition {
+        from: String,
+        to: String,
+        condition: String,
+        #[source]
+        source: ConditionError,
+    },
+
+    #[error("invalid export `{name}` of action `{action}`: {message}")]
+    InvalidExport {
+        action: String,
+        name: String,
+        message: String,
+    },
+
+    #[error("invalid template `{template}`: {message}")]
+    InvalidTemplate { template: String, message: String },
+
+    #[error("unresolved variable `{name}` in template `{template}`")]
+    UnresolvedVariable { name: String, template: String },
+
+    #[error("scenario validation failed:\n{0}")]
+    InvalidScenario(ValidationReport),
+
     #[error("task `{0}` not found")]
     TaskNotFound(String),
 
     #[error("action `{0}` not registered")]
     ActionNotRegistered(String),
 
+    /// A batch of scheduler events was rolled back because one of them failed.
+    #[error("event #{index} ({event}) rejected, batch rolled back: {source}")]
+    EventRejected {
+        index: usize,
+        event: String,
+        #[source]
+        source: Box<SchedulerError>,
+    },
+
     #[error("action `{action}` failed: {source}")]
     ActionExecution {
         action: String,
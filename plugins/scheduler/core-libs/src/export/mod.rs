@@ -0,0 +1,636 @@
+/// Action Exports
+///
+/// Compiles the `export` entries of an action and extracts their values from
+/// the response the action produced. Each value is published to the running
+/// workflow as `{{action-id.name}}`, where later actions and edge conditions
+/// can reference it. Supported `type`s:
+/// - `content`: top-level field `name` of a JSON body
+/// - `jsonpath`: first match of the JSONPath `expr` against a JSON body
+/// - `regex`: first capture group (or the whole match) of `expr` in the body
+/// - `header`: response header `expr` (defaults to `name`), case-insensitive
+/// - `status`: the response status code
+/// - `assert`: checks on the response (`status`, `contains`, `expr` with
+///   optional `equals`, `header`, `max_ms`); all of them must hold. The
+///   variable is `true` or `false`, and a failed assertion fails the action.
+///
+/// When extraction fails the export falls back to its `default`; without a
+/// default the variable stays unset.
+///
+/// Exports with `scope: workbook` are not added to the user's variables;
+/// they are handed back as [`WorkbookWrite`]s for the run-wide store, which
+/// combines them according to `mode`.
+use std::fmt;
+use std::time::Duration;
+
+use indexmap::IndexMap;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use serde_json_path::JsonPath;
+use serde_yaml::Value;
+
+use crate::dsl::{ActionDef, ExportDef, ExportMode};
+use crate::error::SchedulerError;
+
+/// Export `type`s understood by [`ActionExports::compile`].
+pub const EXPORT_TYPES: [&str; 6] = ["content", "jsonpath", "regex", "header", "status", "assert"];
+
+/// What an action component observed, for exports to extract from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActionResponse {
+    pub status_code: Option<u16>,
+    pub headers: IndexMap<String, String>,
+    pub body: String,
+    pub elapsed: Option<Duration>,
+}
+
+impl ActionResponse {
+    /// Header value by name, ignoring ASCII case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// The body parsed as JSON, if it is JSON.
+    pub fn json(&self) -> Option<JsonValue> {
+        serde_json::from_str(&self.body).ok()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Content,
+    JsonPath(JsonPath),
+    Regex(Regex),
+    Header(String),
+    Status,
+}
+
+/// Who sees an exported value.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportScope {
+    /// The virtual user that ran the action.
+    #[default]
+    User,
+    /// Every virtual user of the run.
+    Workbook,
+}
+
+impl ExportScope {
+    fn parse(scope: Option<&str>) -> Result<Self, String> {
+        match scope {
+            None | Some("user") => Ok(ExportScope::User),
+            Some("workbook") => Ok(ExportScope::Workbook),
+            Some(other) => Err(format!(
+                "unknown scope `{}`, expected `user` or `workbook`",
+                other
+            )),
+        }
+    }
+}
+
+/// A compiled extracting `export` entry.
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub name: String,
+    pub scope: ExportScope,
+    pub mode: ExportMode,
+    pub default: Option<String>,
+    source: Source,
+}
+
+impl Export {
+    /// Compiles `def`, an export of `action`. `assert` entries yield `None`;
+    /// see [`Assertion::compile`].
+    pub fn compile(action: &str, def: &ExportDef) -> Result<Option<Self>, SchedulerError> {
+        let invalid = invalid_export(action, def);
+        let source = match def.export_type.as_str() {
+            "content" => Source::Content,
+            "jsonpath" => {
+                Source::JsonPath(parse_json_path(required_expr(def, &invalid)?, &invalid)?)
+            }
+            "regex" => Source::Regex(
+                Regex::new(required_expr(def, &invalid)?)
+                    .map_err(|err| invalid(err.to_string()))?,
+            ),
+            "header" => Source::Header(def.expr.clone().unwrap_or_else(|| def.name.clone())),
+            "status" => Source::Status,
+            "assert" => return Ok(None),
+            other => {
+                return Err(invalid(format!(
+                    "unknown export type `{}`, expected one of: {}",
+                    other,
+                    EXPORT_TYPES.join(", ")
+                )));
+            }
+        };
+
+        Ok(Some(Self {
+            name: def.name.clone(),
+            scope: ExportScope::parse(def.scope.as_deref()).map_err(&invalid)?,
+            mode: def.mode,
+            default: def.default.as_ref().and_then(scalar_to_string),
+            source,
+        }))
+    }
+
+    /// The exported value: extracted from `response`, else the default.
+    pub fn evaluate(&self, response: Option<&ActionResponse>) -> Option<String> {
+        response
+            .and_then(|response| self.extract(response))
+            .or_else(|| self.default.clone())
+    }
+
+    fn extract(&self, response: &ActionResponse) -> Option<String> {
+        match &self.source {
+            Source::Content => response.json()?.get(&self.name).map(json_to_string),
+            Source::JsonPath(path) => {
+                let json = response.json()?;
+                path.query(&json).first().map(json_to_string)
+            }
+            Source::Regex(regex) => {
+                let captures = regex.captures(&response.body)?;
+                captures
+                    .get(1)
+                    .or_else(|| captures.get(0))
+                    .map(|m| m.as_str().to_string())
+            }
+            Source::Header(name) => response.header(name).map(str::to_string),
+            Source::Status => response.status_code.map(|code| code.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Check {
+    Status(Vec<u16>),
+    Contains(String),
+    JsonPath {
+        expr: String,
+        path: JsonPath,
+        equals: Option<String>,
+    },
+    Header(String),
+    MaxMs(u64),
+}
+
+impl Check {
+    fn verify(&self, response: &ActionResponse) -> Result<(), String> {
+        match self {
+            Check::Status(codes) => match response.status_code {
+                Some(code) if codes.contains(&code) => Ok(()),
+                Some(code) => Err(format!("status {} not in {:?}", code, codes)),
+                None => Err("no status code".to_string()),
+            },
+            Check::Contains(needle) if response.body.contains(needle.as_str()) => Ok(()),
+            Check::Contains(needle) => Err(format!("body does not contain {:?}", needle)),
+            Check::JsonPath { expr, path, equals } => {
+                let json = response
+                    .json()
+                    .ok_or_else(|| "body is not JSON".to_string())?;
+                let found = path
+                    .query(&json)
+                    .first()
+                    .map(json_to_string)
+                    .ok_or_else(|| format!("`{}` matched nothing", expr))?;
+                match equals {
+                    Some(expected) if *expected != found => Err(format!(
+                        "`{}` is {:?}, expected {:?}",
+                        expr, found, expected
+                    )),
+                    _ => Ok(()),
+                }
+            }
+            Check::Header(name) if response.header(name).is_some() => Ok(()),
+            Check::Header(name) => Err(format!("header `{}` is missing", name)),
+            Check::MaxMs(limit) => match response.elapsed {
+                Some(elapsed) if elapsed.as_millis() < u128::from(*limit) => Ok(()),
+                Some(elapsed) => Err(format!(
+                    "response took {}ms, limit {}ms",
+                    elapsed.as_millis(),
+                    limit
+                )),
+                None => Err("response time unknown".to_string()),
+            },
+        }
+    }
+}
+
+/// A compiled `type: assert` export entry.
+#[derive(Debug, Clone)]
+pub struct Assertion {
+    pub name: String,
+    checks: Vec<Check>,
+}
+
+impl Assertion {
+    /// Compiles `def` if it is an `assert` entry of `action`.
+    pub fn compile(action: &str, def: &ExportDef) -> Result<Option<Self>, SchedulerError> {
+        if def.export_type != "assert" {
+            return Ok(None);
+        }
+        let invalid = invalid_export(action, def);
+
+        let mut checks = Vec::new();
+        if let Some(status) = &def.status {
+            checks.push(Check::Status(status.codes()));
+        }
+        if let Some(needle) = &def.contains {
+            checks.push(Check::Contains(needle.clone()));
+        }
+        if let Some(expr) = &def.expr {
+            checks.push(Check::JsonPath {
+                expr: expr.clone(),
+                path: parse_json_path(expr, &invalid)?,
+                equals: def.equals.as_ref().and_then(scalar_to_string),
+            });
+        } else if def.equals.is_some() {
+            return Err(invalid("`equals` requires a JSONPath `expr`".to_string()));
+        }
+        if let Some(header) = &def.header {
+            checks.push(Check::Header(header.clone()));
+        }
+        if let Some(limit) = def.max_ms {
+            checks.push(Check::MaxMs(limit));
+        }
+        if checks.is_empty() {
+            return Err(invalid(
+                "`type: assert` needs at least one of `status`, `contains`, `expr`, `header`, `max_ms`"
+                    .to_string(),
+            ));
+        }
+
+        Ok(Some(Self {
+            name: def.name.clone(),
+            checks,
+        }))
+    }
+
+    /// `Err` carries the reason of the first failing check.
+    pub fn verify(&self, response: Option<&ActionResponse>) -> Result<(), String> {
+        let response = response.ok_or_else(|| "action produced no response".to_string())?;
+        self.checks
+            .iter()
+            .try_for_each(|check| check.verify(response))
+    }
+}
+
+/// Outcome of one assertion; `name` is `action-id.name`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssertionResult {
+    pub name: String,
+    pub failure: Option<String>,
+}
+
+impl AssertionResult {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+impl fmt::Display for AssertionResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.failure {
+            None => write!(f, "assertion `{}` passed", self.name),
+            Some(reason) => write!(f, "assertion `{}` failed: {}", self.name, reason),
+        }
+    }
+}
+
+/// Every export of one action, compiled.
+#[derive(Debug, Clone, Default)]
+pub struct ActionExports {
+    action_id: String,
+    exports: Vec<Export>,
+    assertions: Vec<Assertion>,
+}
+
+/// A `scope: workbook` value to publish to the run-wide store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkbookWrite {
+    /// `action-id.name`.
+    pub name: String,
+    pub value: String,
+    pub mode: ExportMode,
+}
+
+/// What [`ActionExports::evaluate`] produced for one response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportValues {
+    /// User-scoped `action-id.name` -> value, assertions included as
+    /// `true` / `false`.
+    pub vars: IndexMap<String, String>,
+    pub workbook: Vec<WorkbookWrite>,
+    pub assertions: Vec<AssertionResult>,
+}
+
+impl ActionExports {
+    pub fn compile(action: &ActionDef) -> Result<Self, SchedulerError> {
+        let mut exports = Vec::new();
+        let mut assertions = Vec::new();
+        for def in &action.export {
+            exports.extend(Export::compile(&action.id, def)?);
+            assertions.extend(Assertion::compile(&action.id, def)?);
+        }
+        Ok(Self {
+            action_id: action.id.clone(),
+            exports,
+            assertions,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.exports.is_empty() && self.assertions.is_empty()
+    }
+
+    pub fn evaluate(&self, response: Option<&ActionResponse>) -> ExportValues {
+        let mut values = ExportValues::default();
+        for export in &self.exports {
+            let Some(value) = export.evaluate(response) else {
+                continue;
+            };
+            let name = self.qualify(&export.name);
+            match export.scope {
+                ExportScope::User => {
+                    values.vars.insert(name, value);
+                }
+                ExportScope::Workbook => values.workbook.push(WorkbookWrite {
+                    name,
+                    value,
+                    mode: export.mode,
+                }),
+            }
+        }
+        for assertion in &self.assertions {
+            let name = self.qualify(&assertion.name);
+            let failure = assertion.verify(response).err();
+            values
+                .vars
+                .insert(name.clone(), failure.is_none().to_string());
+            values.assertions.push(AssertionResult { name, failure });
+        }
+        values
+    }
+
+    fn qualify(&self, name: &str) -> String {
+        format!("{}.{}", self.action_id, name)
+    }
+}
+
+/// Reasons of the failed assertions among `results`, joined; `None` if all
+/// passed.
+pub fn failure_reason(results: &[AssertionResult]) -> Option<String> {
+    let failed: Vec<String> = results
+        .iter()
+        .filter(|result| !result.passed())
+        .map(ToString::to_string)
+        .collect();
+    (!failed.is_empty()).then(|| failed.join("; "))
+}
+
+/// Per-assertion pass/fail counts over a run, in first-seen order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AssertionSummary {
+    counts: IndexMap<String, (usize, usize)>,
+}
+
+impl AssertionSummary {
+    pub fn record(&mut self, result: &AssertionResult) {
+        let (passed, failed) = self.counts.entry(result.name.clone()).or_default();
+        if result.passed() {
+            *passed += 1;
+        } else {
+            *failed += 1;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// `(name, passed, failed)` per assertion.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize, usize)> {
+        self.counts
+            .iter()
+            .map(|(name, (passed, failed))| (name.as_str(), *passed, *failed))
+    }
+}
+
+impl<'a> FromIterator<&'a AssertionResult> for AssertionSummary {
+    fn from_iter<I: IntoIterator<Item = &'a AssertionResult>>(iter: I) -> Self {
+        let mut summary = Self::default();
+        for result in iter {
+            summary.record(result);
+        }
+        summary
+    }
+}
+
+/// One line per assertion.
+impl fmt::Display for AssertionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, passed, failed) in self.iter() {
+            writeln!(f, "  {}: {} passed, {} failed", name, passed, failed)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid_export<'a>(
+    action: &'a str,
+    def: &'a ExportDef,
+) -> impl Fn(String) -> SchedulerError + 'a {
+    move |message| SchedulerError::InvalidExport {
+        action: action.to_string(),
+        name: def.name.clone(),
+        message,
+    }
+}
+
+fn required_expr<'a>(
+    def: &'a ExportDef,
+    invalid: &impl Fn(String) -> SchedulerError,
+) -> Result<&'a str, SchedulerError> {
+    def.expr
+        .as_deref()
+        .ok_or_else(|| invalid(format!("`type: {}` requires `expr`", def.export_type)))
+}
+
+fn parse_json_path(
+    expr: &str,
+    invalid: &impl Fn(String) -> SchedulerError,
+) -> Result<JsonPath, SchedulerError> {
+    JsonPath::parse(expr).map_err(|err| invalid(err.to_string()))
+}
+
+fn json_to_string(value: &JsonValue) -> String {
+    match value {
+        JsonValue::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        other => serde_json::to_string(other).ok(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn action(yaml: &str) -> ActionDef {
+        serde_yaml::from_str(yaml).expect("action yaml")
+    }
+
+    fn response() -> ActionResponse {
+        ActionResponse {
+            status_code: Some(201),
+            headers: IndexMap::from([("x-request-id".to_string(), "req-7".to_string())]),
+            body: r#"{"id": 42, "token": "abc", "items": [{"sku": "A-1"}]}"#.to_string(),
+            elapsed: Some(Duration::from_millis(120)),
+        }
+    }
+
+    #[test]
+    fn extracts_from_every_source() {
+        let exports = ActionExports::compile(&action(
+            r#"
+id: create
+call: post
+export:
+  - { type: content, name: token }
+  - { type: jsonpath, name: sku, expr: "$.items[0].sku" }
+  - { type: regex, name: id, expr: '"id":\s*(\d+)' }
+  - { type: header, name: request, expr: X-Request-Id }
+  - { type: status, name: status_code }
+"#,
+        ))
+        .expect("compile");
+        let vars = exports.evaluate(Some(&response())).vars;
+
+        assert_eq!(vars.len(), 5);
+        assert_eq!(vars["create.token"], "abc");
+        assert_eq!(vars["create.sku"], "A-1");
+        assert_eq!(vars["create.id"], "42");
+        assert_eq!(vars["create.request"], "req-7");
+        assert_eq!(vars["create.status_code"], "201");
+    }
+
+    #[test]
+    fn falls_back_to_default_when_extraction_fails() {
+        let exports = ActionExports::compile(&action(
+            r#"
+id: get
+call: get
+export:
+  - { type: jsonpath, name: missing, expr: "$.nope", default: 0 }
+  - { type: content, name: token, default: none }
+  - { type: header, name: etag }
+"#,
+        ))
+        .expect("compile");
+
+        let vars = exports.evaluate(Some(&response())).vars;
+        assert_eq!(vars["get.missing"], "0");
+        assert_eq!(vars["get.token"], "abc");
+        assert!(!vars.contains_key("get.etag"));
+
+        let vars = exports.evaluate(None).vars;
+        assert_eq!(vars["get.token"], "none");
+    }
+
+    #[test]
+    fn workbook_scoped_exports_are_returned_separately() {
+        let exports = ActionExports::compile(&action(
+            r#"
+id: login
+call: post
+export:
+  - { type: content, name: token, scope: workbook, mode: first_writer_wins }
+  - { type: status, name: status_code, scope: user }
+"#,
+        ))
+        .expect("compile");
+        let values = exports.evaluate(Some(&response()));
+
+        assert_eq!(
+            values.vars.keys().collect::<Vec<_>>(),
+            vec!["login.status_code"]
+        );
+        assert_eq!(
+            values.workbook,
+            vec![WorkbookWrite {
+                name: "login.token".into(),
+                value: "abc".into(),
+                mode: ExportMode::FirstWriterWins,
+            }]
+        );
+    }
+
+    #[test]
+    fn assertions_report_each_failure() {
+        let exports = ActionExports::compile(&action(
+            r#"
+id: create
+call: post
+export:
+  - { type: assert, name: created, status: [200, 201], max_ms: 500 }
+  - { type: assert, name: token, expr: "$.token", equals: abc, header: X-Request-Id }
+  - { type: assert, name: fast, max_ms: 100 }
+  - { type: assert, name: ok, status: 200, contains: '"id"' }
+"#,
+        ))
+        .expect("compile");
+        let values = exports.evaluate(Some(&response()));
+
+        let passed: Vec<_> = values.assertions.iter().map(|r| r.passed()).collect();
+        assert_eq!(passed, vec![true, true, false, false]);
+        assert_eq!(values.vars["create.created"], "true");
+        assert_eq!(values.vars["create.fast"], "false");
+        assert_eq!(
+            failure_reason(&values.assertions).expect("failures"),
+            "assertion `create.fast` failed: response took 120ms, limit 100ms; \
+             assertion `create.ok` failed: status 201 not in [200]"
+        );
+
+        let summary: AssertionSummary = values
+            .assertions
+            .iter()
+            .chain(exports.evaluate(None).assertions.iter())
+            .collect();
+        assert_eq!(
+            summary.iter().next(),
+            Some(("create.created", 1, 1)),
+            "{summary}"
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        for export in [
+            "{ type: jsonpath, name: v }",
+            "{ type: jsonpath, name: v, expr: 'items[' }",
+            "{ type: regex, name: v, expr: '(' }",
+            "{ type: xpath, name: v, expr: //id }",
+            "{ type: assert, name: v }",
+            "{ type: assert, name: v, equals: 1 }",
+            "{ type: status, name: v, scope: tenant }",
+        ] {
+            let def = action(&format!("{{ id: get, call: get, export: [{}] }}", export));
+            let err = ActionExports::compile(&def).expect_err(export);
+            assert!(
+                matches!(err, SchedulerError::InvalidExport { ref action, .. } if action == "get"),
+                "{err}"
+            );
+        }
+    }
+}
@@ -0,0 +1,450 @@
+/// Workflow Graph Rendering
+///
+/// Renders a [`WbsTree`] or [`StateMachine`] as Graphviz DOT or Mermaid.
+/// Action nodes are labelled with their node id, action id and call; edges
+/// with their label, or their condition when unlabelled. An [`EdgeHits`]
+/// overlay from a finished run adds the number of times each edge was taken
+/// and dims the edges that never were.
+use std::fmt::{self, Write as _};
+use std::str::FromStr;
+
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+
+use crate::state_machine::{StateMachine, StateNodeKind, Trigger};
+use crate::wbs::{WbsTaskKind, WbsTree};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            other => Err(format!(
+                "unknown graph format `{}`, expected dot|mermaid",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphNode {
+    pub id: String,
+    pub end: bool,
+    /// `(action id, call)` of an action node.
+    pub action: Option<(String, String)>,
+}
+
+impl GraphNode {
+    fn label(&self) -> Vec<String> {
+        let mut lines = vec![self.id.clone()];
+        if let Some((action, call)) = &self.action {
+            lines.push(format!("{} ({})", action, call.to_uppercase()));
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub label: Option<String>,
+    /// Times the edge was taken, once an [`EdgeHits`] overlay is applied.
+    pub hits: Option<u64>,
+}
+
+impl GraphEdge {
+    fn label(&self) -> Option<String> {
+        match (&self.label, self.hits) {
+            (Some(label), Some(hits)) => Some(format!("{} [{}]", label, hits)),
+            (Some(label), None) => Some(label.clone()),
+            (None, Some(hits)) => Some(format!("[{}]", hits)),
+            (None, None) => None,
+        }
+    }
+
+    fn untaken(&self) -> bool {
+        self.hits == Some(0)
+    }
+}
+
+/// Renderer-neutral view of a workflow.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Graph {
+    pub name: String,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl Graph {
+    pub fn from_wbs(tree: &WbsTree) -> Self {
+        let mut graph = Self {
+            name: tree.name.clone(),
+            ..Self::default()
+        };
+        // Group tasks only structure progress; they are not part of the flow
+        for task in tree.tasks.values().filter(|t| t.kind != WbsTaskKind::Group) {
+            let action = task.action_id.as_ref().map(|action_id| {
+                let call = tree
+                    .actions
+                    .get(action_id)
+                    .map(|action| action.call.clone())
+                    .unwrap_or_else(|| "?".to_string());
+                (action_id.clone(), call)
+            });
+            graph.nodes.push(GraphNode {
+                id: task.id.clone(),
+                end: task.kind == WbsTaskKind::End,
+                action,
+            });
+            for edge in &task.outgoing {
+                graph.edges.push(GraphEdge {
+                    from: task.id.clone(),
+                    to: edge.target.clone(),
+                    label: edge_label(
+                        edge.label.as_deref(),
+                        edge.condition.as_ref().map(|c| c.source()),
+                    ),
+                    hits: None,
+                });
+            }
+        }
+        graph
+    }
+
+    pub fn from_state_machine(name: &str, machine: &StateMachine) -> Self {
+        let mut graph = Self {
+            name: name.to_string(),
+            ..Self::default()
+        };
+        for node in machine.nodes.values() {
+            graph.nodes.push(GraphNode {
+                id: node.id.clone(),
+                end: node.kind == StateNodeKind::End,
+                action: node
+                    .action
+                    .as_ref()
+                    .map(|action| (action.id.clone(), action.call.clone())),
+            });
+            for transition in &node.transitions {
+                let condition = match &transition.trigger {
+                    Trigger::Always => None,
+                    Trigger::Condition(condition) => Some(condition.source()),
+                };
+                graph.edges.push(GraphEdge {
+                    from: node.id.clone(),
+                    to: transition.to.clone(),
+                    label: edge_label(transition.label.as_deref(), condition),
+                    hits: None,
+                });
+            }
+        }
+        graph
+    }
+
+    /// Sets the hit count of every edge; edges missing from `hits` get 0.
+    pub fn with_hits(mut self, hits: &EdgeHits) -> Self {
+        for edge in &mut self.edges {
+            edge.hits = Some(hits.get(&edge.from, &edge.to));
+        }
+        self
+    }
+
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let mut out = String::new();
+        let name = if self.name.is_empty() {
+            "workflow"
+        } else {
+            &self.name
+        };
+        let _ = writeln!(out, "digraph {} {{", dot_quote(name));
+        let _ = writeln!(out, "  node [shape=box];");
+        for node in &self.nodes {
+            let shape = if node.end { ", shape=doublecircle" } else { "" };
+            let _ = writeln!(
+                out,
+                "  {} [label={}{}];",
+                dot_quote(&node.id),
+                dot_quote(&node.label().join("\n")),
+                shape
+            );
+        }
+        for edge in &self.edges {
+            let mut attrs = Vec::new();
+            if let Some(label) = edge.label() {
+                attrs.push(format!("label={}", dot_quote(&label)));
+            }
+            match edge.hits {
+                Some(0) => attrs.push("style=dashed, color=gray".to_string()),
+                Some(_) => attrs.push("penwidth=2".to_string()),
+                None => {}
+            }
+            let attrs = if attrs.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", attrs.join(", "))
+            };
+            let _ = writeln!(
+                out,
+                "  {} -> {}{};",
+                dot_quote(&edge.from),
+                dot_quote(&edge.to),
+                attrs
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    /// Node ids are replaced by `n0`, `n1`, ... since Mermaid ids cannot
+    /// hold every character a workflow node id may contain.
+    pub fn to_mermaid(&self) -> String {
+        let mut ids: IndexMap<String, String> = IndexMap::new();
+        let mut id_of = |id: &str| -> String {
+            let next = format!("n{}", ids.len());
+            ids.entry(id.to_string()).or_insert(next).clone()
+        };
+
+        let mut out = String::from("flowchart TD\n");
+        for node in &self.nodes {
+            let label = mermaid_quote(&node.label().join("<br/>"));
+            let id = id_of(&node.id);
+            if node.end {
+                let _ = writeln!(out, "  {}(({}))", id, label);
+            } else {
+                let _ = writeln!(out, "  {}[{}]", id, label);
+            }
+        }
+        for edge in &self.edges {
+            let arrow = if edge.untaken() { "-.->" } else { "-->" };
+            let from = id_of(&edge.from);
+            let to = id_of(&edge.to);
+            match edge.label() {
+                Some(label) => {
+                    let _ = writeln!(
+                        out,
+                        "  {} {}|{}| {}",
+                        from,
+                        arrow,
+                        mermaid_quote(&label),
+                        to
+                    );
+                }
+                None => {
+                    let _ = writeln!(out, "  {} {} {}", from, arrow, to);
+                }
+            }
+        }
+        out
+    }
+}
+
+/// One edge of an [`EdgeHits`] file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EdgeHit {
+    pub from: String,
+    pub to: String,
+    pub hits: u64,
+}
+
+/// How often each workflow edge was taken during a run; serialized as a
+/// list of [`EdgeHit`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "Vec<EdgeHit>", into = "Vec<EdgeHit>")]
+pub struct EdgeHits {
+    counts: IndexMap<(String, String), u64>,
+}
+
+impl EdgeHits {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, from: &str, to: &str) {
+        *self
+            .counts
+            .entry((from.to_string(), to.to_string()))
+            .or_default() += 1;
+    }
+
+    pub fn get(&self, from: &str, to: &str) -> u64 {
+        self.counts
+            .get(&(from.to_string(), to.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("edge hits serialize")
+    }
+
+    pub fn from_json(input: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(input)
+    }
+}
+
+impl From<Vec<EdgeHit>> for EdgeHits {
+    fn from(edges: Vec<EdgeHit>) -> Self {
+        let mut hits = Self::new();
+        for edge in edges {
+            *hits.counts.entry((edge.from, edge.to)).or_default() += edge.hits;
+        }
+        hits
+    }
+}
+
+impl From<EdgeHits> for Vec<EdgeHit> {
+    fn from(hits: EdgeHits) -> Self {
+        hits.counts
+            .into_iter()
+            .map(|((from, to), hits)| EdgeHit { from, to, hits })
+            .collect()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, &'a str)> for EdgeHits {
+    fn from_iter<I: IntoIterator<Item = (&'a str, &'a str)>>(iter: I) -> Self {
+        let mut hits = Self::new();
+        for (from, to) in iter {
+            hits.record(from, to);
+        }
+        hits
+    }
+}
+
+/// One line per edge, in first-taken order.
+impl fmt::Display for EdgeHits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((from, to), hits) in &self.counts {
+            writeln!(f, "  {} -> {}: {}", from, to, hits)?;
+        }
+        Ok(())
+    }
+}
+
+fn edge_label(label: Option<&str>, condition: Option<&str>) -> Option<String> {
+    label.or(condition).map(str::to_string)
+}
+
+fn dot_quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n");
+    format!("\"{}\"", escaped)
+}
+
+fn mermaid_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "#quot;"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::Scenario;
+
+    const SCENARIO: &str = r#"
+version: "1.0"
+name: branching
+actions:
+  actions:
+    - id: probe
+      call: get
+    - id: retry
+      call: post
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      edges:
+        - to: retry-node
+          trigger:
+            condition: 'probe.status_code != "200"'
+        - to: end
+          label: ok
+    - id: retry-node
+      type: action
+      action: retry
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#;
+
+    fn graph() -> Graph {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        Graph::from_wbs(&WbsTree::build(&scenario).expect("build"))
+    }
+
+    #[test]
+    fn renders_dot() {
+        let dot = graph().to_dot();
+        assert!(dot.starts_with("digraph \"branching\" {\n"), "{dot}");
+        assert!(
+            dot.contains(r#""start" [label="start\nprobe (GET)"];"#),
+            "{dot}"
+        );
+        assert!(
+            dot.contains(r#""end" [label="end", shape=doublecircle];"#),
+            "{dot}"
+        );
+        assert!(
+            dot.contains(r#""start" -> "retry-node" [label="probe.status_code != \"200\""];"#),
+            "{dot}"
+        );
+        assert!(dot.contains(r#""retry-node" -> "end";"#), "{dot}");
+    }
+
+    #[test]
+    fn renders_mermaid_with_hits() {
+        let hits: EdgeHits = [("start", "end"), ("start", "end")].into_iter().collect();
+        let mermaid = graph().with_hits(&hits).to_mermaid();
+        assert_eq!(
+            mermaid,
+            "flowchart TD\n\
+             \x20 n0[\"start<br/>probe (GET)\"]\n\
+             \x20 n1[\"retry-node<br/>retry (POST)\"]\n\
+             \x20 n2((\"end\"))\n\
+             \x20 n0 -.->|\"probe.status_code != #quot;200#quot; [0]\"| n1\n\
+             \x20 n0 -->|\"ok [2]\"| n2\n\
+             \x20 n1 -.->|\"[0]\"| n2\n"
+        );
+    }
+
+    #[test]
+    fn state_machine_matches_wbs_and_hits_round_trip() {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build");
+        let machine = StateMachine::from_wbs(&tree);
+        assert_eq!(Graph::from_state_machine("branching", &machine), graph());
+
+        let hits: EdgeHits = [("start", "retry-node"), ("retry-node", "end")]
+            .into_iter()
+            .collect();
+        let parsed = EdgeHits::from_json(&hits.to_json()).expect("json");
+        assert_eq!(parsed, hits);
+        assert_eq!(parsed.get("start", "retry-node"), 1);
+        assert_eq!(parsed.get("start", "end"), 0);
+    }
+}
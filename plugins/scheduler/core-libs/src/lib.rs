@@ -1,18 +1,34 @@
+pub mod analysis;
+pub mod condition;
+pub mod data;
 pub mod dsl;
 pub mod error;
+pub mod export;
+pub mod graph;
 pub mod ip;
+pub mod secrets;
 pub mod socket;
 pub mod state_machine;
+pub mod variables;
 pub mod wbs;
 pub mod workbook;
 
 #[cfg(target_arch = "wasm32")]
 pub mod component;
 
+pub use analysis::{Diagnostic, Severity, ValidationReport};
+pub use condition::{Condition, ConditionError, ConditionValue, VariableResolver};
+pub use data::{Feeder, FeederSet};
 pub use dsl::*;
 pub use error::SchedulerError;
+pub use export::{
+    ActionExports, ActionResponse, AssertionResult, AssertionSummary, ExportScope, WorkbookWrite,
+};
+pub use graph::{EdgeHits, Graph, GraphFormat};
 pub use ip::{IpBinding, IpPool, IpPoolError, IpRange, PoolStats, ResourceType};
+pub use secrets::Secrets;
 pub use socket::{AddressFamily, Socket, SocketAddress, SocketError, SocketHandle, SocketProtocol};
 pub use state_machine::StateMachine;
+pub use variables::{Variable, VariableSource, Variables};
 pub use wbs::{WbsEdge, WbsTask, WbsTaskKind, WbsTree};
 pub use workbook::Workbook;
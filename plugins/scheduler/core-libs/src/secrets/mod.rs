@@ -0,0 +1,220 @@
+/// Scenario Secrets
+///
+/// Resolves the `secrets:` section from environment variables or from files
+/// next to the scenario (preopened as `.` in the WASI component). A secret
+/// is exposed to templates as `{{name}}`.
+///
+/// Every resolved value is tracked in a process-wide registry so that
+/// console output, trace details and reports can be passed through
+/// [`redact`] before they are printed.
+use std::fmt;
+use std::path::Path;
+use std::sync::RwLock;
+
+use indexmap::IndexMap;
+
+use crate::dsl::{Scenario, SecretDef};
+use crate::error::SchedulerError;
+use crate::variables::{MASK, Variables};
+
+/// Values masked by [`redact`], longest first so that a secret containing
+/// another one is masked as a whole.
+static TRACKED: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Where a secret was read from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SecretSource {
+    Env(String),
+    File(String),
+}
+
+impl fmt::Display for SecretSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecretSource::Env(name) => write!(f, "env {}", name),
+            SecretSource::File(path) => write!(f, "file {}", path),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Secret {
+    value: String,
+    source: SecretSource,
+}
+
+/// Resolved secrets, in declaration order.
+#[derive(Debug, Clone, Default)]
+pub struct Secrets {
+    entries: IndexMap<String, Secret>,
+}
+
+impl Secrets {
+    /// Reads every secret of `scenario`: `env` entries through `env`, `file`
+    /// entries relative to `base_dir`. Resolved values are tracked.
+    pub fn resolve(
+        scenario: &Scenario,
+        env: impl Fn(&str) -> Option<String>,
+        base_dir: &Path,
+    ) -> Result<Self, SchedulerError> {
+        let mut entries = IndexMap::new();
+        for (name, def) in &scenario.secrets {
+            let secret_error = |message: String| SchedulerError::Secret {
+                name: name.clone(),
+                message,
+            };
+            let secret = match def {
+                SecretDef {
+                    env: Some(var),
+                    file: None,
+                } => Secret {
+                    value: env(var).ok_or_else(|| {
+                        secret_error(format!("environment variable `{}` is not set", var))
+                    })?,
+                    source: SecretSource::Env(var.clone()),
+                },
+                SecretDef {
+                    env: None,
+                    file: Some(file),
+                } => {
+                    let path = base_dir.join(file);
+                    let raw = std::fs::read_to_string(&path)
+                        .map_err(|err| secret_error(format!("{}: {}", path.display(), err)))?;
+                    Secret {
+                        value: raw.trim_end_matches(['\n', '\r']).to_string(),
+                        source: SecretSource::File(file.clone()),
+                    }
+                }
+                _ => return Err(secret_error("set exactly one of `env` and `file`".into())),
+            };
+            if secret.value.is_empty() {
+                return Err(secret_error(format!("{} is empty", secret.source)));
+            }
+            track(&secret.value);
+            entries.insert(name.clone(), secret);
+        }
+        Ok(Self { entries })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Plain `name -> value` pairs for template rendering.
+    pub fn values(&self) -> IndexMap<String, String> {
+        self.entries
+            .iter()
+            .map(|(name, secret)| (name.clone(), secret.value.clone()))
+            .collect()
+    }
+}
+
+/// One line per secret; values are never printed.
+impl fmt::Display for Secrets {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, secret) in &self.entries {
+            writeln!(f, "  {} = {} ({})", name, MASK, secret.source)?;
+        }
+        Ok(())
+    }
+}
+
+/// Environment variables read by the `env` secrets of `scenario`, for a
+/// host that forwards them to the component.
+pub fn env_names(scenario: &Scenario) -> impl Iterator<Item = &str> {
+    scenario
+        .secrets
+        .values()
+        .filter_map(|def| def.env.as_deref())
+}
+
+/// Adds `value` to the values masked by [`redact`].
+pub fn track(value: &str) {
+    if value.is_empty() {
+        return;
+    }
+    let mut tracked = TRACKED.write().expect("secret registry lock poisoned");
+    if !tracked.iter().any(|known| known == value) {
+        tracked.push(value.to_string());
+        tracked.sort_by_key(|known| std::cmp::Reverse(known.len()));
+    }
+}
+
+/// Tracks the values of variables declared with `secret: true`.
+pub fn track_variables(variables: &Variables) {
+    for (_, var) in variables.iter().filter(|(_, var)| var.secret) {
+        if let Some(value) = &var.value {
+            track(value);
+        }
+    }
+}
+
+/// `text` with every tracked secret value replaced by [`MASK`].
+pub fn redact(text: &str) -> String {
+    let tracked = TRACKED.read().expect("secret registry lock poisoned");
+    let mut redacted = text.to_string();
+    for value in tracked.iter() {
+        if redacted.contains(value.as_str()) {
+            redacted = redacted.replace(value.as_str(), MASK);
+        }
+    }
+    redacted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+version: "1.0"
+name: secrets
+secrets:
+  tenant.token:
+    env: SECRETS_TEST_TOKEN
+  db_password:
+    file: db_password.txt
+"#;
+
+    #[test]
+    fn resolves_env_and_file_secrets() {
+        let dir = std::env::temp_dir().join(format!("scheduler-secrets-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create dir");
+        std::fs::write(dir.join("db_password.txt"), "pa55-from-file\n").expect("write secret");
+
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        let secrets = Secrets::resolve(
+            &scenario,
+            |var| (var == "SECRETS_TEST_TOKEN").then(|| "tok-123456".to_string()),
+            &dir,
+        )
+        .expect("resolve");
+        std::fs::remove_dir_all(&dir).ok();
+
+        let values = secrets.values();
+        assert_eq!(values["tenant.token"], "tok-123456");
+        assert_eq!(values["db_password"], "pa55-from-file");
+        assert_eq!(
+            env_names(&scenario).collect::<Vec<_>>(),
+            ["SECRETS_TEST_TOKEN"]
+        );
+
+        let printed = secrets.to_string();
+        assert!(printed.contains("tenant.token = ****** (env SECRETS_TEST_TOKEN)"));
+        assert!(!printed.contains("tok-123456"));
+        assert_eq!(
+            redact("GET http://h/?t=tok-123456 pw=pa55-from-file"),
+            "GET http://h/?t=****** pw=******"
+        );
+    }
+
+    #[test]
+    fn unresolved_secret_is_an_error() {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        let err = Secrets::resolve(&scenario, |_| None, Path::new("/nonexistent"))
+            .expect_err("token unset");
+        assert!(
+            matches!(&err, SchedulerError::Secret { name, .. } if name == "tenant.token"),
+            "{err}"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use indexmap::IndexMap;
 
-use crate::dsl::ActionDef;
+use crate::condition::{Condition, VariableResolver};
+use crate::dsl::{ActionDef, ThinkTimeDef};
 use crate::wbs::{WbsTask, WbsTaskKind, WbsTree};
 
 #[derive(Debug, Clone)]
@@ -13,7 +14,7 @@ impl StateMachine {
         let nodes = tree
             .tasks
             .values()
-            .map(|task| (task.id.clone(), StateNode::from_task(task, tree)))
+            .filter_map(|task| Some((task.id.clone(), StateNode::from_task(task, tree)?)))
             .collect();
 
         Self { nodes }
@@ -27,9 +28,16 @@ impl StateMachine {
         self.nodes.values().map(|node| node.transitions.len()).sum()
     }
 
+    /// Group tasks are structural and have no state node.
     pub fn sync_task(&mut self, task: &WbsTask, tree: &WbsTree) {
-        let node = StateNode::from_task(task, tree);
-        self.nodes.insert(task.id.clone(), node);
+        match StateNode::from_task(task, tree) {
+            Some(node) => {
+                self.nodes.insert(task.id.clone(), node);
+            }
+            None => {
+                self.nodes.shift_remove(&task.id);
+            }
+        }
     }
 
     pub fn remove_task(&mut self, task_id: &str) -> Option<StateNode> {
@@ -54,10 +62,19 @@ pub struct StateNode {
     pub kind: StateNodeKind,
     pub action: Option<ActionDef>,
     pub transitions: Vec<StateTransition>,
+    /// Visits allowed per user iteration, if limited.
+    pub max_visits: Option<usize>,
+    /// Pause after the node's action.
+    pub think_time: Option<ThinkTimeDef>,
 }
 
 impl StateNode {
-    fn from_task(task: &WbsTask, tree: &WbsTree) -> Self {
+    fn from_task(task: &WbsTask, tree: &WbsTree) -> Option<Self> {
+        let kind = match task.kind {
+            WbsTaskKind::Action => StateNodeKind::Action,
+            WbsTaskKind::End => StateNodeKind::End,
+            WbsTaskKind::Group => return None,
+        };
         let action = task
             .action_id
             .as_ref()
@@ -72,18 +89,21 @@ impl StateNode {
                 trigger: edge
                     .condition
                     .as_ref()
-                    .map(|cond| Trigger::Condition(cond.clone()))
+                    .cloned()
+                    .map(Trigger::Condition)
                     .unwrap_or(Trigger::Always),
                 label: edge.label.clone(),
             })
             .collect();
 
-        Self {
+        Some(Self {
             id: task.id.clone(),
-            kind: StateNodeKind::from(task.kind),
+            kind,
             action,
             transitions,
-        }
+            max_visits: task.max_visits,
+            think_time: task.think_time.clone(),
+        })
     }
 }
 
@@ -93,15 +113,6 @@ pub enum StateNodeKind {
     End,
 }
 
-impl From<WbsTaskKind> for StateNodeKind {
-    fn from(value: WbsTaskKind) -> Self {
-        match value {
-            WbsTaskKind::Action => StateNodeKind::Action,
-            WbsTaskKind::End => StateNodeKind::End,
-        }
-    }
-}
-
 #[derive(Debug, Clone)]
 pub struct StateTransition {
     pub to: String,
@@ -112,7 +123,19 @@ pub struct StateTransition {
 #[derive(Debug, Clone)]
 pub enum Trigger {
     Always,
-    Condition(String),
+    Condition(Condition),
+}
+
+impl Trigger {
+    pub fn is_satisfied<R>(&self, vars: &R) -> bool
+    where
+        R: VariableResolver + ?Sized,
+    {
+        match self {
+            Trigger::Always => true,
+            Trigger::Condition(condition) => condition.evaluate(vars),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -134,6 +157,10 @@ mod tests {
         let scenario = Scenario {
             version: "1.0".into(),
             name: "branchy_sm".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
             workbook: WorkbookSection::default(),
             actions: ActionsSection {
                 actions: vec![ActionDef {
@@ -148,6 +175,10 @@ mod tests {
                     WorkflowNode {
                         id: "start".into(),
                         node_type: WorkflowNodeType::Action,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
                         action: Some("action-a".into()),
                         edges: vec![
                             WorkflowEdge {
@@ -167,17 +198,26 @@ mod tests {
                     WorkflowNode {
                         id: "success".into(),
                         node_type: WorkflowNodeType::End,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
                         action: None,
                         edges: vec![],
                     },
                     WorkflowNode {
                         id: "fail".into(),
                         node_type: WorkflowNodeType::End,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
                         action: None,
                         edges: vec![],
                     },
                 ],
             },
+            load: None,
         };
 
         WbsTree::build(&scenario).expect("branchy tree")
@@ -202,8 +242,8 @@ mod tests {
         assert_eq!(start.transitions.len(), 2);
 
         match &start.transitions[0].trigger {
-            Trigger::Condition(expr) => {
-                assert_eq!(expr, "{{action-a.status == 200}}");
+            Trigger::Condition(condition) => {
+                assert_eq!(condition.source(), "{{action-a.status == 200}}");
             }
             Trigger::Always => panic!("expected conditional trigger"),
         }
@@ -214,6 +254,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn triggers_evaluate_compiled_conditions() {
+        let tree = branchy_tree();
+        let machine = StateMachine::from_wbs(&tree);
+        let start = machine.nodes.get("start").expect("start node");
+
+        let mut vars = IndexMap::new();
+        vars.insert("action-a.status".to_string(), "200".to_string());
+        assert!(start.transitions[0].trigger.is_satisfied(&vars));
+
+        vars.insert("action-a.status".to_string(), "500".to_string());
+        assert!(!start.transitions[0].trigger.is_satisfied(&vars));
+        assert!(start.transitions[1].trigger.is_satisfied(&vars));
+    }
+
     #[test]
     fn dynamic_sync_updates_nodes() {
         let mut tree = branchy_tree();
@@ -224,6 +279,10 @@ mod tests {
             action_id: Some("action-a".into()),
             kind: WbsTaskKind::Action,
             outgoing: vec![],
+            parent: None,
+            weight: 1.0,
+            max_visits: None,
+            think_time: None,
         };
 
         tree.insert_task(dynamic.clone());
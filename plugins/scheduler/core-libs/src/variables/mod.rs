@@ -0,0 +1,285 @@
+/// Scenario Variables
+///
+/// Effective values of the `variables:` section. Declared defaults are
+/// overridden by `NTX_VAR_*` environment variables, which are in turn
+/// overridden by `--var key=value` on the command line.
+use std::fmt;
+
+use indexmap::IndexMap;
+use serde_yaml::Value;
+
+use crate::dsl::{Scenario, VariableDef};
+use crate::error::SchedulerError;
+
+/// Prefix of environment variables that override scenario variables.
+pub const ENV_PREFIX: &str = "NTX_VAR_";
+
+/// Replacement printed instead of a secret value.
+pub const MASK: &str = "******";
+
+/// Where the effective value of a variable came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableSource {
+    Default,
+    Env,
+    Cli,
+}
+
+impl fmt::Display for VariableSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            VariableSource::Default => "default",
+            VariableSource::Env => "env",
+            VariableSource::Cli => "--var",
+        };
+        f.write_str(label)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variable {
+    pub value: Option<String>,
+    pub secret: bool,
+    pub source: VariableSource,
+}
+
+impl Variable {
+    /// The value as it may be printed: secrets are masked.
+    pub fn display_value(&self) -> &str {
+        match &self.value {
+            Some(_) if self.secret => MASK,
+            Some(value) => value,
+            None => "<unset>",
+        }
+    }
+}
+
+/// Effective scenario variables, in declaration order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Variables {
+    entries: IndexMap<String, Variable>,
+}
+
+impl Variables {
+    /// Defaults declared in the scenario's `variables:` section.
+    pub fn from_scenario(scenario: &Scenario) -> Self {
+        let entries = scenario
+            .variables
+            .iter()
+            .map(|(name, def)| {
+                let (default, secret) = match def {
+                    VariableDef::Detailed {
+                        default, secret, ..
+                    } => (default.as_ref(), *secret),
+                    VariableDef::Value(value) => (Some(value), false),
+                };
+                let variable = Variable {
+                    value: default.and_then(scalar_to_string),
+                    secret,
+                    source: VariableSource::Default,
+                };
+                (name.clone(), variable)
+            })
+            .collect();
+        Self { entries }
+    }
+
+    /// Defaults overridden by `env` (filtered on [`ENV_PREFIX`]) and then by
+    /// `overrides`. Fails if a declared variable ends up without a value.
+    pub fn resolve(
+        scenario: &Scenario,
+        env: impl IntoIterator<Item = (String, String)>,
+        overrides: &[(String, String)],
+    ) -> Result<Self, SchedulerError> {
+        let mut variables = Self::from_scenario(scenario);
+        variables.apply_env(env);
+        variables.apply_overrides(overrides);
+        variables.check_complete()?;
+        Ok(variables)
+    }
+
+    /// Applies `NTX_VAR_<NAME>` entries. `<NAME>` matches a declared
+    /// variable case-insensitively; otherwise it is added in lower case.
+    pub fn apply_env(&mut self, env: impl IntoIterator<Item = (String, String)>) {
+        for (key, value) in env {
+            let Some(suffix) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            if suffix.is_empty() {
+                continue;
+            }
+            let name = self
+                .entries
+                .keys()
+                .find(|name| name.eq_ignore_ascii_case(suffix))
+                .cloned()
+                .unwrap_or_else(|| suffix.to_ascii_lowercase());
+            self.set(name, value, VariableSource::Env);
+        }
+    }
+
+    pub fn apply_overrides(&mut self, overrides: &[(String, String)]) {
+        for (name, value) in overrides {
+            self.set(name.clone(), value.clone(), VariableSource::Cli);
+        }
+    }
+
+    pub fn check_complete(&self) -> Result<(), SchedulerError> {
+        match self.entries.iter().find(|(_, var)| var.value.is_none()) {
+            Some((name, _)) => Err(SchedulerError::MissingVariable(name.clone())),
+            None => Ok(()),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Variable> {
+        self.entries.get(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Variable)> {
+        self.entries.iter().map(|(name, var)| (name.as_str(), var))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Plain `name -> value` pairs for template rendering.
+    pub fn values(&self) -> IndexMap<String, String> {
+        self.entries
+            .iter()
+            .filter_map(|(name, var)| Some((name.clone(), var.value.clone()?)))
+            .collect()
+    }
+
+    /// `name=value` pairs in the [`ENV_PREFIX`] form, for handing the
+    /// effective values to a component through its environment.
+    pub fn to_env(&self) -> Vec<(String, String)> {
+        self.values()
+            .into_iter()
+            .map(|(name, value)| (format!("{}{}", ENV_PREFIX, name), value))
+            .collect()
+    }
+
+    fn set(&mut self, name: String, value: String, source: VariableSource) {
+        let entry = self.entries.entry(name).or_insert(Variable {
+            value: None,
+            secret: false,
+            source,
+        });
+        entry.value = Some(value);
+        entry.source = source;
+    }
+}
+
+/// One line per variable, secrets masked.
+impl fmt::Display for Variables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, var) in &self.entries {
+            writeln!(f, "  {} = {} ({})", name, var.display_value(), var.source)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a `--var key=value` argument.
+pub fn parse_override(arg: &str) -> Result<(String, String), SchedulerError> {
+    match arg.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => {
+            Ok((key.trim().to_string(), value.to_string()))
+        }
+        _ => Err(SchedulerError::InvalidVariableOverride(arg.to_string())),
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        other => serde_yaml::to_string(other)
+            .ok()
+            .map(|s| s.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCENARIO: &str = r#"
+version: "1.0"
+name: vars
+variables:
+  base_url: "http://127.0.0.1:8080"
+  users: 10
+  api_token:
+    secret: true
+    default: "s3cr3t"
+  region:
+    description: "required, no default"
+"#;
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn cli_overrides_env_overrides_defaults() {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        let variables = Variables::resolve(
+            &scenario,
+            env(&[
+                ("NTX_VAR_USERS", "20"),
+                ("NTX_VAR_REGION", "eu"),
+                ("NTX_VAR_EXTRA", "x"),
+                ("HOME", "/root"),
+            ]),
+            &[("users".to_string(), "30".to_string())],
+        )
+        .expect("resolve");
+
+        let values = variables.values();
+        assert_eq!(values["base_url"], "http://127.0.0.1:8080");
+        assert_eq!(values["users"], "30");
+        assert_eq!(values["region"], "eu");
+        assert_eq!(values["extra"], "x");
+        assert!(!values.contains_key("home"));
+        assert_eq!(variables.get("users").unwrap().source, VariableSource::Cli);
+        assert_eq!(variables.get("region").unwrap().source, VariableSource::Env);
+    }
+
+    #[test]
+    fn missing_required_variable_is_an_error() {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        let err = Variables::resolve(&scenario, Vec::new(), &[]).expect_err("region unset");
+        assert!(matches!(err, SchedulerError::MissingVariable(name) if name == "region"));
+    }
+
+    #[test]
+    fn display_masks_secrets() {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("parse");
+        let variables =
+            Variables::resolve(&scenario, env(&[("NTX_VAR_REGION", "eu")]), &[]).expect("resolve");
+        let printed = variables.to_string();
+        assert!(
+            printed.contains("api_token = ****** (default)"),
+            "{printed}"
+        );
+        assert!(!printed.contains("s3cr3t"));
+        assert_eq!(variables.values()["api_token"], "s3cr3t");
+    }
+
+    #[test]
+    fn parses_overrides() {
+        assert_eq!(
+            parse_override("url=http://a/?b=c").unwrap(),
+            ("url".to_string(), "http://a/?b=c".to_string())
+        );
+        assert!(parse_override("novalue").is_err());
+        assert!(parse_override("=x").is_err());
+    }
+}
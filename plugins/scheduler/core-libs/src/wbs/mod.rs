@@ -1,6 +1,11 @@
+mod progress;
+
+pub use progress::ProgressModel;
+
 use indexmap::IndexMap;
 
-use crate::dsl::{ActionDef, ResourceDef, Scenario, WorkflowNodeType};
+use crate::condition::Condition;
+use crate::dsl::{ActionDef, ResourceDef, Scenario, ThinkTimeDef, WorkflowNodeType};
 use crate::error::SchedulerError;
 
 pub type TaskId = String;
@@ -39,20 +44,20 @@ impl WbsTree {
             let kind = match node.node_type {
                 WorkflowNodeType::Action => WbsTaskKind::Action,
                 WorkflowNodeType::End => WbsTaskKind::End,
+                WorkflowNodeType::Group => WbsTaskKind::Group,
             };
 
             let outgoing = node
                 .edges
                 .iter()
-                .map(|edge| WbsEdge {
-                    target: edge.to.clone(),
-                    condition: edge
-                        .trigger
-                        .as_ref()
-                        .and_then(|trigger| trigger.condition.clone()),
-                    label: edge.label.clone(),
+                .map(|edge| {
+                    Ok(WbsEdge {
+                        target: edge.to.clone(),
+                        condition: edge.compile_condition(&node.id)?,
+                        label: edge.label.clone(),
+                    })
                 })
-                .collect();
+                .collect::<Result<Vec<_>, SchedulerError>>()?;
 
             tasks.insert(
                 node.id.clone(),
@@ -61,6 +66,10 @@ impl WbsTree {
                     action_id: node.action.clone(),
                     kind,
                     outgoing,
+                    parent: node.parent.clone(),
+                    weight: node.weight.unwrap_or(DEFAULT_WEIGHT),
+                    max_visits: node.max_visits,
+                    think_time: node.think_time.clone(),
                 },
             );
         }
@@ -89,6 +98,30 @@ impl WbsTree {
         self.tasks.get(id)
     }
 
+    /// Tasks whose `parent` is `parent`, or the top-level tasks for `None`.
+    pub fn children<'a>(&'a self, parent: Option<&'a str>) -> impl Iterator<Item = &'a WbsTask> {
+        self.tasks
+            .values()
+            .filter(move |task| task.parent.as_deref() == parent)
+    }
+
+    /// Groups enclosing `task_id`, innermost first. Stops at a parent cycle.
+    pub fn ancestors(&self, task_id: &str) -> Vec<&str> {
+        let mut chain: Vec<&str> = Vec::new();
+        let mut current = self
+            .tasks
+            .get(task_id)
+            .and_then(|task| task.parent.as_deref());
+        while let Some(id) = current {
+            if id == task_id || chain.contains(&id) {
+                break;
+            }
+            chain.push(id);
+            current = self.tasks.get(id).and_then(|task| task.parent.as_deref());
+        }
+        chain
+    }
+
     pub fn insert_task(&mut self, task: WbsTask) -> Option<WbsTask> {
         self.tasks.insert(task.id.clone(), task)
     }
@@ -136,24 +169,37 @@ impl WbsTree {
     }
 }
 
+/// Weight of a task that does not set one.
+pub const DEFAULT_WEIGHT: f64 = 1.0;
+
 #[derive(Debug, Clone)]
 pub struct WbsTask {
     pub id: TaskId,
     pub action_id: Option<String>,
     pub kind: WbsTaskKind,
     pub outgoing: Vec<WbsEdge>,
+    /// Enclosing group task, if any.
+    pub parent: Option<TaskId>,
+    /// Share in the parent's progress relative to its siblings.
+    pub weight: f64,
+    /// Visits allowed per user iteration, if limited.
+    pub max_visits: Option<usize>,
+    /// Pause after the task's action.
+    pub think_time: Option<ThinkTimeDef>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WbsTaskKind {
     Action,
     End,
+    /// Structural node grouping its children; has no action and no edges.
+    Group,
 }
 
 #[derive(Debug, Clone)]
 pub struct WbsEdge {
     pub target: TaskId,
-    pub condition: Option<String>,
+    pub condition: Option<Condition>,
     pub label: Option<String>,
 }
 
@@ -175,6 +221,10 @@ mod tests {
         Scenario {
             version: "1.0".into(),
             name: "branchy".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
             workbook: WorkbookSection::default(),
             actions: ActionsSection {
                 actions: vec![ActionDef {
@@ -189,6 +239,10 @@ mod tests {
                     WorkflowNode {
                         id: "start".into(),
                         node_type: WorkflowNodeType::Action,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
                         action: Some("action-a".into()),
                         edges: vec![
                             WorkflowEdge {
@@ -208,17 +262,26 @@ mod tests {
                     WorkflowNode {
                         id: "success".into(),
                         node_type: WorkflowNodeType::End,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
                         action: None,
                         edges: vec![],
                     },
                     WorkflowNode {
                         id: "retry".into(),
                         node_type: WorkflowNodeType::End,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
                         action: None,
                         edges: vec![],
                     },
                 ],
             },
+            load: None,
         }
     }
 
@@ -245,7 +308,7 @@ mod tests {
         let first = &start.outgoing[0];
         assert_eq!(first.target, "success");
         assert_eq!(
-            first.condition.as_deref(),
+            first.condition.as_ref().map(Condition::source),
             Some("{{action-a.status == 200}}")
         );
         assert_eq!(first.label.as_deref(), Some("ok"));
@@ -256,6 +319,16 @@ mod tests {
         assert_eq!(second.label.as_deref(), Some("retry"));
     }
 
+    #[test]
+    fn build_rejects_malformed_condition() {
+        let mut scenario = branchy_scenario();
+        scenario.workflows.nodes[0].edges[0].trigger = Some(TriggerDef {
+            condition: Some("{{action-a.status == }}".into()),
+        });
+        let err = WbsTree::build(&scenario).expect_err("malformed condition");
+        assert!(matches!(err, SchedulerError::InvalidCondition { from, .. } if from == "start"));
+    }
+
     #[test]
     fn supports_dynamic_task_mutations() {
         let scenario = branchy_scenario();
@@ -266,6 +339,10 @@ mod tests {
             action_id: Some("action-a".into()),
             kind: WbsTaskKind::Action,
             outgoing: vec![],
+            parent: None,
+            weight: 1.0,
+            max_visits: None,
+            think_time: None,
         };
 
         tree.insert_task(dynamic.clone());
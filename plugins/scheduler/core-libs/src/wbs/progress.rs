@@ -0,0 +1,190 @@
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use super::{TaskId, WbsTaskKind, WbsTree};
+
+/// Weighted progress roll-up over the WBS hierarchy.
+///
+/// Each level splits its parent's share among the children that carry work
+/// (action tasks and groups containing one) in proportion to their
+/// `weight`. The progress of a set of completed action tasks is the sum of
+/// their shares, so the whole workflow is `1.0` and a group is its share.
+#[derive(Debug, Clone, Default)]
+pub struct ProgressModel {
+    /// Share of the whole workflow of every action task.
+    actions: IndexMap<TaskId, f64>,
+    /// Share of every group task that contains an action.
+    groups: IndexMap<TaskId, f64>,
+    /// Enclosing groups of every action task, innermost first.
+    ancestors: IndexMap<TaskId, Vec<TaskId>>,
+}
+
+impl ProgressModel {
+    pub fn from_wbs(tree: &WbsTree) -> Self {
+        let mut model = Self::default();
+        let mut working_groups = HashSet::new();
+        for task in tree.tasks.values() {
+            if task.kind == WbsTaskKind::Action {
+                let ancestors = tree.ancestors(&task.id);
+                working_groups.extend(ancestors.iter().map(|id| id.to_string()));
+                model.ancestors.insert(
+                    task.id.clone(),
+                    ancestors.into_iter().map(String::from).collect(),
+                );
+            }
+        }
+
+        let mut visited = HashSet::new();
+        model.distribute(tree, None, 1.0, &working_groups, &mut visited);
+        model
+    }
+
+    fn distribute(
+        &mut self,
+        tree: &WbsTree,
+        parent: Option<&str>,
+        share: f64,
+        working_groups: &HashSet<String>,
+        visited: &mut HashSet<TaskId>,
+    ) {
+        let children: Vec<_> = tree
+            .children(parent)
+            .filter(|task| match task.kind {
+                WbsTaskKind::Action => true,
+                WbsTaskKind::Group => working_groups.contains(&task.id),
+                WbsTaskKind::End => false,
+            })
+            .map(|task| (task.id.clone(), task.kind, task.weight.max(0.0)))
+            .collect();
+        let total: f64 = children.iter().map(|(_, _, weight)| weight).sum();
+        if total <= 0.0 {
+            return;
+        }
+
+        for (id, kind, weight) in children {
+            if !visited.insert(id.clone()) {
+                continue;
+            }
+            let child_share = share * weight / total;
+            if kind == WbsTaskKind::Group {
+                self.groups.insert(id.clone(), child_share);
+                self.distribute(tree, Some(&id), child_share, working_groups, visited);
+            } else {
+                self.actions.insert(id, child_share);
+            }
+        }
+    }
+
+    /// Share of the whole workflow of an action or group task.
+    pub fn share(&self, task_id: &str) -> f64 {
+        self.actions
+            .get(task_id)
+            .or_else(|| self.groups.get(task_id))
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Groups that contain at least one action, in declaration order.
+    pub fn groups(&self) -> impl Iterator<Item = &str> {
+        self.groups.keys().map(String::as_str)
+    }
+
+    /// Progress of the whole workflow once `completed` action tasks are done.
+    pub fn fraction<'a>(&self, completed: impl IntoIterator<Item = &'a str>) -> f64 {
+        let done: f64 = completed
+            .into_iter()
+            .map(|id| self.share_of_action(id))
+            .sum();
+        done.min(1.0)
+    }
+
+    /// Progress within `group`, or `None` for a group without actions.
+    pub fn group_fraction<'a>(
+        &self,
+        group: &str,
+        completed: impl IntoIterator<Item = &'a str>,
+    ) -> Option<f64> {
+        let group_share = *self.groups.get(group)?;
+        let done: f64 = completed
+            .into_iter()
+            .filter(|id| {
+                self.ancestors
+                    .get(*id)
+                    .is_some_and(|ancestors| ancestors.iter().any(|a| a == group))
+            })
+            .map(|id| self.share_of_action(id))
+            .sum();
+        Some((done / group_share).min(1.0))
+    }
+
+    fn share_of_action(&self, task_id: &str) -> f64 {
+        self.actions.get(task_id).copied().unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dsl::Scenario;
+
+    const GROUPED: &str = r#"
+version: "1.0"
+name: grouped
+actions:
+  actions:
+    - id: a
+      call: get
+      with:
+        url: "http://host/a"
+workflows:
+  nodes:
+    - id: login
+      type: group
+      weight: 1
+    - id: shop
+      type: group
+      weight: 3
+    - id: start
+      type: action
+      action: a
+      parent: login
+      edges:
+        - to: browse
+    - id: browse
+      type: action
+      action: a
+      parent: shop
+      edges:
+        - to: checkout
+    - id: checkout
+      type: action
+      action: a
+      parent: shop
+      weight: 2
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#;
+
+    #[test]
+    fn rolls_weighted_progress_up_the_hierarchy() {
+        let scenario = Scenario::from_yaml_str(GROUPED).expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let model = ProgressModel::from_wbs(&tree);
+
+        assert_eq!(model.groups().collect::<Vec<_>>(), ["login", "shop"]);
+        assert_eq!(model.share("login"), 0.25);
+        assert_eq!(model.share("browse"), 0.25);
+        assert_eq!(model.share("checkout"), 0.5);
+
+        assert_eq!(model.fraction(["start", "browse"]), 0.5);
+        assert_eq!(model.group_fraction("login", ["start"]), Some(1.0));
+        assert_eq!(
+            model.group_fraction("shop", ["start", "browse"]),
+            Some(1.0 / 3.0)
+        );
+        assert_eq!(model.fraction(["start", "browse", "checkout"]), 1.0);
+    }
+}
@@ -74,12 +74,17 @@ mod tests {
         let scenario = Scenario {
             version: "1.0".into(),
             name: "workbook_test".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
             workbook: WorkbookSection {
                 resources: vec![ResourceDef {
                     id: "resource".into(),
                     resource_type: "http_endpoint".into(),
                     properties: IndexMap::new(),
                 }],
+                ip_pools: vec![],
             },
             actions: ActionsSection {
                 actions: vec![
@@ -91,14 +96,18 @@ mod tests {
                             ExportDef {
                                 export_type: "content".into(),
                                 name: "ip".into(),
+                                expr: None,
                                 scope: Some("workbook".into()),
                                 default: Some(Value::from("127.0.0.1")),
+                                ..Default::default()
                             },
                             ExportDef {
                                 export_type: "content".into(),
                                 name: "status".into(),
+                                expr: None,
                                 scope: None,
                                 default: Some(Value::from(200)),
+                                ..Default::default()
                             },
                         ],
                     },
@@ -111,6 +120,7 @@ mod tests {
                 ],
             },
             workflows: WorkflowSection { nodes: vec![] },
+            load: None,
         };
 
         let workbook = Workbook::from_scenario(&scenario);
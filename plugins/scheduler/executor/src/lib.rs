@@ -1,7 +1,11 @@
+use std::fmt;
+
 use anyhow::Result;
 
 use scheduler_core::dsl::ActionDef;
 use scheduler_core::error::SchedulerError;
+use scheduler_core::export::failure_reason;
+pub use scheduler_core::export::{ActionResponse, AssertionResult};
 use scheduler_core::state_machine::StateMachine;
 use scheduler_core::wbs::{WbsEdge, WbsTask, WbsTree};
 
@@ -18,6 +22,21 @@ pub trait ActionComponent {
     fn release(&mut self) -> Result<()>;
 }
 
+/// Non-blocking counterpart of [`ActionComponent`].
+///
+/// Implementations await I/O instead of blocking the calling thread, so many
+/// virtual users can share a few runtime threads. The futures are `Send` so
+/// that a user can run as a spawned task.
+pub trait AsyncActionComponent {
+    fn init(&mut self) -> impl Future<Output = Result<()>> + Send;
+    fn do_action(
+        &mut self,
+        action: &ActionDef,
+        ctx: &mut ActionContext<'_>,
+    ) -> impl Future<Output = Result<ActionOutcome>> + Send;
+    fn release(&mut self) -> impl Future<Output = Result<()>> + Send;
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ActionStatus {
     Success,
@@ -28,6 +47,10 @@ pub enum ActionStatus {
 pub struct ActionOutcome {
     pub status: ActionStatus,
     pub detail: Option<String>,
+    /// What the action observed; exports are extracted from it.
+    pub response: Option<ActionResponse>,
+    /// Results of the action's `type: assert` exports.
+    pub assertions: Vec<AssertionResult>,
 }
 
 impl ActionOutcome {
@@ -35,6 +58,8 @@ impl ActionOutcome {
         Self {
             status: ActionStatus::Success,
             detail: None,
+            response: None,
+            assertions: Vec::new(),
         }
     }
 
@@ -42,6 +67,8 @@ impl ActionOutcome {
         Self {
             status: ActionStatus::Failed,
             detail: None,
+            response: None,
+            assertions: Vec::new(),
         }
     }
 
@@ -49,6 +76,25 @@ impl ActionOutcome {
         self.detail = Some(detail.into());
         self
     }
+
+    pub fn with_response(mut self, response: ActionResponse) -> Self {
+        self.response = Some(response);
+        self
+    }
+
+    /// Records assertion results; any failure marks the outcome failed and
+    /// appends the reasons to the detail.
+    pub fn with_assertions(mut self, assertions: Vec<AssertionResult>) -> Self {
+        if let Some(reason) = failure_reason(&assertions) {
+            self.status = ActionStatus::Failed;
+            self.detail = Some(match self.detail.take() {
+                Some(detail) => format!("{}; {}", detail, reason),
+                None => reason,
+            });
+        }
+        self.assertions = assertions;
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -57,6 +103,7 @@ pub struct ActionTrace {
     pub action_id: String,
     pub status: ActionStatus,
     pub detail: Option<String>,
+    pub assertions: Vec<AssertionResult>,
 }
 
 #[derive(Debug, Clone)]
@@ -70,6 +117,33 @@ pub enum SchedulerEvent {
 }
 
 impl SchedulerEvent {
+    /// Applies the events emitted by one action atomically: if any event
+    /// fails, `wbs` and `state_machine` are restored to their state before
+    /// the batch and the failing event is reported (1-based index).
+    pub fn apply_batch(
+        events: Vec<SchedulerEvent>,
+        wbs: &mut WbsTree,
+        state_machine: &mut StateMachine,
+    ) -> Result<(), SchedulerError> {
+        if events.is_empty() {
+            return Ok(());
+        }
+
+        let snapshot = (wbs.clone(), state_machine.clone());
+        for (idx, event) in events.into_iter().enumerate() {
+            let described = event.to_string();
+            if let Err(source) = event.apply(wbs, state_machine) {
+                (*wbs, *state_machine) = snapshot;
+                return Err(SchedulerError::EventRejected {
+                    index: idx + 1,
+                    event: described,
+                    source: Box::new(source),
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn apply(
         self,
         wbs: &mut WbsTree,
@@ -119,6 +193,23 @@ impl SchedulerEvent {
     }
 }
 
+impl fmt::Display for SchedulerEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchedulerEvent::RegisterAction(action) => write!(f, "register action `{}`", action.id),
+            SchedulerEvent::InsertTask(task) => write!(f, "insert task `{}`", task.id),
+            SchedulerEvent::RemoveTask { task_id } => write!(f, "remove task `{}`", task_id),
+            SchedulerEvent::UpdateTask { task } => write!(f, "update task `{}`", task.id),
+            SchedulerEvent::AddEdge { from, edge } => {
+                write!(f, "add edge `{}` -> `{}`", from, edge.target)
+            }
+            SchedulerEvent::RemoveEdge { from, target } => {
+                write!(f, "remove edge `{}` -> `{}`", from, target)
+            }
+        }
+    }
+}
+
 pub struct ActionContext<'a> {
     wbs: &'a WbsTree,
     pending_events: Vec<SchedulerEvent>,
@@ -174,3 +265,142 @@ impl<'a> ActionContext<'a> {
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scheduler_core::dsl::Scenario;
+    use scheduler_core::wbs::WbsTaskKind;
+
+    const SCENARIO: &str = r#"
+version: "1.0"
+name: events
+actions:
+  actions:
+    - id: probe
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#;
+
+    fn setup() -> (WbsTree, StateMachine) {
+        let scenario = Scenario::from_yaml_str(SCENARIO).expect("scenario");
+        let wbs = WbsTree::build(&scenario).expect("wbs");
+        let state_machine = StateMachine::from_wbs(&wbs);
+        (wbs, state_machine)
+    }
+
+    fn task(id: &str) -> WbsTask {
+        WbsTask {
+            id: id.into(),
+            action_id: Some("probe".into()),
+            kind: WbsTaskKind::Action,
+            outgoing: vec![edge("end")],
+            parent: None,
+            weight: 1.0,
+            max_visits: None,
+            think_time: None,
+        }
+    }
+
+    fn edge(target: &str) -> WbsEdge {
+        WbsEdge {
+            target: target.into(),
+            condition: None,
+            label: None,
+        }
+    }
+
+    /// Both structures are backed by `IndexMap`s, so their debug output is
+    /// a stable fingerprint of their contents.
+    fn fingerprint(wbs: &WbsTree, state_machine: &StateMachine) -> String {
+        format!("{:?}\n{:?}", wbs, state_machine)
+    }
+
+    #[test]
+    fn apply_batch_applies_every_event() {
+        let (mut wbs, mut state_machine) = setup();
+        let events = vec![
+            SchedulerEvent::InsertTask(task("follow-up")),
+            SchedulerEvent::AddEdge {
+                from: "start".into(),
+                edge: edge("follow-up"),
+            },
+        ];
+
+        SchedulerEvent::apply_batch(events, &mut wbs, &mut state_machine).expect("batch");
+
+        assert!(wbs.tasks.contains_key("follow-up"));
+        let targets: Vec<_> = state_machine.nodes["start"]
+            .transitions
+            .iter()
+            .map(|transition| transition.to.as_str())
+            .collect();
+        assert_eq!(targets, ["end", "follow-up"]);
+    }
+
+    #[test]
+    fn apply_batch_restores_the_snapshot_on_a_failing_event() {
+        let (mut wbs, mut state_machine) = setup();
+        let before = fingerprint(&wbs, &state_machine);
+        let events = vec![
+            SchedulerEvent::InsertTask(task("follow-up")),
+            SchedulerEvent::AddEdge {
+                from: "start".into(),
+                edge: edge("follow-up"),
+            },
+            SchedulerEvent::RemoveEdge {
+                from: "missing".into(),
+                target: "end".into(),
+            },
+            SchedulerEvent::RemoveTask {
+                task_id: "start".into(),
+            },
+        ];
+
+        let err = SchedulerEvent::apply_batch(events, &mut wbs, &mut state_machine)
+            .expect_err("third event fails");
+
+        let SchedulerError::EventRejected {
+            index,
+            event,
+            source,
+        } = err
+        else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(index, 3);
+        assert_eq!(event, "remove edge `missing` -> `end`");
+        assert!(matches!(*source, SchedulerError::TaskNotFound(ref id) if id == "missing"));
+        // The two applied events are undone and the fourth is never applied
+        assert_eq!(fingerprint(&wbs, &state_machine), before);
+    }
+
+    #[test]
+    fn apply_batch_reports_a_failing_first_event() {
+        let (mut wbs, mut state_machine) = setup();
+        let before = fingerprint(&wbs, &state_machine);
+        let events = vec![
+            SchedulerEvent::UpdateTask {
+                task: task("missing"),
+            },
+            SchedulerEvent::InsertTask(task("follow-up")),
+        ];
+
+        let err = SchedulerEvent::apply_batch(events, &mut wbs, &mut state_machine)
+            .expect_err("first event fails");
+
+        assert!(
+            matches!(err, SchedulerError::EventRejected { index: 1, .. }),
+            "{err:?}"
+        );
+        assert_eq!(fingerprint(&wbs, &state_machine), before);
+    }
+}
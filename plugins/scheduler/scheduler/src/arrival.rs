@@ -0,0 +1,153 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+use crate::utils::{parse_duration, parse_rate};
+use scheduler_core::dsl::{ArrivalDistribution, ArrivalRateConfig};
+
+/// 开放模型的到达时刻
+///
+/// 到达率在 `duration` 内从 `rate` 线性变化到 `target_rate`，累计期望到达数
+/// 为 Λ(t) = r0·t + (r1 − r0)·t² / 2T。均匀到达取 Λ(t) = 0, 1, 2, …；泊松到达
+/// 取指数分布增量的累加和，即对非齐次泊松过程做时间变换。
+///
+/// 到达时刻只取决于速率曲线，与服务器响应快慢无关。
+#[derive(Debug)]
+pub struct ArrivalSchedule {
+    start_rate: f64,
+    end_rate: f64,
+    duration: Duration,
+    distribution: ArrivalDistribution,
+    rng: fastrand::Rng,
+    /// 下一次到达对应的累计期望到达数
+    arrivals: f64,
+}
+
+impl ArrivalSchedule {
+    pub fn new(config: &ArrivalRateConfig) -> Result<Self> {
+        let start_rate = parse_rate(&config.rate).context("invalid arrival_rate.rate")?;
+        let end_rate = match &config.target_rate {
+            Some(rate) => parse_rate(rate).context("invalid arrival_rate.target_rate")?,
+            None => start_rate,
+        };
+        Ok(Self {
+            start_rate,
+            end_rate,
+            duration: parse_duration(&config.duration).context("invalid arrival_rate.duration")?,
+            distribution: config.distribution,
+            rng: fastrand::Rng::new(),
+            arrivals: 0.0,
+        })
+    }
+
+    /// 固定随机种子（泊松到达可重现）
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.rng = fastrand::Rng::with_seed(seed);
+        self
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+
+    /// 整个时段内的期望到达数
+    pub fn expected_arrivals(&self) -> f64 {
+        (self.start_rate + self.end_rate) / 2.0 * self.duration.as_secs_f64()
+    }
+
+    /// Λ(t) = `arrivals` 的时刻；超出 `duration` 时返回 `None`
+    fn time_at(&self, arrivals: f64) -> Option<Duration> {
+        let total = self.duration.as_secs_f64();
+        let slope = (self.end_rate - self.start_rate) / (2.0 * total);
+        let discriminant = self.start_rate * self.start_rate + 4.0 * slope * arrivals;
+        if discriminant < 0.0 {
+            return None;
+        }
+        // 二次方程较小的根，写成不会相消的形式（slope 为 0 时即 arrivals / rate）
+        let denominator = self.start_rate + discriminant.sqrt();
+        let at = if arrivals == 0.0 {
+            0.0
+        } else if denominator > 0.0 {
+            2.0 * arrivals / denominator
+        } else {
+            return None;
+        };
+        (at < total).then(|| Duration::from_secs_f64(at))
+    }
+}
+
+impl Iterator for ArrivalSchedule {
+    /// 距离开始的时间
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let arrivals = match self.distribution {
+            ArrivalDistribution::Constant => {
+                let arrivals = self.arrivals;
+                self.arrivals += 1.0;
+                arrivals
+            }
+            ArrivalDistribution::Poisson => {
+                self.arrivals -= (1.0 - self.rng.f64()).ln();
+                self.arrivals
+            }
+        };
+        self.time_at(arrivals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule(
+        rate: &str,
+        target_rate: Option<&str>,
+        distribution: ArrivalDistribution,
+    ) -> ArrivalSchedule {
+        ArrivalSchedule::new(&ArrivalRateConfig {
+            rate: rate.to_string(),
+            target_rate: target_rate.map(str::to_string),
+            duration: "10s".to_string(),
+            distribution,
+            tenant_id: None,
+        })
+        .expect("valid arrival rate")
+    }
+
+    #[test]
+    fn test_constant_rate_is_evenly_spaced() {
+        let arrivals: Vec<_> = schedule("2/s", None, ArrivalDistribution::Constant).collect();
+        assert_eq!(arrivals.len(), 20);
+        assert_eq!(arrivals[0], Duration::ZERO);
+        assert_eq!(arrivals[1], Duration::from_millis(500));
+        assert_eq!(arrivals[19], Duration::from_millis(9500));
+    }
+
+    #[test]
+    fn test_ramped_rate_follows_the_curve() {
+        // 0 → 4/s：Λ(t) = t² / 5，前 5 秒只有 5 次到达，后 5 秒 15 次
+        let ramp = schedule("0/s", Some("4/s"), ArrivalDistribution::Constant);
+        assert_eq!(ramp.expected_arrivals(), 20.0);
+        let arrivals: Vec<_> = ramp.collect();
+        assert_eq!(arrivals.len(), 20);
+        let early = arrivals
+            .iter()
+            .filter(|at| **at < Duration::from_secs(5))
+            .count();
+        assert_eq!(early, 5);
+
+        // 降速：4/s → 0
+        let arrivals = schedule("4/s", Some("0/s"), ArrivalDistribution::Constant).count();
+        assert_eq!(arrivals, 20);
+    }
+
+    #[test]
+    fn test_poisson_arrivals_match_the_rate() {
+        let arrivals: Vec<_> = schedule("100/s", None, ArrivalDistribution::Poisson)
+            .with_seed(7)
+            .collect();
+        assert!((900..1100).contains(&arrivals.len()), "{}", arrivals.len());
+        assert!(arrivals.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(arrivals.iter().all(|at| *at < Duration::from_secs(10)));
+    }
+}
@@ -0,0 +1,267 @@
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::ip_manager::IpAllocation;
+use crate::shared::SharedValue;
+use crate::user::{ABORTED_STATUS, ExecutionTrace};
+use scheduler_core::export::AssertionSummary;
+use scheduler_core::graph::EdgeHits;
+
+/// 负载测试检查点
+///
+/// 定期写入磁盘（JSON），用于在主机崩溃后以 `--resume` 继续 ramp-up：
+/// 已完成的用户不再启动，未完成的用户从下一次迭代继续。
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// 写入时距负载测试开始的时间
+    pub elapsed: Duration,
+    /// 已启动的 ramp-up 阶段数
+    pub phases_started: usize,
+    /// 已分配的最大用户 ID
+    pub last_user_id: usize,
+    pub users: IndexMap<usize, UserRecord>,
+    pub ip_allocations: Vec<IpAllocation>,
+    /// 工作簿级共享变量
+    pub shared: IndexMap<String, SharedValue>,
+    pub metrics: RunMetrics,
+}
+
+/// 单个用户的检查点记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserRecord {
+    pub tenant_id: String,
+    pub allocated_ip: Option<IpAddr>,
+    /// 已完成的迭代数
+    pub completed_iterations: usize,
+    /// 所有迭代均已完成，或用户已失败退出
+    pub finished: bool,
+}
+
+impl UserRecord {
+    pub fn new(tenant_id: String, allocated_ip: Option<IpAddr>) -> Self {
+        Self {
+            tenant_id,
+            allocated_ip,
+            completed_iterations: 0,
+            finished: false,
+        }
+    }
+}
+
+/// 已完成迭代的汇总指标
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunMetrics {
+    /// 每个动作的耗时（毫秒），按完成顺序
+    pub durations_ms: Vec<u64>,
+    pub assertions: AssertionSummary,
+    pub edge_hits: EdgeHits,
+    /// 被中止的迭代数，按中止原因
+    #[serde(default)]
+    pub aborted: IndexMap<String, usize>,
+}
+
+impl RunMetrics {
+    pub fn record(&mut self, traces: &[ExecutionTrace]) {
+        for trace in traces {
+            // 中止的迭代单独统计，不计入耗时与边命中
+            if trace.status == ABORTED_STATUS {
+                *self.aborted.entry(trace.detail.clone()).or_default() += 1;
+                continue;
+            }
+            self.durations_ms.push(trace.duration_ms);
+            for assertion in &trace.assertions {
+                self.assertions.record(assertion);
+            }
+            self.edge_hits.record(&trace.node_id, &trace.next_node);
+        }
+    }
+
+    /// 已执行的动作数
+    pub fn actions(&self) -> usize {
+        self.durations_ms.len()
+    }
+
+    /// 被中止的迭代总数
+    pub fn aborted_iterations(&self) -> usize {
+        self.aborted.values().sum()
+    }
+}
+
+impl Checkpoint {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read checkpoint: {}", path.display()))?;
+        serde_json::from_str(&raw)
+            .with_context(|| format!("invalid checkpoint: {}", path.display()))
+    }
+
+    /// 先写临时文件再改名，崩溃时不会留下半个检查点
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let mut tmp = path.as_os_str().to_owned();
+        tmp.push(".tmp");
+        let json = serde_json::to_string_pretty(self).context("failed to serialize checkpoint")?;
+        std::fs::write(&tmp, json)
+            .with_context(|| format!("failed to write checkpoint: {}", path.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("failed to write checkpoint: {}", path.display()))
+    }
+}
+
+/// 运行状态
+///
+/// 记录已启动的阶段与用户、每个用户完成的迭代以及汇总指标，由所有用户共享
+/// （以 `Arc` 传递）。迭代完成与指标汇总在同一把锁下更新，检查点总是一致的。
+#[derive(Debug, Default)]
+pub struct RunState {
+    inner: Mutex<Checkpoint>,
+}
+
+impl RunState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 从检查点继续
+    pub fn from_checkpoint(checkpoint: &Checkpoint) -> Self {
+        Self {
+            inner: Mutex::new(checkpoint.clone()),
+        }
+    }
+
+    /// 一个 ramp-up 阶段的所有用户已创建；`phases_started` 为已启动的阶段数
+    pub fn start_phase(&self, phases_started: usize, users: Vec<(usize, UserRecord)>) {
+        let mut inner = self.inner.lock().expect("run state lock poisoned");
+        inner.phases_started = phases_started;
+        for (user_id, record) in users {
+            inner.last_user_id = inner.last_user_id.max(user_id);
+            inner.users.insert(user_id, record);
+        }
+    }
+
+    /// 到达率模式下登记一个新到达的用户
+    pub fn add_user(&self, user_id: usize, record: UserRecord) {
+        let mut inner = self.inner.lock().expect("run state lock poisoned");
+        inner.last_user_id = inner.last_user_id.max(user_id);
+        inner.users.insert(user_id, record);
+    }
+
+    /// 用户拿到并发空位后分配到的 IP
+    pub fn set_allocated_ip(&self, user_id: usize, allocated_ip: Option<IpAddr>) {
+        let mut inner = self.inner.lock().expect("run state lock poisoned");
+        if let Some(user) = inner.users.get_mut(&user_id) {
+            user.allocated_ip = allocated_ip;
+        }
+    }
+
+    /// 用户完成一次迭代，汇总该迭代的执行跟踪
+    pub fn complete_iteration(&self, user_id: usize, traces: &[ExecutionTrace]) {
+        let mut inner = self.inner.lock().expect("run state lock poisoned");
+        if let Some(user) = inner.users.get_mut(&user_id) {
+            user.completed_iterations += 1;
+        }
+        inner.metrics.record(traces);
+    }
+
+    /// 用户结束（完成或失败），恢复时不再启动
+    pub fn finish_user(&self, user_id: usize) {
+        let mut inner = self.inner.lock().expect("run state lock poisoned");
+        if let Some(user) = inner.users.get_mut(&user_id) {
+            user.finished = true;
+        }
+    }
+
+    pub fn metrics(&self) -> RunMetrics {
+        self.inner
+            .lock()
+            .expect("run state lock poisoned")
+            .metrics
+            .clone()
+    }
+
+    /// 生成检查点；IP 分配与共享变量由调用方提供
+    pub fn checkpoint(
+        &self,
+        elapsed: Duration,
+        ip_allocations: Vec<IpAllocation>,
+        shared: IndexMap<String, SharedValue>,
+    ) -> Checkpoint {
+        let inner = self.inner.lock().expect("run state lock poisoned");
+        Checkpoint {
+            elapsed,
+            ip_allocations,
+            shared,
+            ..inner.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scheduler_core::export::AssertionResult;
+
+    fn trace(user_id: usize, iteration: usize, duration_ms: u64) -> ExecutionTrace {
+        ExecutionTrace {
+            user_id,
+            iteration,
+            node_id: "start".to_string(),
+            next_node: "end".to_string(),
+            action_id: "probe".to_string(),
+            status: "Success".to_string(),
+            detail: String::new(),
+            duration_ms,
+            assertions: vec![AssertionResult {
+                name: "probe.ok".to_string(),
+                failure: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let state = RunState::new();
+        state.start_phase(
+            1,
+            vec![
+                (1, UserRecord::new("tenant-a".into(), None)),
+                (2, UserRecord::new("tenant-a".into(), None)),
+            ],
+        );
+        // 用户拿到并发空位后才分配 IP
+        state.set_allocated_ip(2, "10.0.1.1".parse().ok());
+        state.complete_iteration(1, &[trace(1, 0, 12)]);
+        state.finish_user(1);
+        state.complete_iteration(2, &[trace(2, 0, 30)]);
+
+        let path =
+            std::env::temp_dir().join(format!("scheduler-checkpoint-{}", std::process::id()));
+        let shared = IndexMap::from([(
+            "login.ids".to_string(),
+            SharedValue::List(vec!["1".into(), "2".into()]),
+        )]);
+        state
+            .checkpoint(Duration::from_secs(42), Vec::new(), shared.clone())
+            .save(&path)
+            .expect("save checkpoint");
+        let restored = Checkpoint::load(&path).expect("load checkpoint");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(restored.elapsed, Duration::from_secs(42));
+        assert_eq!((restored.phases_started, restored.last_user_id), (1, 2));
+        assert!(restored.users[&1].finished);
+        assert_eq!(restored.users[&2].completed_iterations, 1);
+        assert_eq!(restored.users[&2].allocated_ip, "10.0.1.1".parse().ok());
+        assert_eq!(restored.shared, shared);
+        assert_eq!(restored.metrics.durations_ms, vec![12, 30]);
+        assert_eq!(restored.metrics.edge_hits.get("start", "end"), 2);
+        assert_eq!(
+            restored.metrics.assertions.iter().collect::<Vec<_>>(),
+            vec![("probe.ok", 2, 0)]
+        );
+    }
+}
@@ -1,11 +1,25 @@
 /// Scheduler WASM Component Implementation
 ///
 /// This module implements the scheduler as a WASM component
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
 use anyhow::{Context, Result};
 
-use crate::{IpPoolManager, TemplateContext, UserContext, UserExecutor, parse_duration};
+use crate::{
+    ArrivalSchedule, IpPoolManager, IterationLimits, ProgressBoard, RateLimiter, RunMetrics,
+    SharedStore, TemplateContext, ThinkTime, UserContext, UserExecutor, parse_duration,
+};
 use scheduler_actions_http::HttpActionComponent;
-use scheduler_core::dsl::Scenario;
+use scheduler_core::{
+    data::{FeederSet, GUEST_DATA_DIR},
+    dsl::{ParseMode, Scenario},
+    secrets::{self, Secrets},
+    state_machine::StateMachine,
+    variables::Variables,
+    wbs::{ProgressModel, WbsTree},
+};
 
 // Generate bindings for the component
 wit_bindgen::generate!({
@@ -17,22 +31,52 @@ struct SchedulerComponent;
 
 impl Guest for SchedulerComponent {
     fn run_scenario(scenario_yaml: String) -> Result<String, String> {
+        // The summary and errors are returned to the host: mask tracked secrets
         match run_scenario_impl(&scenario_yaml) {
-            Ok(summary) => Ok(summary),
-            Err(e) => Err(format!("Scenario execution failed: {:#}", e)),
+            Ok(summary) => Ok(secrets::redact(&summary)),
+            Err(e) => Err(secrets::redact(&format!(
+                "Scenario execution failed: {:#}",
+                e
+            ))),
         }
     }
 }
 
 fn run_scenario_impl(scenario_yaml: &str) -> Result<String> {
     // Parse scenario
-    let scenario =
+    let mut scenario =
         Scenario::from_yaml_str(scenario_yaml).context("Failed to parse scenario YAML")?;
-
-    scenario.validate().context("Scenario validation failed")?;
+    // The host preopens the scenario's directory as `.`
+    let included = scenario
+        .resolve_includes(Path::new("."), ParseMode::Lenient)
+        .context("Failed to resolve scenario includes")?;
+
+    let report = scenario.analyze_source_with_includes(scenario_yaml, &included);
+    if report.has_errors() {
+        return Err(anyhow::anyhow!("Scenario validation failed:\n{}", report));
+    }
+    for warning in report.warnings() {
+        println!("⚠️  {}", warning);
+    }
 
     let workbook = scheduler_core::workbook::Workbook::from_scenario(&scenario);
-    let template_ctx = TemplateContext::from_workbook(&workbook);
+    // The host passes effective variables (its `--var`s included) as NTX_VAR_* and
+    // has already printed them
+    let variables = Variables::resolve(&scenario, std::env::vars(), &[])
+        .context("Failed to resolve scenario variables")?;
+    secrets::track_variables(&variables);
+    // The host forwards the environment variables named by `env` secrets;
+    // `file` secrets are read relative to the preopened scenario directory
+    let secrets = Secrets::resolve(&scenario, |name| std::env::var(name).ok(), Path::new("."))
+        .context("Failed to resolve scenario secrets")?;
+    let template_ctx = TemplateContext::from_workbook_with_secrets(&workbook, &variables, &secrets);
+    let wbs = WbsTree::build(&scenario).context("Failed to build workflow tree")?;
+    let state_machine = StateMachine::from_wbs(&wbs);
+    // The host preopens `data.dir` at GUEST_DATA_DIR
+    let feeders = Arc::new(
+        FeederSet::load(scenario.data.as_ref(), Path::new(GUEST_DATA_DIR))
+            .context("Failed to load data feeders")?,
+    );
 
     let scenario_name = scenario.name.clone();
 
@@ -43,11 +87,44 @@ fn run_scenario_impl(scenario_yaml: &str) -> Result<String> {
         ));
     };
 
+    // Stages retire users while others are still running
+    if !load_config.stages.is_empty() {
+        return Err(anyhow::anyhow!(
+            "load.stages needs concurrent users; run it with the native scheduler"
+        ));
+    }
+
     println!("🚀 Running load test: {}", scenario_name);
-    println!("Ramp-up phases: {}", load_config.ramp_up.phases.len());
+    match &load_config.arrival_rate {
+        Some(arrival_rate) => println!(
+            "Arrival rate: {} → {} over {} ({:?})",
+            arrival_rate.rate,
+            arrival_rate
+                .target_rate
+                .as_deref()
+                .unwrap_or(&arrival_rate.rate),
+            arrival_rate.duration,
+            arrival_rate.distribution
+        ),
+        None => println!("Ramp-up phases: {}", load_config.ramp_up.phases.len()),
+    }
     println!("User lifetime: {:?}", load_config.user_lifetime.mode);
     println!("Iterations: {}", load_config.user_lifetime.iterations);
     println!("Think time: {}", load_config.user_lifetime.think_time);
+    if let Some(pacing) = &load_config.user_lifetime.pacing {
+        println!("Pacing: one iteration every {}", pacing);
+    }
+    if let Some(concurrency) = &load_config.concurrency {
+        println!(
+            "Concurrency: {} users, spawn {}, actions {}",
+            concurrency.max_concurrent_users,
+            concurrency.spawn_rate_limit,
+            concurrency
+                .action_rate_limit
+                .as_deref()
+                .unwrap_or("unlimited")
+        );
+    }
 
     // Initialize IP pool manager
     let mut ip_manager = IpPoolManager::new();
@@ -76,26 +153,176 @@ fn run_scenario_impl(scenario_yaml: &str) -> Result<String> {
         }
     }
 
-    // Prepare user lifecycle parameters
-    let iterations = load_config.user_lifetime.iterations;
-    let think_time = parse_duration(&load_config.user_lifetime.think_time)?;
+    // Prepare user lifecycle parameters; each arrival runs a single iteration
+    let iterations = match load_config.arrival_rate {
+        Some(_) => 1,
+        None => load_config.user_lifetime.iterations,
+    };
+    let think_time = ThinkTime::parse(&load_config.user_lifetime.think_time)
+        .context("invalid user_lifetime.think_time")?;
+    let pacing = load_config
+        .user_lifetime
+        .pacing
+        .as_deref()
+        .map(parse_duration)
+        .transpose()
+        .context("invalid user_lifetime.pacing")?;
+    let limits = IterationLimits {
+        max_steps: load_config.user_lifetime.max_steps_per_iteration,
+        timeout: load_config
+            .user_lifetime
+            .iteration_timeout
+            .as_deref()
+            .map(parse_duration)
+            .transpose()?,
+    };
+
+    // Users already run one at a time, so only the rate limits apply here
+    let concurrency = load_config.concurrency.as_ref();
+    let spawn_rate = concurrency
+        .map(|c| RateLimiter::parse(&c.spawn_rate_limit, Some(1.0)))
+        .transpose()
+        .context("invalid load.concurrency.spawn_rate_limit")?;
+    let action_rate = concurrency
+        .and_then(|c| c.action_rate_limit.as_deref())
+        .map(|rate| RateLimiter::parse(rate, None))
+        .transpose()
+        .context("invalid load.concurrency.action_rate_limit")?
+        .map(Arc::new);
+
+    // Workbook-scoped exports shared by all users
+    let shared = Arc::new(SharedStore::new());
+
+    // Weighted progress of every user, rolled up over the WBS groups
+    let progress = Arc::new(ProgressBoard::new(ProgressModel::from_wbs(&wbs)));
 
     // Collect all execution traces
     let mut all_traces = Vec::new();
     let mut user_id_counter = 0usize;
 
-    println!("\n⏱️  Starting ramp-up...");
+    // Runs one user to completion (no async in WASM component yet)
+    let mut run_user = |user_id: usize, tenant_id: String| {
+        if let Some(limiter) = &spawn_rate {
+            limiter.acquire();
+        }
+
+        // Allocate IP if enabled
+        let allocated_ip = if load_config.user_resources.ip_binding.enabled {
+            let pool_id = &load_config.user_resources.ip_binding.pool_id;
+
+            match ip_manager.allocate_ip(pool_id, &tenant_id, &format!("user-{}", user_id)) {
+                Ok(ip) => Some(ip),
+                Err(e) => {
+                    eprintln!("⚠️  Failed to allocate IP for user-{}: {}", user_id, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        // Create user context
+        let user_ctx = UserContext::new_with_id(user_id, tenant_id.clone(), allocated_ip);
+
+        // Create user executor
+        let mut executor = UserExecutor::new(
+            user_ctx,
+            state_machine.clone(),
+            iterations,
+            think_time,
+            template_ctx.clone(),
+        )
+        .with_feeders(Arc::clone(&feeders))
+        .with_shared_store(Arc::clone(&shared))
+        .with_progress(Arc::clone(&progress))
+        .with_iteration_limits(limits);
+        if let Some(limiter) = &action_rate {
+            executor = executor.with_action_rate_limit(Arc::clone(limiter));
+        }
+        if let Some(pacing) = pacing {
+            executor = executor.with_pacing(pacing);
+        }
+
+        // Create HTTP action component
+        let mut component = HttpActionComponent::new();
+
+        // Execute user
+        match executor.run(&mut component) {
+            Ok(traces) => {
+                println!(
+                    "✓ User-{} completed {} iterations, {} actions",
+                    user_id,
+                    iterations,
+                    traces.len()
+                );
+                all_traces.extend(traces);
+            }
+            Err(e) => {
+                // Report where in its iteration the user stopped
+                let at = progress
+                    .user(user_id)
+                    .map(|p| format!(" at {}", p))
+                    .unwrap_or_default();
+                eprintln!(
+                    "✗ User-{} failed{}: {}",
+                    user_id,
+                    at,
+                    secrets::redact(&format!("{:#}", e))
+                );
+            }
+        }
+
+        // Release IP if needed
+        if load_config.user_resources.ip_binding.enabled {
+            if let Some(ip) = allocated_ip {
+                let pool_id = &load_config.user_resources.ip_binding.pool_id;
+                if let Err(e) = ip_manager.release_ip(pool_id, ip) {
+                    eprintln!(
+                        "⚠️  Failed to release IP {} for user-{}: {}",
+                        ip, user_id, e
+                    );
+                }
+            }
+        }
+    };
 
-    // Execute users sequentially (no async in WASM component yet)
-    for phase in &load_config.ramp_up.phases {
+    // Arrival count and the largest delay behind the arrival schedule
+    let arrivals = if let Some(arrival_rate) = &load_config.arrival_rate {
+        // Users run one at a time, so an arrival that falls due while another
+        // user is running starts late
+        let schedule = ArrivalSchedule::new(arrival_rate)?;
         println!(
-            "\n📊 Phase at {}s: Spawning {} users...",
-            phase.at_second, phase.spawn_users
+            "\n⏱️  Starting arrivals: ~{:.0} over {:.0}s...",
+            schedule.expected_arrivals(),
+            schedule.duration().as_secs_f64()
         );
+        let tenant_id = arrival_rate
+            .tenant_id
+            .clone()
+            .unwrap_or_else(|| "default-tenant".to_string());
+
+        let start = Instant::now();
+        let (mut count, mut max_lag) = (0, Duration::ZERO);
+        for at in schedule {
+            let now = start.elapsed();
+            if now < at {
+                std::thread::sleep(at - now);
+            }
+            max_lag = max_lag.max(now.saturating_sub(at));
 
-        for _ in 0..phase.spawn_users {
             user_id_counter += 1;
-            let user_id = user_id_counter;
+            run_user(user_id_counter, tenant_id.clone());
+            count += 1;
+        }
+        Some((count, max_lag))
+    } else {
+        println!("\n⏱️  Starting ramp-up...");
+
+        for phase in &load_config.ramp_up.phases {
+            println!(
+                "\n📊 Phase at {}s: Spawning {} users...",
+                phase.at_second, phase.spawn_users
+            );
 
             // Determine tenant ID
             let tenant_id = phase
@@ -103,67 +330,13 @@ fn run_scenario_impl(scenario_yaml: &str) -> Result<String> {
                 .clone()
                 .unwrap_or_else(|| "default-tenant".to_string());
 
-            // Allocate IP if enabled
-            let allocated_ip = if load_config.user_resources.ip_binding.enabled {
-                let pool_id = &load_config.user_resources.ip_binding.pool_id;
-
-                match ip_manager.allocate_ip(pool_id, &tenant_id, &format!("user-{}", user_id)) {
-                    Ok(ip) => Some(ip),
-                    Err(e) => {
-                        eprintln!("⚠️  Failed to allocate IP for user-{}: {}", user_id, e);
-                        None
-                    }
-                }
-            } else {
-                None
-            };
-
-            // Create user context
-            let user_ctx = UserContext::new_with_id(user_id, tenant_id.clone(), allocated_ip);
-
-            // Create user executor
-            let mut executor = UserExecutor::new(
-                user_ctx,
-                scenario.workflows.clone(),
-                scenario.actions.clone(),
-                iterations,
-                think_time,
-                template_ctx.clone(),
-            );
-
-            // Create HTTP action component
-            let mut component = HttpActionComponent::new();
-
-            // Execute user
-            match executor.run(&mut component) {
-                Ok(traces) => {
-                    println!(
-                        "✓ User-{} completed {} iterations, {} actions",
-                        user_id,
-                        iterations,
-                        traces.len()
-                    );
-                    all_traces.extend(traces);
-                }
-                Err(e) => {
-                    eprintln!("✗ User-{} failed: {:#}", user_id, e);
-                }
-            }
-
-            // Release IP if needed
-            if load_config.user_resources.ip_binding.enabled {
-                if let Some(ip) = allocated_ip {
-                    let pool_id = &load_config.user_resources.ip_binding.pool_id;
-                    if let Err(e) = ip_manager.release_ip(pool_id, ip) {
-                        eprintln!(
-                            "⚠️  Failed to release IP {} for user-{}: {}",
-                            ip, user_id, e
-                        );
-                    }
-                }
+            for _ in 0..phase.spawn_users {
+                user_id_counter += 1;
+                run_user(user_id_counter, tenant_id.clone());
             }
         }
-    }
+        None
+    };
 
     // Generate summary
     let mut summary = String::new();
@@ -171,14 +344,25 @@ fn run_scenario_impl(scenario_yaml: &str) -> Result<String> {
     summary.push_str("═══════════════════════════════════════\n");
     summary.push_str(&format!("Scenario: {}\n", scenario_name));
     summary.push_str(&format!("Total users spawned: {}\n", user_id_counter));
-    summary.push_str(&format!("Total actions executed: {}\n", all_traces.len()));
+    if let Some((count, max_lag)) = arrivals {
+        summary.push_str(&format!(
+            "Arrivals: {} (max start lag {}ms)\n",
+            count,
+            max_lag.as_millis()
+        ));
+    }
 
-    if !all_traces.is_empty() {
+    // Aborted iterations are counted apart from latency and edge hits
+    let mut metrics = RunMetrics::default();
+    metrics.record(&all_traces);
+    summary.push_str(&format!("Total actions executed: {}\n", metrics.actions()));
+
+    if metrics.actions() > 0 {
         // Calculate statistics
-        let total_duration_ms: u64 = all_traces.iter().map(|t| t.duration_ms).sum();
-        let avg_duration = total_duration_ms as f64 / all_traces.len() as f64;
+        let total_duration_ms: u64 = metrics.durations_ms.iter().sum();
+        let avg_duration = total_duration_ms as f64 / metrics.actions() as f64;
 
-        let mut durations: Vec<u64> = all_traces.iter().map(|t| t.duration_ms).collect();
+        let mut durations = metrics.durations_ms.clone();
         durations.sort_unstable();
 
         let p50 = durations[durations.len() / 2];
@@ -192,6 +376,36 @@ fn run_scenario_impl(scenario_yaml: &str) -> Result<String> {
         summary.push_str(&format!("  P99: {}ms\n", p99));
         summary.push_str(&format!("  Min: {}ms\n", durations[0]));
         summary.push_str(&format!("  Max: {}ms\n", durations[durations.len() - 1]));
+
+        // Per-assertion pass/fail counts
+        if !metrics.assertions.is_empty() {
+            summary.push_str("\nAssertions:\n");
+            summary.push_str(&metrics.assertions.to_string());
+        }
+
+        // How often each workflow edge was taken
+        summary.push_str("\nEdge Hits:\n");
+        summary.push_str(&metrics.edge_hits.to_string());
+    }
+
+    if metrics.aborted_iterations() > 0 {
+        summary.push_str(&format!(
+            "\nAborted Iterations: {}\n",
+            metrics.aborted_iterations()
+        ));
+        for (reason, count) in &metrics.aborted {
+            summary.push_str(&format!("  {}: {}\n", reason, count));
+        }
+    }
+
+    summary.push_str("\nProgress:\n");
+    summary.push_str(&progress.to_string());
+
+    if !shared.is_empty() {
+        summary.push_str("\nWorkbook Variables:\n");
+        for (name, value) in shared.snapshot() {
+            summary.push_str(&format!("  {} = {}\n", name, value));
+        }
     }
 
     // IP pool statistics
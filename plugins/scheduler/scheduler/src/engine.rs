@@ -1,4 +1,5 @@
-use std::collections::VecDeque;
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
 use std::sync::{
     Arc,
     atomic::{AtomicBool, Ordering},
@@ -6,16 +7,26 @@ use std::sync::{
 use std::thread;
 use std::time::Duration;
 
-use crate::TemplateContext;
+use crate::journal::{Journal, JournalEntry};
+use crate::{SharedStore, TemplateContext};
 #[cfg(not(target_arch = "wasm32"))]
 use ctrlc;
 use indexmap::IndexSet;
 use scheduler_actions_http::HttpActionComponent;
 use scheduler_core::{
-    dsl::Scenario, error::SchedulerError, state_machine::StateMachine, wbs::WbsTree,
+    analysis::{START_NODE, ValidationReport},
+    dsl::{ParseMode, Scenario, SourceMap},
+    error::SchedulerError,
+    export::ActionExports,
+    secrets::{self, Secrets},
+    state_machine::{StateMachine, StateNodeKind},
+    variables::Variables,
+    wbs::{ProgressModel, WbsTree},
     workbook::Workbook,
 };
-use scheduler_executor::{ActionComponent, ActionContext, ActionTrace, SchedulerEvent};
+use scheduler_executor::{
+    ActionComponent, ActionContext, ActionStatus, ActionTrace, SchedulerEvent,
+};
 
 #[derive(Debug, Clone)]
 pub struct SchedulerPipeline {
@@ -24,20 +35,68 @@ pub struct SchedulerPipeline {
     template: TemplateContext,
     wbs: WbsTree,
     state_machine: StateMachine,
+    progress: ProgressModel,
+    report: ValidationReport,
+    variables: Variables,
+    secrets: Secrets,
+    journal: Option<Arc<Journal>>,
+    shutdown: Arc<AtomicBool>,
 }
 
 impl SchedulerPipeline {
     pub fn load_from_yaml_str(input: &str) -> Result<Self, SchedulerError> {
-        let scenario = Scenario::from_yaml_str(input)?;
-        scenario.validate()?;
-        Self::from_scenario(scenario)
+        Self::load_from_yaml_str_with_mode(input, ParseMode::Lenient)
+    }
+
+    /// `ParseMode::Strict` 时，任何未知字段都会导致加载失败。
+    pub fn load_from_yaml_str_with_mode(
+        input: &str,
+        mode: ParseMode,
+    ) -> Result<Self, SchedulerError> {
+        let scenario = Scenario::from_yaml_str_with_mode(input, mode)?;
+        Self::load_parsed(scenario, input, &SourceMap::default(), Path::new("."))
+    }
+
+    /// 从文件加载场景，`include` 按该文件所在目录解析。
+    pub fn load_from_yaml_file(path: &Path, mode: ParseMode) -> Result<Self, SchedulerError> {
+        let input = std::fs::read_to_string(path)?;
+        let (scenario, included) = Scenario::from_yaml_str_at_with_includes(&input, path, mode)?;
+        let base_dir = path.parent().unwrap_or_else(|| Path::new("."));
+        Self::load_parsed(scenario, &input, &included, base_dir)
+    }
+
+    /// 诊断定位到 `input`；被包含的条目按 `included` 定位到各自文件。
+    fn load_parsed(
+        scenario: Scenario,
+        input: &str,
+        included: &SourceMap,
+        base_dir: &Path,
+    ) -> Result<Self, SchedulerError> {
+        let report = scenario.analyze_source_with_includes(input, included);
+        if report.has_errors() {
+            return Err(SchedulerError::InvalidScenario(report));
+        }
+        let mut pipeline = Self::build(scenario, base_dir)?;
+        pipeline.report = report;
+        Ok(pipeline)
     }
 
+    /// `secrets:` 中的 `file` 相对当前目录解析。
     pub fn from_scenario(scenario: Scenario) -> Result<Self, SchedulerError> {
+        Self::build(scenario, Path::new("."))
+    }
+
+    fn build(scenario: Scenario, base_dir: &Path) -> Result<Self, SchedulerError> {
+        let report = scenario.analyze();
         let workbook = Workbook::from_scenario(&scenario);
-        let template = TemplateContext::from_workbook(&workbook);
+        let mut variables = Variables::from_scenario(&scenario);
+        variables.apply_env(std::env::vars());
+        secrets::track_variables(&variables);
+        let secrets = Secrets::resolve(&scenario, |name| std::env::var(name).ok(), base_dir)?;
+        let template = TemplateContext::from_workbook_with_secrets(&workbook, &variables, &secrets);
         let wbs = WbsTree::build(&scenario)?;
         let state_machine = StateMachine::from_wbs(&wbs);
+        let progress = ProgressModel::from_wbs(&wbs);
 
         Ok(Self {
             scenario,
@@ -45,7 +104,67 @@ impl SchedulerPipeline {
             template,
             wbs,
             state_machine,
+            progress,
+            report,
+            variables,
+            secrets,
+            journal: None,
+            shutdown: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    /// 应用 `--var key=value` 覆盖（优先级高于 `NTX_VAR_*` 环境变量），
+    /// 并检查所有声明的变量都有值。
+    pub fn apply_variable_overrides(
+        &mut self,
+        overrides: &[(String, String)],
+    ) -> Result<(), SchedulerError> {
+        self.variables.apply_overrides(overrides);
+        self.variables.check_complete()?;
+        secrets::track_variables(&self.variables);
+        let strict = self.template.is_strict();
+        self.template = TemplateContext::from_workbook_with_secrets(
+            &self.workbook,
+            &self.variables,
+            &self.secrets,
+        )
+        .with_strict(strict);
+        Ok(())
+    }
+
+    /// 严格模板模式：动作参数中出现未定义的变量时，该动作失败。
+    pub fn set_strict_templates(&mut self, strict: bool) {
+        self.template.set_strict(strict);
+    }
+
+    /// 将之后 `run` 中的动作派发、结果、事件与转移写入运行日志
+    pub fn set_journal(&mut self, journal: Arc<Journal>) {
+        self.journal = Some(journal);
+    }
+
+    /// 置位后 `run` 在当前任务结束时停止；每个流水线实例独立
+    pub fn shutdown_flag(&self) -> Arc<AtomicBool> {
+        self.shutdown.clone()
+    }
+
+    /// 将 Ctrl-C 绑定到本实例的停止标志（每个进程只能注册一次）
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn install_ctrlc_handler(&self) -> Result<(), SchedulerError> {
+        let flag = self.shutdown.clone();
+        ctrlc::set_handler(move || {
+            flag.store(true, Ordering::SeqCst);
         })
+        .map_err(|source| SchedulerError::SignalHandler { source })
+    }
+
+    /// 生效的场景变量（打印时请使用其 `Display`，密文已遮蔽）。
+    pub fn variables(&self) -> &Variables {
+        &self.variables
+    }
+
+    /// 已解析的密钥（`Display` 只输出名称与来源）。
+    pub fn secrets(&self) -> &Secrets {
+        &self.secrets
     }
 
     pub fn scenario(&self) -> &Scenario {
@@ -68,6 +187,16 @@ impl SchedulerPipeline {
         &self.state_machine
     }
 
+    /// 按 WBS 分组与权重汇总进度的模型（供 `ProgressBoard` 使用）。
+    pub fn progress_model(&self) -> &ProgressModel {
+        &self.progress
+    }
+
+    /// Structural diagnostics collected when the scenario was loaded.
+    pub fn validation_report(&self) -> &ValidationReport {
+        &self.report
+    }
+
     pub fn summary(&self) -> PipelineSummary {
         PipelineSummary {
             resources: self.workbook.resource_count(),
@@ -82,6 +211,9 @@ impl SchedulerPipeline {
         self.run(&mut component)
     }
 
+    /// 从 `start` 节点开始执行工作流：每个动作完成后按转移条件只进入一个后继节点，
+    /// 直到到达终点或没有满足条件的转移。动作通过 `SchedulerEvent` 插入的节点
+    /// 若没有转移指向，则作为独立分支执行。
     pub fn run<C>(&mut self, component: &mut C) -> Result<Vec<ActionTrace>, SchedulerError>
     where
         C: ActionComponent,
@@ -90,14 +222,14 @@ impl SchedulerPipeline {
             .init()
             .map_err(|source| SchedulerError::ActionComponentInit { source })?;
 
-        let shutdown = setup_shutdown_flag()?;
         let run_result = TaskExecutor::new(
             component,
             &mut self.wbs,
             &mut self.state_machine,
             self.template.clone(),
-            shutdown,
+            self.shutdown.clone(),
         )
+        .with_journal(self.journal.clone())
         .run();
 
         let release_result = component
@@ -109,6 +241,14 @@ impl SchedulerPipeline {
             }
         }
 
+        // 同步执行返回时日志已落盘
+        if let Some(journal) = &self.journal
+            && let Err(err) = journal.flush()
+            && run_result.is_ok()
+        {
+            return Err(err.into());
+        }
+
         run_result
     }
 }
@@ -127,32 +267,19 @@ pub struct PipelineSummary {
     pub edges: usize,
 }
 
-#[cfg(not(target_arch = "wasm32"))]
-fn setup_shutdown_flag() -> Result<Arc<AtomicBool>, SchedulerError> {
-    let flag = Arc::new(AtomicBool::new(false));
-    let handler_flag = flag.clone();
-    ctrlc::set_handler(move || {
-        handler_flag.store(true, Ordering::SeqCst);
-    })
-    .map_err(|source| SchedulerError::SignalHandler { source })?;
-    Ok(flag)
-}
-
-#[cfg(target_arch = "wasm32")]
-fn setup_shutdown_flag() -> Result<Arc<AtomicBool>, SchedulerError> {
-    // Signal handling not available in WASM
-    Ok(Arc::new(AtomicBool::new(false)))
-}
-
 struct TaskExecutor<'a, C> {
     component: &'a mut C,
     wbs: &'a mut WbsTree,
     state_machine: &'a mut StateMachine,
     template: TemplateContext,
+    shared: SharedStore,
     queues: PriorityQueues,
     seen_tasks: IndexSet<String>,
     traces: Vec<ActionTrace>,
+    /// 事件批次被拒绝的动作（跟踪记录下标）
+    rejected: HashSet<usize>,
     shutdown: Arc<AtomicBool>,
+    journal: Option<Arc<Journal>>,
 }
 
 impl<'a, C> TaskExecutor<'a, C>
@@ -166,20 +293,37 @@ where
         template: TemplateContext,
         shutdown: Arc<AtomicBool>,
     ) -> Self {
+        // 场景中已有的节点只能经由转移到达；之后插入的节点在 `advance` 中识别
+        let seen_tasks = wbs.tasks.keys().cloned().collect();
         let mut executor = Self {
             component,
             wbs,
             state_machine,
             template,
+            shared: SharedStore::new(),
             queues: PriorityQueues::new(),
-            seen_tasks: IndexSet::new(),
+            seen_tasks,
             traces: Vec::new(),
+            rejected: HashSet::new(),
             shutdown,
+            journal: None,
         };
-        executor.enqueue_new_action_tasks();
+        executor.enqueue_node(START_NODE);
         executor
     }
 
+    fn with_journal(mut self, journal: Option<Arc<Journal>>) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    fn record(&self, entry: JournalEntry) -> Result<(), SchedulerError> {
+        if let Some(journal) = &self.journal {
+            journal.record(&entry)?;
+        }
+        Ok(())
+    }
+
     fn run(mut self) -> Result<Vec<ActionTrace>, SchedulerError> {
         let mut idle_spins = 0usize;
 
@@ -210,7 +354,11 @@ where
     fn dispatch(&mut self, task: ScheduledTask) -> Result<(), SchedulerError> {
         match task.kind {
             TaskKind::Action { task_id } => self.execute_action(task_id),
-            TaskKind::Event(event) => self.execute_event(event),
+            TaskKind::Advance { task_id, trace } => self.advance(&task_id, trace),
+            TaskKind::Events { trace, events } => {
+                self.execute_events(trace, events);
+                Ok(())
+            }
             TaskKind::Idle => Ok(()),
         }
     }
@@ -231,49 +379,153 @@ where
             .cloned()
             .ok_or_else(|| SchedulerError::ActionNotRegistered(action_id.clone()))?;
 
-        let action = self.template.render_action(&action);
+        // 模板错误（严格模式下的未定义变量、函数参数非法等）只让该动作失败
+        let action = match self.template.render_action(&action) {
+            Ok(action) => action,
+            Err(err) => {
+                self.traces.push(ActionTrace {
+                    task_id: task.id.clone(),
+                    action_id,
+                    status: ActionStatus::Failed,
+                    detail: Some(secrets::redact(&format!("template error: {}", err))),
+                    assertions: Vec::new(),
+                });
+                self.queues.push(ScheduledTask::advance(
+                    task.id,
+                    self.traces.len() - 1,
+                    ACTION_PRIORITY,
+                ));
+                return Ok(());
+            }
+        };
 
+        self.record(JournalEntry::dispatch(None, 0, &task.id, &action))?;
         let wbs_view: &WbsTree = &self.wbs;
         let mut ctx = ActionContext::new(wbs_view);
-        let outcome = self
-            .component
-            .do_action(&action, &mut ctx)
-            .map_err(|source| SchedulerError::ActionExecution {
-                action: action_id.clone(),
-                source,
-            })?;
-
-        for event in ctx.into_events() {
-            self.queues
-                .push(ScheduledTask::event(event, EVENT_PRIORITY));
+        let outcome = match self.component.do_action(&action, &mut ctx) {
+            Ok(outcome) => outcome,
+            Err(source) => {
+                self.record(JournalEntry::action_error(
+                    None, 0, &task.id, &action_id, &source,
+                ))?;
+                return Err(SchedulerError::ActionExecution {
+                    action: action_id,
+                    source,
+                });
+            }
+        };
+        let events = ctx.into_events();
+        self.record(JournalEntry::outcome(
+            None, 0, &task.id, &action_id, &outcome, &events,
+        ))?;
+
+        // 导出变量写入模板上下文，供后续动作引用；断言失败时动作记为失败
+        let values = ActionExports::compile(&action)?.evaluate(outcome.response.as_ref());
+        self.template.extend(&values.vars);
+        if !values.workbook.is_empty() {
+            self.shared.apply(&values.workbook);
+            self.template.extend(&self.shared.snapshot());
+        }
+        let outcome = outcome.with_assertions(values.assertions);
+
+        // 本动作产生的事件作为一个批次原子应用，失败时回滚并将该动作记为失败
+        if !events.is_empty() {
+            self.queues.push(ScheduledTask::events(
+                self.traces.len(),
+                events,
+                EVENT_PRIORITY,
+            ));
         }
 
         self.traces.push(ActionTrace {
             task_id: task.id.clone(),
             action_id: action_id.clone(),
             status: outcome.status,
-            detail: outcome.detail,
+            detail: outcome.detail.as_deref().map(secrets::redact),
+            assertions: outcome.assertions,
         });
 
-        self.enqueue_new_action_tasks();
+        // 后继节点在本动作产生的事件全部应用之后再选择（事件优先级更高）
+        self.queues.push(ScheduledTask::advance(
+            task.id,
+            self.traces.len() - 1,
+            ACTION_PRIORITY,
+        ));
         Ok(())
     }
 
-    fn execute_event(&mut self, event: SchedulerEvent) -> Result<(), SchedulerError> {
-        event.apply(self.wbs, self.state_machine)?;
-        self.enqueue_new_action_tasks();
+    /// 事件被拒绝时 WBS 与状态机已回滚；产生事件的动作（`trace`）记为失败，
+    /// 其所在分支不再推进。
+    fn execute_events(&mut self, trace: usize, events: Vec<SchedulerEvent>) {
+        if let Err(err) = SchedulerEvent::apply_batch(events, self.wbs, self.state_machine) {
+            self.rejected.insert(trace);
+            let trace = &mut self.traces[trace];
+            trace.status = ActionStatus::Failed;
+            trace.detail = Some(secrets::redact(&err.to_string()));
+        }
+    }
+
+    /// 按声明顺序评估 `task_id` 的转移条件，只将第一个满足条件的后继入队；
+    /// 没有满足条件的转移、或该次执行（`trace`）的事件批次被拒绝时，该分支结束。
+    fn advance(&mut self, task_id: &str, trace: usize) -> Result<(), SchedulerError> {
+        self.enqueue_detached_tasks();
+
+        let Some(node) = self.state_machine.nodes.get(task_id) else {
+            return Ok(());
+        };
+        let next = if self.rejected.contains(&trace) {
+            None
+        } else {
+            node.transitions
+                .iter()
+                .find(|transition| transition.trigger.is_satisfied(&self.template))
+                .map(|transition| transition.to.clone())
+        };
+        self.record(JournalEntry::Transition {
+            user: None,
+            iteration: 0,
+            from: task_id.to_string(),
+            to: next.clone(),
+        })?;
+        if let Some(next) = next {
+            self.enqueue_node(&next);
+        }
         Ok(())
     }
 
+    /// 动态插入、且没有任何转移指向的动作节点作为独立分支执行一次
+    fn enqueue_detached_tasks(&mut self) {
+        let inserted: Vec<String> = self
+            .wbs
+            .action_task_ids()
+            .into_iter()
+            .filter(|id| !self.seen_tasks.contains(id))
+            .collect();
+        for id in inserted {
+            self.seen_tasks.insert(id.clone());
+            let targeted = self
+                .state_machine
+                .nodes
+                .values()
+                .flat_map(|node| &node.transitions)
+                .any(|transition| transition.to == id);
+            if !targeted {
+                self.queues.push(ScheduledTask::action(id, ACTION_PRIORITY));
+            }
+        }
+    }
+
     fn execute_idle(&self) {
         thread::sleep(Duration::from_millis(10));
     }
 
-    fn enqueue_new_action_tasks(&mut self) {
-        for id in self.wbs.action_task_ids() {
-            if self.seen_tasks.insert(id.clone()) {
-                self.queues.push(ScheduledTask::action(id, ACTION_PRIORITY));
-            }
+    /// 将动作节点入队；终点与不存在的节点不执行
+    fn enqueue_node(&mut self, task_id: &str) {
+        if let Some(node) = self.state_machine.nodes.get(task_id)
+            && node.kind == StateNodeKind::Action
+        {
+            self.queues
+                .push(ScheduledTask::action(task_id.to_string(), ACTION_PRIORITY));
         }
     }
 }
@@ -322,10 +574,17 @@ impl ScheduledTask {
         }
     }
 
-    fn event(event: SchedulerEvent, priority: u8) -> Self {
+    fn advance(task_id: String, trace: usize, priority: u8) -> Self {
+        Self {
+            priority,
+            kind: TaskKind::Advance { task_id, trace },
+        }
+    }
+
+    fn events(trace: usize, events: Vec<SchedulerEvent>, priority: u8) -> Self {
         Self {
             priority,
-            kind: TaskKind::Event(event),
+            kind: TaskKind::Events { trace, events },
         }
     }
 
@@ -339,8 +598,19 @@ impl ScheduledTask {
 
 #[derive(Clone)]
 enum TaskKind {
-    Action { task_id: String },
-    Event(SchedulerEvent),
+    Action {
+        task_id: String,
+    },
+    /// 动作完成后沿工作流转移选择后继节点；`trace` 为该动作的跟踪记录下标
+    Advance {
+        task_id: String,
+        trace: usize,
+    },
+    /// 一个动作产生的事件批次；`trace` 为该动作的跟踪记录下标
+    Events {
+        trace: usize,
+        events: Vec<SchedulerEvent>,
+    },
     Idle,
 }
 
@@ -350,15 +620,70 @@ mod tests {
     use anyhow::Result;
     use scheduler_core::{
         dsl::ActionDef,
+        variables::VariableSource,
         wbs::{WbsEdge, WbsTask, WbsTaskKind},
     };
-    use scheduler_executor::{ActionComponent, ActionContext, ActionOutcome};
+    use scheduler_executor::{ActionComponent, ActionContext, ActionOutcome, ActionResponse};
 
     const SAMPLE: &str = include_str!(concat!(
         env!("CARGO_MANIFEST_DIR"),
         "/../res/http_scenario.yaml"
     ));
 
+    #[test]
+    fn load_from_file_resolves_includes() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../res/http_include_demo.yaml");
+        let pipeline =
+            SchedulerPipeline::load_from_yaml_file(&path, ParseMode::Strict).expect("pipeline");
+        assert!(pipeline.workbook().resources.contains_key("resource"));
+        assert!(pipeline.wbs().tasks.contains_key("start"));
+
+        let raw = std::fs::read_to_string(&path).expect("read scenario");
+        let err = SchedulerPipeline::load_from_yaml_str(&raw).expect_err("unresolved include");
+        assert!(err.to_string().contains("is not resolved"), "{err}");
+    }
+
+    #[test]
+    fn variables_fill_resource_placeholders() {
+        let mut pipeline = SchedulerPipeline::load_from_yaml_str(SAMPLE).expect("pipeline");
+        assert_eq!(
+            pipeline
+                .template_context()
+                .render_str("{{resource.ip}}:{{resource.port}}")
+                .unwrap(),
+            "127.0.0.1:8080"
+        );
+
+        pipeline
+            .apply_variable_overrides(&[("target_ip".into(), "10.0.0.9".into())])
+            .expect("override");
+        assert_eq!(
+            pipeline
+                .template_context()
+                .render_str("{{resource.ip}}")
+                .unwrap(),
+            "10.0.0.9"
+        );
+        assert_eq!(
+            pipeline.variables().get("target_ip").unwrap().source,
+            VariableSource::Cli
+        );
+    }
+
+    #[test]
+    fn load_reports_graph_errors_with_locations() {
+        let yaml = SAMPLE.replace("- id: start", "- id: begin");
+        let err = SchedulerPipeline::load_from_yaml_str(&yaml).expect_err("missing start");
+        match err {
+            SchedulerError::InvalidScenario(report) => {
+                let missing = report.errors().next().expect("missing start error");
+                assert_eq!(missing.message, "workflow has no `start` node");
+                assert!(missing.location.is_some());
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+    }
+
     #[test]
     fn pipeline_builds_summary() {
         let pipeline = SchedulerPipeline::load_from_yaml_str(SAMPLE).expect("pipeline");
@@ -392,6 +717,10 @@ mod tests {
                         condition: None,
                         label: Some("dynamic".into()),
                     }],
+                    parent: None,
+                    weight: 1.0,
+                    max_visits: None,
+                    think_time: None,
                 });
             }
 
@@ -411,4 +740,351 @@ mod tests {
         let traces = pipeline.run(&mut component).expect("run pipeline");
         assert!(traces.iter().any(|trace| trace.task_id == "dynamic-node"));
     }
+
+    /// 探测动作插入两个独立节点，第一个被执行的推送动作再插入一个
+    struct InsertingComponent {
+        pushes: usize,
+    }
+
+    impl InsertingComponent {
+        fn detached(id: &str) -> WbsTask {
+            WbsTask {
+                id: id.into(),
+                action_id: Some("push-post".into()),
+                kind: WbsTaskKind::Action,
+                outgoing: vec![WbsEdge {
+                    target: "end".into(),
+                    condition: None,
+                    label: None,
+                }],
+                parent: None,
+                weight: 1.0,
+                max_visits: None,
+                think_time: None,
+            }
+        }
+    }
+
+    impl ActionComponent for InsertingComponent {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn do_action(
+            &mut self,
+            action: &ActionDef,
+            ctx: &mut ActionContext<'_>,
+        ) -> Result<ActionOutcome> {
+            if action.id == "probe-get" {
+                ctx.add_task(Self::detached("dyn-a"));
+                ctx.add_task(Self::detached("dyn-b"));
+            } else {
+                if self.pushes == 0 {
+                    ctx.add_task(Self::detached("dyn-c"));
+                }
+                self.pushes += 1;
+            }
+            Ok(probe_ok())
+        }
+
+        fn release(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn tasks_inserted_during_run_keep_priority_order() {
+        let scenario = Scenario::from_yaml_str(SAMPLE).expect("scenario");
+        let mut pipeline = SchedulerPipeline::from_scenario(scenario).expect("pipeline");
+        let traces = pipeline
+            .run(&mut InsertingComponent { pushes: 0 })
+            .expect("run pipeline");
+
+        // 事件批次先于后继选择应用；同一优先级内按入队顺序执行，
+        // 运行中插入的 dyn-c 排在已入队的 push-node 之后
+        let visited: Vec<_> = traces.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(visited, ["start", "dyn-a", "dyn-b", "push-node", "dyn-c"]);
+    }
+
+    #[test]
+    fn priority_queues_prefer_tasks_pushed_mid_drain() {
+        let mut queues = PriorityQueues::new();
+        queues.push(ScheduledTask::action("first".into(), ACTION_PRIORITY));
+        queues.push(ScheduledTask::action("second".into(), ACTION_PRIORITY));
+        let mut order = Vec::new();
+        while let Some(task) = queues.pop() {
+            let TaskKind::Action { task_id } = task.kind else {
+                unreachable!("only action tasks are queued");
+            };
+            if task_id == "first" {
+                queues.push(ScheduledTask::action("urgent".into(), EVENT_PRIORITY));
+                queues.push(ScheduledTask::action("late".into(), ACTION_PRIORITY));
+            }
+            order.push(task_id);
+        }
+        assert_eq!(order, ["first", "urgent", "second", "late"]);
+    }
+
+    #[test]
+    fn shutdown_flag_is_per_pipeline() {
+        let scenario = Scenario::from_yaml_str(SAMPLE).expect("scenario");
+        let mut stopped = SchedulerPipeline::from_scenario(scenario.clone()).expect("pipeline");
+        let mut running = SchedulerPipeline::from_scenario(scenario).expect("pipeline");
+        stopped.shutdown_flag().store(true, Ordering::SeqCst);
+
+        let traces = stopped
+            .run(&mut SpawnComponent { spawned: false })
+            .expect("run pipeline");
+        assert!(traces.is_empty());
+        let traces = running
+            .run(&mut SpawnComponent { spawned: false })
+            .expect("run pipeline");
+        assert!(!traces.is_empty());
+    }
+
+    #[test]
+    fn replay_reproduces_recorded_run() {
+        let path = std::env::temp_dir().join(format!("scheduler-journal-{}", std::process::id()));
+        let summarize = |traces: &[ActionTrace]| -> Vec<_> {
+            traces
+                .iter()
+                .map(|t| {
+                    (
+                        t.task_id.clone(),
+                        t.action_id.clone(),
+                        t.status,
+                        t.detail.clone(),
+                    )
+                })
+                .collect()
+        };
+
+        let mut recorded = SchedulerPipeline::load_from_yaml_str(SAMPLE).expect("pipeline");
+        recorded.set_journal(Arc::new(Journal::create(&path).expect("create journal")));
+        let traces = recorded
+            .run(&mut SpawnComponent { spawned: false })
+            .expect("record run");
+
+        let mut replay = crate::Replay::load(&path).expect("load journal");
+        let mut replayed = SchedulerPipeline::load_from_yaml_str(SAMPLE).expect("pipeline");
+        let replayed_traces = replayed.run(&mut replay.pipeline).expect("replay run");
+        std::fs::remove_file(&path).ok();
+
+        assert!(traces.iter().any(|trace| trace.task_id == "dynamic-node"));
+        assert_eq!(summarize(&replayed_traces), summarize(&traces));
+        assert_eq!(replay.pipeline.remaining(), 0);
+    }
+
+    const BRANCHY: &str = r#"
+version: "1.0"
+name: branchy
+actions:
+  actions:
+    - id: probe
+      call: get
+      with:
+        url: "http://host/probe"
+      export:
+        - type: status
+          name: code
+    - id: ok
+      call: get
+      with:
+        url: "http://host/ok"
+    - id: recover
+      call: get
+      with:
+        url: "http://host/recover"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      edges:
+        - to: ok-node
+          trigger:
+            condition: "{{probe.code == 200}}"
+        - to: recover-node
+    - id: ok-node
+      type: action
+      action: ok
+      edges:
+        - to: end
+    - id: recover-node
+      type: action
+      action: recover
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#;
+
+    struct StatusComponent {
+        status_code: u16,
+    }
+
+    impl ActionComponent for StatusComponent {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn do_action(
+            &mut self,
+            _action: &ActionDef,
+            _ctx: &mut ActionContext<'_>,
+        ) -> Result<ActionOutcome> {
+            Ok(ActionOutcome::success().with_response(ActionResponse {
+                status_code: Some(self.status_code),
+                ..Default::default()
+            }))
+        }
+
+        fn release(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn run_follows_only_the_taken_transition() {
+        for (status_code, expected) in [(200, "ok-node"), (503, "recover-node")] {
+            let scenario = Scenario::from_yaml_str(BRANCHY).expect("scenario");
+            let mut pipeline = SchedulerPipeline::from_scenario(scenario).expect("pipeline");
+            let traces = pipeline
+                .run(&mut StatusComponent { status_code })
+                .expect("run pipeline");
+            let visited: Vec<_> = traces.iter().map(|t| t.task_id.as_str()).collect();
+            assert_eq!(visited, ["start", expected]);
+        }
+    }
+
+    /// 插入任务后向不存在的节点加边：整个批次回滚
+    struct HalfValidComponent;
+
+    impl ActionComponent for HalfValidComponent {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn do_action(
+            &mut self,
+            action: &ActionDef,
+            ctx: &mut ActionContext<'_>,
+        ) -> Result<ActionOutcome> {
+            if action.id == "probe-get" {
+                ctx.add_task(WbsTask {
+                    id: "ghost".into(),
+                    action_id: Some("push-post".into()),
+                    kind: WbsTaskKind::Action,
+                    outgoing: vec![],
+                    parent: None,
+                    weight: 1.0,
+                    max_visits: None,
+                    think_time: None,
+                });
+                ctx.add_edge(
+                    "missing-node",
+                    WbsEdge {
+                        target: "ghost".into(),
+                        condition: None,
+                        label: None,
+                    },
+                );
+            }
+            Ok(probe_ok())
+        }
+
+        fn release(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 使样例中 `start` 的转移走向 `push-node`
+    fn probe_ok() -> ActionOutcome {
+        ActionOutcome::success().with_response(ActionResponse {
+            status_code: Some(200),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn rejected_event_batch_rolls_back_and_fails_the_action() {
+        let scenario = Scenario::from_yaml_str(SAMPLE).expect("scenario");
+        let mut pipeline = SchedulerPipeline::from_scenario(scenario).expect("pipeline");
+        let traces = pipeline.run(&mut HalfValidComponent).expect("run pipeline");
+
+        let probe = &traces[0];
+        assert_eq!(probe.task_id, "start");
+        assert_eq!(probe.status, ActionStatus::Failed);
+        assert_eq!(
+            probe.detail.as_deref(),
+            Some(
+                "event #2 (add edge `missing-node` -> `ghost`) rejected, batch rolled back: \
+                 task `missing-node` not found"
+            )
+        );
+        // 第一个事件（插入 ghost）已成功应用，随批次一起回滚
+        assert!(!pipeline.wbs().tasks.contains_key("ghost"));
+        assert!(!pipeline.state_machine().nodes.contains_key("ghost"));
+        // 被拒绝的动作不再推进：不会进入 push-node，也不会执行 ghost
+        let visited: Vec<_> = traces.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(visited, ["start"]);
+    }
+
+    /// 与 `HalfValidComponent` 相同的批次形状，但边的起点是刚插入的任务：批次被接受
+    struct ValidBatchComponent;
+
+    impl ActionComponent for ValidBatchComponent {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn do_action(
+            &mut self,
+            action: &ActionDef,
+            ctx: &mut ActionContext<'_>,
+        ) -> Result<ActionOutcome> {
+            if action.id == "probe-get" {
+                ctx.add_task(WbsTask {
+                    outgoing: vec![],
+                    ..InsertingComponent::detached("follow-up")
+                });
+                ctx.add_edge(
+                    "follow-up",
+                    WbsEdge {
+                        target: "end".into(),
+                        condition: None,
+                        label: None,
+                    },
+                );
+            }
+            Ok(probe_ok())
+        }
+
+        fn release(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn accepted_event_batch_lets_the_workflow_advance() {
+        let scenario = Scenario::from_yaml_str(SAMPLE).expect("scenario");
+        let mut pipeline = SchedulerPipeline::from_scenario(scenario).expect("pipeline");
+        let traces = pipeline
+            .run(&mut ValidBatchComponent)
+            .expect("run pipeline");
+
+        // 产生批次的动作成功，批次未被拒绝
+        assert_eq!(traces[0].status, ActionStatus::Success);
+        assert_eq!(traces[0].detail, None);
+        // 插入的任务与边都已生效，插入的任务先于转移选出的 push-node 执行
+        let follow_up = pipeline
+            .wbs()
+            .tasks
+            .get("follow-up")
+            .expect("inserted task");
+        assert_eq!(follow_up.outgoing[0].target, "end");
+        assert!(pipeline.state_machine().nodes.contains_key("follow-up"));
+        let visited: Vec<_> = traces.iter().map(|t| t.task_id.as_str()).collect();
+        assert_eq!(visited, ["start", "follow-up", "push-node"]);
+    }
 }
@@ -1,10 +1,20 @@
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::net::IpAddr;
 
 use scheduler_core::dsl::IpPoolDef;
 use scheduler_core::ip::{IpPool, ResourceType};
 
+/// 一个已分配的 IP（写入检查点）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IpAllocation {
+    pub pool_id: String,
+    pub ip: IpAddr,
+    pub tenant_id: String,
+    pub user_id: String,
+}
+
 /// IP 池管理器
 ///
 /// 负责管理多个 IP 池，支持：
@@ -104,6 +114,47 @@ impl IpPoolManager {
             .with_context(|| format!("Failed to release IP {} from pool '{}'", ip, pool_id))
     }
 
+    /// 所有池中当前已分配的 IP，按池 ID 与地址排序
+    pub fn allocations(&self) -> Vec<IpAllocation> {
+        let mut allocations: Vec<IpAllocation> = self
+            .pools
+            .iter()
+            .flat_map(|(pool_id, pool)| {
+                pool.list_bindings()
+                    .into_iter()
+                    .map(move |binding| IpAllocation {
+                        pool_id: pool_id.clone(),
+                        ip: binding.ip,
+                        tenant_id: binding.subinstance.clone(),
+                        user_id: binding.subid.clone(),
+                    })
+            })
+            .collect();
+        allocations.sort_by(|a, b| (&a.pool_id, a.ip).cmp(&(&b.pool_id, b.ip)));
+        allocations
+    }
+
+    /// 恢复检查点中的一个分配
+    pub fn restore_allocation(&mut self, allocation: &IpAllocation) -> Result<()> {
+        let pool = self
+            .pools
+            .get_mut(&allocation.pool_id)
+            .with_context(|| format!("IP pool '{}' not found", allocation.pool_id))?;
+
+        pool.allocate_specific(
+            allocation.ip,
+            allocation.tenant_id.as_str(),
+            allocation.user_id.as_str(),
+            ResourceType::Custom("http-client".into()),
+        )
+        .with_context(|| {
+            format!(
+                "Failed to restore IP {} in pool '{}'",
+                allocation.ip, allocation.pool_id
+            )
+        })
+    }
+
     /// 获取池统计信息
     ///
     /// # Arguments
@@ -218,6 +269,45 @@ mod tests {
         assert_eq!(ip5.to_string(), "10.0.1.0");
     }
 
+    #[test]
+    fn test_restore_allocations() {
+        let pool_defs = vec![IpPoolDef {
+            id: "test-pool".to_string(),
+            name: "Test Pool".to_string(),
+            ranges: vec!["10.0.1.0/30".to_string()],
+            allocation_strategy: None,
+        }];
+
+        let mut manager = IpPoolManager::new();
+        manager
+            .initialize_from_config(&pool_defs)
+            .expect("should initialize");
+        manager
+            .allocate_ip("test-pool", "tenant-a", "user-1")
+            .expect("should allocate");
+        manager
+            .allocate_ip("test-pool", "tenant-b", "user-2")
+            .expect("should allocate");
+        let allocations = manager.allocations();
+
+        // 在新的管理器中恢复，已恢复的 IP 不会再被分配
+        let mut restored = IpPoolManager::new();
+        restored
+            .initialize_from_config(&pool_defs)
+            .expect("should initialize");
+        for allocation in &allocations {
+            restored
+                .restore_allocation(allocation)
+                .expect("should restore");
+        }
+        assert_eq!(restored.allocations(), allocations);
+        assert_eq!(allocations[1].tenant_id, "tenant-b");
+        let next = restored
+            .allocate_ip("test-pool", "tenant-a", "user-3")
+            .expect("should allocate");
+        assert_eq!(next.to_string(), "10.0.1.2");
+    }
+
     #[test]
     fn test_stats() {
         let pool_defs = vec![IpPoolDef {
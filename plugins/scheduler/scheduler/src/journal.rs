@@ -0,0 +1,843 @@
+use anyhow::{Context, Result, anyhow, bail};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex, mpsc};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use scheduler_core::condition::Condition;
+use scheduler_core::dsl::{ActionDef, ExportMode, ThinkTimeDef};
+use scheduler_core::secrets;
+use scheduler_core::variables::MASK;
+use scheduler_core::wbs::{WbsEdge, WbsTask, WbsTaskKind};
+use scheduler_executor::{
+    ActionComponent, ActionContext, ActionOutcome, ActionResponse, ActionStatus, SchedulerEvent,
+};
+
+use crate::shared::{SharedStore, SharedValue};
+
+/// 运行日志
+///
+/// 以 NDJSON 追加写入（每行一条 `JournalEntry`），记录用户上线、迭代数据、
+/// 动作派发、动作结果（含其产生的 `SchedulerEvent`）与所选转移。
+/// 由所有用户共享（以 `Arc` 传递）；写入前遮蔽已跟踪的密钥。
+///
+/// 文件 I/O 在独立的写入线程中进行，[`Journal::record`] 只把记录放入通道，
+/// 不会阻塞异步运行时；需要落盘时调用 [`Journal::flush`]。
+#[derive(Debug)]
+pub struct Journal {
+    sender: mpsc::Sender<WriterCommand>,
+    writer: Mutex<Option<JoinHandle<io::Result<()>>>>,
+}
+
+#[derive(Debug)]
+enum WriterCommand {
+    Line(String),
+    /// 之前的记录全部写入并刷新后回复
+    Flush(mpsc::Sender<io::Result<()>>),
+}
+
+/// 日志条目；`user` 为 `None` 表示单次执行模式（`SchedulerPipeline::run`）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum JournalEntry {
+    /// 用户上线
+    UserStart {
+        user: usize,
+        tenant_id: String,
+        allocated_ip: Option<String>,
+        /// 第一次迭代的序号；`--resume` 续跑的用户不从 0 开始
+        #[serde(default)]
+        first_iteration: usize,
+    },
+    /// 一次迭代开始，`data` 为该迭代使用的数据行
+    Iteration {
+        user: usize,
+        iteration: usize,
+        data: IndexMap<String, String>,
+    },
+    /// 动作交给组件执行（`with` 为渲染后的参数）
+    Dispatch {
+        user: Option<usize>,
+        iteration: usize,
+        task_id: String,
+        action_id: String,
+        call: String,
+        with: serde_json::Value,
+    },
+    /// 组件返回的结果
+    Outcome {
+        user: Option<usize>,
+        iteration: usize,
+        task_id: String,
+        action_id: String,
+        status: RecordedStatus,
+        detail: Option<String>,
+        response: Option<RecordedResponse>,
+        events: Vec<RecordedEvent>,
+    },
+    /// 组件返回错误（用户随之停止）
+    ActionError {
+        user: Option<usize>,
+        iteration: usize,
+        task_id: String,
+        action_id: String,
+        error: String,
+    },
+    /// 迭代因超出上限（步数、节点访问次数或时间）而中止；`step` 为已执行的动作数
+    Abort {
+        user: usize,
+        iteration: usize,
+        step: usize,
+        task_id: String,
+        reason: String,
+    },
+    /// 所选转移；`to` 为 `None` 表示没有满足条件的转移
+    Transition {
+        user: Option<usize>,
+        iteration: usize,
+        from: String,
+        to: Option<String>,
+    },
+    /// 工作簿共享变量的初始值（从检查点恢复时），版本号为 0
+    SharedInit {
+        values: IndexMap<String, SharedValue>,
+    },
+    /// 一次生效的工作簿共享变量写入；`version` 为写入后的版本号，
+    /// 所有用户的写入按版本号排成全局顺序
+    SharedWrite {
+        user: usize,
+        iteration: usize,
+        task_id: String,
+        version: u64,
+        name: String,
+        value: String,
+        mode: ExportMode,
+    },
+    /// 用户第 `read` 次（从 0 开始）读取共享变量时看到的版本；
+    /// 只在版本与该用户上一次读取不同时记录
+    SharedRead {
+        user: usize,
+        iteration: usize,
+        read: usize,
+        version: u64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedStatus {
+    Success,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status_code: Option<u16>,
+    pub headers: IndexMap<String, String>,
+    pub body: String,
+    pub elapsed: Option<Duration>,
+}
+
+/// 可序列化的 `SchedulerEvent`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RecordedEvent {
+    RegisterAction { action: serde_json::Value },
+    InsertTask { task: RecordedTask },
+    RemoveTask { task_id: String },
+    UpdateTask { task: RecordedTask },
+    AddEdge { from: String, edge: RecordedEdge },
+    RemoveEdge { from: String, target: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedTask {
+    pub id: String,
+    pub action_id: Option<String>,
+    /// `action`、`end` 或 `group`
+    pub kind: String,
+    pub outgoing: Vec<RecordedEdge>,
+    pub parent: Option<String>,
+    pub weight: f64,
+    #[serde(default)]
+    pub max_visits: Option<usize>,
+    #[serde(default)]
+    pub think_time: Option<ThinkTimeDef>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEdge {
+    pub target: String,
+    pub condition: Option<String>,
+    pub label: Option<String>,
+}
+
+impl Journal {
+    /// 新建（或截断）日志文件并启动写入线程
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let (sender, receiver) = mpsc::channel();
+        let writer = thread::Builder::new()
+            .name("journal-writer".into())
+            .spawn(move || {
+                for command in receiver {
+                    match command {
+                        WriterCommand::Line(line) => {
+                            writeln!(file, "{}", line)?;
+                        }
+                        WriterCommand::Flush(done) => {
+                            let _ = done.send(file.flush());
+                        }
+                    }
+                }
+                file.flush()
+            })?;
+        Ok(Self {
+            sender,
+            writer: Mutex::new(Some(writer)),
+        })
+    }
+
+    /// 追加一条记录（交给写入线程，不等待落盘）
+    pub fn record(&self, entry: &JournalEntry) -> io::Result<()> {
+        let line = secrets::redact(&serde_json::to_string(entry).map_err(io::Error::other)?);
+        self.sender
+            .send(WriterCommand::Line(line))
+            .map_err(|_| self.writer_error())
+    }
+
+    /// 等待此前的记录全部写入文件
+    pub fn flush(&self) -> io::Result<()> {
+        let (done, wait) = mpsc::channel();
+        if self.sender.send(WriterCommand::Flush(done)).is_ok()
+            && let Ok(result) = wait.recv()
+        {
+            return result;
+        }
+        Err(self.writer_error())
+    }
+
+    /// 写入线程已退出：取其错误（只有第一次调用能取到）
+    fn writer_error(&self) -> io::Error {
+        let writer = self.writer.lock().expect("journal lock poisoned").take();
+        match writer.map(JoinHandle::join) {
+            Some(Ok(Err(err))) => err,
+            _ => io::Error::other("journal writer stopped"),
+        }
+    }
+
+    /// 读取日志文件中的所有记录
+    pub fn read(path: &Path) -> Result<Vec<JournalEntry>> {
+        let file = File::open(path)
+            .with_context(|| format!("failed to open journal: {}", path.display()))?;
+        let mut entries = Vec::new();
+        for (idx, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let entry = serde_json::from_str(&line).with_context(|| {
+                format!("{}:{}: invalid journal entry", path.display(), idx + 1)
+            })?;
+            entries.push(entry);
+        }
+        Ok(entries)
+    }
+}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl JournalEntry {
+    /// 组件执行前的派发记录
+    pub fn dispatch(
+        user: Option<usize>,
+        iteration: usize,
+        task_id: &str,
+        action: &ActionDef,
+    ) -> Self {
+        JournalEntry::Dispatch {
+            user,
+            iteration,
+            task_id: task_id.to_string(),
+            action_id: action.id.clone(),
+            call: action.call.clone(),
+            with: serde_json::to_value(&action.with).unwrap_or_default(),
+        }
+    }
+
+    /// 组件返回的错误，含错误链
+    pub fn action_error(
+        user: Option<usize>,
+        iteration: usize,
+        task_id: &str,
+        action_id: &str,
+        error: &anyhow::Error,
+    ) -> Self {
+        JournalEntry::ActionError {
+            user,
+            iteration,
+            task_id: task_id.to_string(),
+            action_id: action_id.to_string(),
+            error: format!("{:#}", error),
+        }
+    }
+
+    /// 组件返回后的结果记录（断言由导出重新计算，不记录）
+    pub fn outcome(
+        user: Option<usize>,
+        iteration: usize,
+        task_id: &str,
+        action_id: &str,
+        outcome: &ActionOutcome,
+        events: &[SchedulerEvent],
+    ) -> Self {
+        JournalEntry::Outcome {
+            user,
+            iteration,
+            task_id: task_id.to_string(),
+            action_id: action_id.to_string(),
+            status: match outcome.status {
+                ActionStatus::Success => RecordedStatus::Success,
+                ActionStatus::Failed => RecordedStatus::Failed,
+            },
+            detail: outcome.detail.clone(),
+            response: outcome.response.as_ref().map(|response| RecordedResponse {
+                status_code: response.status_code,
+                headers: response.headers.clone(),
+                body: response.body.clone(),
+                elapsed: response.elapsed,
+            }),
+            events: events.iter().map(RecordedEvent::from).collect(),
+        }
+    }
+
+    /// 条目类型（与序列化的 `kind` 相同）
+    fn kind(&self) -> &'static str {
+        match self {
+            JournalEntry::UserStart { .. } => "user_start",
+            JournalEntry::Iteration { .. } => "iteration",
+            JournalEntry::Dispatch { .. } => "dispatch",
+            JournalEntry::Outcome { .. } => "outcome",
+            JournalEntry::ActionError { .. } => "action_error",
+            JournalEntry::Abort { .. } => "abort",
+            JournalEntry::Transition { .. } => "transition",
+            JournalEntry::SharedInit { .. } => "shared_init",
+            JournalEntry::SharedWrite { .. } => "shared_write",
+            JournalEntry::SharedRead { .. } => "shared_read",
+        }
+    }
+
+    /// 重放时作为输入使用的值（数据行、响应、事件、共享变量）是否被遮蔽；
+    /// 派发参数、详情与错误信息只用于展示，输出时同样会被遮蔽
+    fn has_masked_input(&self) -> bool {
+        let masked = |text: &str| text.contains(MASK);
+        match self {
+            JournalEntry::Iteration { data, .. } => data.values().any(|value| masked(value)),
+            JournalEntry::Outcome {
+                response, events, ..
+            } => {
+                response.as_ref().is_some_and(|response| {
+                    masked(&response.body)
+                        || response
+                            .headers
+                            .iter()
+                            .any(|(name, value)| masked(name) || masked(value))
+                }) || events
+                    .iter()
+                    .any(|event| masked(&serde_json::to_string(event).unwrap_or_default()))
+            }
+            JournalEntry::SharedInit { values } => {
+                masked(&serde_json::to_string(values).unwrap_or_default())
+            }
+            JournalEntry::SharedWrite { value, .. } => masked(value),
+            _ => false,
+        }
+    }
+}
+
+impl From<&SchedulerEvent> for RecordedEvent {
+    fn from(event: &SchedulerEvent) -> Self {
+        match event {
+            SchedulerEvent::RegisterAction(action) => RecordedEvent::RegisterAction {
+                action: serde_json::to_value(action).unwrap_or_default(),
+            },
+            SchedulerEvent::InsertTask(task) => RecordedEvent::InsertTask { task: task.into() },
+            SchedulerEvent::RemoveTask { task_id } => RecordedEvent::RemoveTask {
+                task_id: task_id.clone(),
+            },
+            SchedulerEvent::UpdateTask { task } => RecordedEvent::UpdateTask { task: task.into() },
+            SchedulerEvent::AddEdge { from, edge } => RecordedEvent::AddEdge {
+                from: from.clone(),
+                edge: edge.into(),
+            },
+            SchedulerEvent::RemoveEdge { from, target } => RecordedEvent::RemoveEdge {
+                from: from.clone(),
+                target: target.clone(),
+            },
+        }
+    }
+}
+
+impl RecordedEvent {
+    fn restore(&self) -> Result<SchedulerEvent> {
+        Ok(match self {
+            RecordedEvent::RegisterAction { action } => {
+                SchedulerEvent::RegisterAction(serde_json::from_value(action.clone())?)
+            }
+            RecordedEvent::InsertTask { task } => SchedulerEvent::InsertTask(task.restore()?),
+            RecordedEvent::RemoveTask { task_id } => SchedulerEvent::RemoveTask {
+                task_id: task_id.clone(),
+            },
+            RecordedEvent::UpdateTask { task } => SchedulerEvent::UpdateTask {
+                task: task.restore()?,
+            },
+            RecordedEvent::AddEdge { from, edge } => SchedulerEvent::AddEdge {
+                from: from.clone(),
+                edge: edge.restore()?,
+            },
+            RecordedEvent::RemoveEdge { from, target } => SchedulerEvent::RemoveEdge {
+                from: from.clone(),
+                target: target.clone(),
+            },
+        })
+    }
+}
+
+impl From<&WbsTask> for RecordedTask {
+    fn from(task: &WbsTask) -> Self {
+        Self {
+            id: task.id.clone(),
+            action_id: task.action_id.clone(),
+            kind: match task.kind {
+                WbsTaskKind::Action => "action",
+                WbsTaskKind::End => "end",
+                WbsTaskKind::Group => "group",
+            }
+            .to_string(),
+            outgoing: task.outgoing.iter().map(RecordedEdge::from).collect(),
+            parent: task.parent.clone(),
+            weight: task.weight,
+            max_visits: task.max_visits,
+            think_time: task.think_time.clone(),
+        }
+    }
+}
+
+impl RecordedTask {
+    fn restore(&self) -> Result<WbsTask> {
+        Ok(WbsTask {
+            id: self.id.clone(),
+            action_id: self.action_id.clone(),
+            kind: match self.kind.as_str() {
+                "action" => WbsTaskKind::Action,
+                "end" => WbsTaskKind::End,
+                "group" => WbsTaskKind::Group,
+                other => bail!("unknown task kind `{}` in journal", other),
+            },
+            outgoing: self
+                .outgoing
+                .iter()
+                .map(RecordedEdge::restore)
+                .collect::<Result<_>>()?,
+            parent: self.parent.clone(),
+            weight: self.weight,
+            max_visits: self.max_visits,
+            think_time: self.think_time.clone(),
+        })
+    }
+}
+
+impl From<&WbsEdge> for RecordedEdge {
+    fn from(edge: &WbsEdge) -> Self {
+        Self {
+            target: edge.target.clone(),
+            condition: edge
+                .condition
+                .as_ref()
+                .map(|condition| condition.source().to_string()),
+            label: edge.label.clone(),
+        }
+    }
+}
+
+impl RecordedEdge {
+    fn restore(&self) -> Result<WbsEdge> {
+        let condition = self
+            .condition
+            .as_deref()
+            .map(Condition::parse)
+            .transpose()
+            .map_err(|err| anyhow!("invalid condition in journal: {}", err))?;
+        Ok(WbsEdge {
+            target: self.target.clone(),
+            condition,
+            label: self.label.clone(),
+        })
+    }
+}
+
+/// 从日志重建的一次运行，按用户拆分
+#[derive(Debug, Default)]
+pub struct Replay {
+    /// 单次执行模式的动作结果
+    pub pipeline: ReplayComponent,
+    /// 负载测试中每个用户的记录，按上线顺序
+    pub users: IndexMap<usize, UserReplay>,
+    /// 所有用户对工作簿共享变量的写入
+    pub shared: Arc<SharedHistory>,
+}
+
+/// 单个用户的记录
+#[derive(Debug, Default)]
+pub struct UserReplay {
+    pub tenant_id: String,
+    pub allocated_ip: Option<String>,
+    /// 第一次迭代的序号，重放时交给 `UserExecutor::starting_at`
+    pub first_iteration: usize,
+    /// 每次迭代使用的数据行
+    pub iterations: Vec<IndexMap<String, String>>,
+    /// 被中止的迭代，按迭代序号
+    pub aborts: IndexMap<usize, RecordedAbort>,
+    /// 读取共享变量时看到的版本，按读取序号
+    pub shared_reads: BTreeMap<usize, u64>,
+    pub component: ReplayComponent,
+}
+
+impl UserReplay {
+    /// 该用户读取工作簿共享变量的方式：按记录的版本由 `history` 重建
+    pub fn shared_replay(&self, history: &Arc<SharedHistory>) -> SharedReplay {
+        SharedReplay {
+            history: Arc::clone(history),
+            reads: self.shared_reads.clone(),
+            next_read: 0,
+            version: 0,
+        }
+    }
+}
+
+/// 记录的工作簿共享变量：初始值与按版本号排序的写入
+#[derive(Debug, Default)]
+pub struct SharedHistory {
+    initial: IndexMap<String, SharedValue>,
+    writes: Vec<(u64, String, String, ExportMode)>,
+}
+
+impl SharedHistory {
+    /// 依次应用版本号不超过 `version` 的写入，得到当时的快照
+    pub fn snapshot(&self, version: u64) -> IndexMap<String, String> {
+        let store = SharedStore::from_values(self.initial.clone());
+        for (_, name, value, mode) in self.writes.iter().take_while(|write| write.0 <= version) {
+            store.write(name, value.clone(), *mode);
+        }
+        store.snapshot()
+    }
+}
+
+/// 重放一个用户对工作簿共享变量的读取
+#[derive(Debug)]
+pub struct SharedReplay {
+    history: Arc<SharedHistory>,
+    reads: BTreeMap<usize, u64>,
+    next_read: usize,
+    version: u64,
+}
+
+impl SharedReplay {
+    /// 下一次读取看到的值：版本未记录时沿用上一次读取的版本
+    pub fn next_snapshot(&mut self) -> IndexMap<String, String> {
+        if let Some(version) = self.reads.get(&self.next_read) {
+            self.version = *version;
+        }
+        self.next_read += 1;
+        self.history.snapshot(self.version)
+    }
+}
+
+/// 记录的迭代中止：在第 `step` 个动作（节点 `task_id`）之前中止
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedAbort {
+    pub step: usize,
+    pub task_id: String,
+    pub reason: String,
+}
+
+impl Replay {
+    pub fn from_entries(entries: Vec<JournalEntry>) -> Self {
+        let mut replay = Self::default();
+        let mut shared = SharedHistory::default();
+        for entry in entries {
+            match entry {
+                JournalEntry::UserStart {
+                    user,
+                    tenant_id,
+                    allocated_ip,
+                    first_iteration,
+                } => {
+                    let user = replay.users.entry(user).or_default();
+                    user.tenant_id = tenant_id;
+                    user.allocated_ip = allocated_ip;
+                    user.first_iteration = first_iteration;
+                }
+                JournalEntry::Iteration { user, data, .. } => {
+                    replay.users.entry(user).or_default().iterations.push(data);
+                }
+                JournalEntry::Outcome {
+                    user,
+                    action_id,
+                    status,
+                    detail,
+                    response,
+                    events,
+                    ..
+                } => {
+                    let recorded = RecordedOutcome {
+                        action_id,
+                        status,
+                        detail,
+                        response,
+                        events,
+                        error: None,
+                    };
+                    replay.push_outcome(user, recorded);
+                }
+                JournalEntry::ActionError {
+                    user,
+                    action_id,
+                    error,
+                    ..
+                } => {
+                    let recorded = RecordedOutcome {
+                        action_id,
+                        status: RecordedStatus::Failed,
+                        detail: None,
+                        response: None,
+                        events: Vec::new(),
+                        error: Some(error),
+                    };
+                    replay.push_outcome(user, recorded);
+                }
+                JournalEntry::Abort {
+                    user,
+                    iteration,
+                    step,
+                    task_id,
+                    reason,
+                } => {
+                    let abort = RecordedAbort {
+                        step,
+                        task_id,
+                        reason,
+                    };
+                    replay
+                        .users
+                        .entry(user)
+                        .or_default()
+                        .aborts
+                        .insert(iteration, abort);
+                }
+                JournalEntry::SharedInit { values } => shared.initial = values,
+                JournalEntry::SharedWrite {
+                    version,
+                    name,
+                    value,
+                    mode,
+                    ..
+                } => shared.writes.push((version, name, value, mode)),
+                JournalEntry::SharedRead {
+                    user,
+                    read,
+                    version,
+                    ..
+                } => {
+                    replay
+                        .users
+                        .entry(user)
+                        .or_default()
+                        .shared_reads
+                        .insert(read, version);
+                }
+                JournalEntry::Dispatch { .. } | JournalEntry::Transition { .. } => {}
+            }
+        }
+        // 日志中的写入顺序与版本号顺序可能不同（多个用户并发写入）
+        shared.writes.sort_by_key(|write| write.0);
+        replay.shared = Arc::new(shared);
+        replay
+    }
+
+    fn push_outcome(&mut self, user: Option<usize>, recorded: RecordedOutcome) {
+        match user {
+            Some(user) => self
+                .users
+                .entry(user)
+                .or_default()
+                .component
+                .outcomes
+                .push_back(recorded),
+            None => self.pipeline.outcomes.push_back(recorded),
+        }
+    }
+
+    /// 读取日志；影响重放的记录中含有被遮蔽的密钥时失败，
+    /// 否则重放会静默地使用遮蔽后的值
+    pub fn load(path: &Path) -> Result<Self> {
+        let entries = Journal::read(path)?;
+        if let Some((idx, entry)) = entries
+            .iter()
+            .enumerate()
+            .find(|(_, entry)| entry.has_masked_input())
+        {
+            bail!(
+                "{}: entry #{} ({}) contains masked secret values (`{}`); \
+                 this run cannot be replayed",
+                path.display(),
+                idx + 1,
+                entry.kind(),
+                MASK
+            );
+        }
+        Ok(Self::from_entries(entries))
+    }
+}
+
+#[derive(Debug)]
+struct RecordedOutcome {
+    action_id: String,
+    status: RecordedStatus,
+    detail: Option<String>,
+    response: Option<RecordedResponse>,
+    events: Vec<RecordedEvent>,
+    /// 组件返回的错误；重放时原样返回
+    error: Option<String>,
+}
+
+/// 按日志顺序返回记录的动作结果并重放其事件，不访问网络
+#[derive(Debug, Default)]
+pub struct ReplayComponent {
+    outcomes: VecDeque<RecordedOutcome>,
+}
+
+impl ReplayComponent {
+    /// 尚未重放的动作结果数
+    pub fn remaining(&self) -> usize {
+        self.outcomes.len()
+    }
+}
+
+impl ActionComponent for ReplayComponent {
+    fn init(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn do_action(
+        &mut self,
+        action: &ActionDef,
+        ctx: &mut ActionContext<'_>,
+    ) -> Result<ActionOutcome> {
+        let recorded = self
+            .outcomes
+            .pop_front()
+            .with_context(|| format!("replay diverged: no recorded outcome for `{}`", action.id))?;
+        if recorded.action_id != action.id {
+            bail!(
+                "replay diverged: expected action `{}`, got `{}`",
+                recorded.action_id,
+                action.id
+            );
+        }
+        if let Some(error) = recorded.error {
+            bail!("{}", error);
+        }
+
+        for event in &recorded.events {
+            match event.restore()? {
+                SchedulerEvent::RegisterAction(action) => ctx.register_action(action),
+                SchedulerEvent::InsertTask(task) => ctx.add_task(task),
+                SchedulerEvent::RemoveTask { task_id } => ctx.remove_task(&task_id),
+                SchedulerEvent::UpdateTask { task } => ctx.update_task(task),
+                SchedulerEvent::AddEdge { from, edge } => ctx.add_edge(&from, edge),
+                SchedulerEvent::RemoveEdge { from, target } => ctx.remove_edge(&from, &target),
+            }
+        }
+
+        Ok(ActionOutcome {
+            status: match recorded.status {
+                RecordedStatus::Success => ActionStatus::Success,
+                RecordedStatus::Failed => ActionStatus::Failed,
+            },
+            detail: recorded.detail,
+            response: recorded.response.map(|response| ActionResponse {
+                status_code: response.status_code,
+                headers: response.headers,
+                body: response.body,
+                elapsed: response.elapsed,
+            }),
+            assertions: Vec::new(),
+        })
+    }
+
+    fn release(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masked_inputs_fail_replay() {
+        const SECRET: &str = "journal-test-secret-3f9c";
+        secrets::track(SECRET);
+        let path =
+            std::env::temp_dir().join(format!("scheduler-journal-masked-{}", std::process::id()));
+        let journal = Journal::create(&path).expect("create journal");
+
+        // 错误信息只用于展示，被遮蔽不影响重放
+        let error = anyhow!("login rejected key {}", SECRET);
+        journal
+            .record(&JournalEntry::action_error(
+                Some(1),
+                0,
+                "start",
+                "login",
+                &error,
+            ))
+            .expect("record");
+        journal.flush().expect("flush journal");
+        assert!(Replay::load(&path).is_ok());
+
+        // 响应体是导出与条件的输入
+        let outcome = ActionOutcome::success().with_response(ActionResponse {
+            body: format!(r#"{{"token": "{}"}}"#, SECRET),
+            ..Default::default()
+        });
+        journal
+            .record(&JournalEntry::outcome(
+                Some(1),
+                0,
+                "start",
+                "login",
+                &outcome,
+                &[],
+            ))
+            .expect("record");
+        journal.flush().expect("flush journal");
+        let err = Replay::load(&path).expect_err("masked response");
+        std::fs::remove_file(&path).ok();
+        assert!(
+            err.to_string()
+                .contains("entry #2 (outcome) contains masked secret values"),
+            "{err}"
+        );
+    }
+}
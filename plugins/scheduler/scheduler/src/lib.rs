@@ -1,6 +1,14 @@
+pub mod arrival;
+pub mod checkpoint;
 pub mod engine;
 pub mod ip_manager;
+pub mod journal;
+pub mod progress;
+pub mod rate_limit;
+pub mod shared;
+pub mod stages;
 pub mod template;
+pub mod think_time;
 pub mod user;
 pub mod utils;
 
@@ -8,12 +16,20 @@ pub mod utils;
 #[cfg(target_arch = "wasm32")]
 pub mod component;
 
+pub use arrival::ArrivalSchedule;
+pub use checkpoint::{Checkpoint, RunMetrics, RunState, UserRecord};
 pub use engine::SchedulerPipeline;
-pub use ip_manager::IpPoolManager;
+pub use ip_manager::{IpAllocation, IpPoolManager};
+pub use journal::{Journal, JournalEntry, RecordedAbort, Replay, ReplayComponent};
+pub use progress::{ProgressBoard, UserProgress};
+pub use rate_limit::RateLimiter;
 pub use scheduler_core::{dsl, error::SchedulerError, state_machine, wbs, workbook};
 pub use scheduler_executor::{
     ActionComponent, ActionContext, ActionOutcome, ActionStatus, ActionTrace, SchedulerEvent,
 };
+pub use shared::SharedStore;
+pub use stages::StagePlan;
 pub use template::TemplateContext;
-pub use user::{ExecutionTrace, UserContext, UserExecutor};
-pub use utils::parse_duration;
+pub use think_time::ThinkTime;
+pub use user::{ExecutionTrace, IterationLimits, RetireSignal, UserContext, UserExecutor};
+pub use utils::{parse_duration, parse_rate};
@@ -1,15 +1,29 @@
 use std::{
     env,
+    net::IpAddr,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
     time::{Duration, Instant},
 };
 
 use anyhow::{Context, Result};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::task::JoinHandle;
 use tokio::time::sleep;
 
-use scheduler::{IpPoolManager, SchedulerPipeline, UserContext, UserExecutor, parse_duration};
-use scheduler_actions_http::HttpActionComponent;
+use scheduler::{
+    ActionTrace, ArrivalSchedule, Checkpoint, ExecutionTrace, IpPoolManager, IterationLimits,
+    Journal, JournalEntry, ProgressBoard, RateLimiter, Replay, RetireSignal, RunState,
+    SchedulerPipeline, SharedStore, StagePlan, TemplateContext, ThinkTime, UserContext,
+    UserExecutor, UserRecord, parse_duration, state_machine::StateMachine,
+};
+use scheduler_actions_http::AsyncHttpActionComponent;
+use scheduler_core::{
+    data::FeederSet, dsl::ParseMode, export::AssertionSummary, secrets, variables::parse_override,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -18,15 +32,14 @@ async fn main() -> anyhow::Result<()> {
         .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")))
         .join("res/http_scenario.yaml");
 
-    let scenario_path = env::args()
-        .nth(1)
-        .map(PathBuf::from)
-        .unwrap_or(default_path);
-
-    let raw = std::fs::read_to_string(&scenario_path)
-        .with_context(|| format!("failed to read scenario file: {}", scenario_path.display()))?;
+    let args = CliArgs::parse(env::args().skip(1))?;
+    let scenario_path = args.scenario_path.unwrap_or(default_path);
 
-    let mut pipeline = SchedulerPipeline::load_from_yaml_str(&raw)?;
+    let mut pipeline = SchedulerPipeline::load_from_yaml_file(&scenario_path, args.mode)
+        .with_context(|| format!("failed to load scenario file: {}", scenario_path.display()))?;
+    pipeline.apply_variable_overrides(&args.overrides)?;
+    // --strict 同时启用严格模板：未定义的变量让动作失败，而不是原样保留占位符
+    pipeline.set_strict_templates(args.mode == ParseMode::Strict);
     let summary = pipeline.summary();
 
     println!("scenario: {}", pipeline.scenario().name);
@@ -38,37 +51,492 @@ async fn main() -> anyhow::Result<()> {
     for id in pipeline.workbook().resources.keys() {
         println!("  - {}", id);
     }
+    for warning in pipeline.validation_report().warnings() {
+        println!("⚠️  {}", warning);
+    }
+    if !pipeline.variables().is_empty() {
+        print!("variables:\n{}", pipeline.variables());
+    }
+    if !pipeline.secrets().is_empty() {
+        print!("secrets:\n{}", pipeline.secrets());
+    }
+
+    if let Some(path) = &args.replay {
+        return replay(&mut pipeline, path);
+    }
+    let journal = match &args.journal {
+        Some(path) => Some(Arc::new(Journal::create(path).with_context(|| {
+            format!("failed to create journal: {}", path.display())
+        })?)),
+        None => None,
+    };
+
+    // --resume 之后继续写入同一个检查点，除非另行指定 --checkpoint
+    let checkpoint = CheckpointOptions {
+        path: args.checkpoint.clone().or_else(|| args.resume.clone()),
+        resume: args.resume.as_deref().map(Checkpoint::load).transpose()?,
+    };
 
     // 检测是否有负载配置
     if let Some(load_config) = &pipeline.scenario().load {
         println!("\n🚀 Load testing mode enabled");
-        println!("Ramp-up phases: {}", load_config.ramp_up.phases.len());
+        match &load_config.arrival_rate {
+            _ if !load_config.stages.is_empty() => {
+                for stage in &load_config.stages {
+                    println!(
+                        "Stage: {} users over {}",
+                        stage.target_users, stage.duration
+                    );
+                }
+            }
+            Some(arrival_rate) => println!(
+                "Arrival rate: {} → {} over {} ({:?})",
+                arrival_rate.rate,
+                arrival_rate
+                    .target_rate
+                    .as_deref()
+                    .unwrap_or(&arrival_rate.rate),
+                arrival_rate.duration,
+                arrival_rate.distribution
+            ),
+            None => println!("Ramp-up phases: {}", load_config.ramp_up.phases.len()),
+        }
         println!("User lifetime: {:?}", load_config.user_lifetime.mode);
         println!("Iterations: {}", load_config.user_lifetime.iterations);
         println!("Think time: {}", load_config.user_lifetime.think_time);
+        if let Some(pacing) = &load_config.user_lifetime.pacing {
+            println!("Pacing: one iteration every {}", pacing);
+        }
+        if let Some(concurrency) = &load_config.concurrency {
+            println!(
+                "Concurrency: {} users, spawn {}, actions {}",
+                concurrency.max_concurrent_users,
+                concurrency.spawn_rate_limit,
+                concurrency
+                    .action_rate_limit
+                    .as_deref()
+                    .unwrap_or("unlimited")
+            );
+        }
+
+        let data_dir = pipeline.scenario().data.as_ref().map(|data| {
+            scenario_path
+                .parent()
+                .unwrap_or_else(|| Path::new("."))
+                .join(&data.dir)
+        });
+        let feeders = match &data_dir {
+            Some(dir) => FeederSet::load(pipeline.scenario().data.as_ref(), dir)?,
+            None => FeederSet::default(),
+        };
+        for feeder in feeders.feeders() {
+            println!("Data feeder '{}': {} rows", feeder.name(), feeder.len());
+        }
 
-        run_load_test(&pipeline, load_config).await?;
+        run_load_test(
+            &pipeline,
+            load_config,
+            Arc::new(feeders),
+            args.edge_hits.as_deref(),
+            journal,
+            checkpoint,
+        )
+        .await?;
     } else {
         println!("\n📋 Single execution mode");
+        if let Some(journal) = journal {
+            pipeline.set_journal(journal);
+        }
+        pipeline.install_ctrlc_handler()?;
         let traces = pipeline.run_default()?;
-        println!("executed {} action(s):", traces.len());
-        for trace in traces {
-            println!(
-                "  - task={} action={} status={:?} detail={}",
-                trace.task_id,
-                trace.action_id,
-                trace.status,
-                trace.detail.as_deref().unwrap_or("<no detail>")
-            );
+        print_action_traces(&traces);
+    }
+
+    Ok(())
+}
+
+fn print_action_traces(traces: &[ActionTrace]) {
+    println!("executed {} action(s):", traces.len());
+    for trace in traces {
+        println!(
+            "  - task={} action={} status={:?} detail={}",
+            trace.task_id,
+            trace.action_id,
+            trace.status,
+            trace.detail.as_deref().unwrap_or("<no detail>")
+        );
+    }
+
+    let assertions: AssertionSummary = traces.iter().flat_map(|t| &t.assertions).collect();
+    if !assertions.is_empty() {
+        println!("assertions:");
+        print!("{}", assertions);
+    }
+}
+
+/// 按运行日志重放：动作结果取自日志，不访问网络；负载测试的用户依次重放，
+/// 不等待 think time。工作簿共享变量按记录的全局写入顺序重建，每个用户读到的值
+/// 与记录时相同。
+fn replay(pipeline: &mut SchedulerPipeline, path: &Path) -> Result<()> {
+    let mut replay = Replay::load(path)?;
+    println!("\n⏪ Replaying journal {}", path.display());
+
+    let Some(load_config) = &pipeline.scenario().load else {
+        pipeline.install_ctrlc_handler()?;
+        let traces = pipeline.run(&mut replay.pipeline)?;
+        print_action_traces(&traces);
+        return Ok(());
+    };
+
+    let mut traces: Vec<ExecutionTrace> = Vec::new();
+    for (user_id, user) in replay.users {
+        let allocated_ip = user
+            .allocated_ip
+            .as_deref()
+            .map(str::parse)
+            .transpose()
+            .context("invalid IP address in journal")?;
+        let shared = user.shared_replay(&replay.shared);
+        let mut component = user.component;
+        let mut executor = UserExecutor::new(
+            UserContext::new(user_id, user.tenant_id, allocated_ip),
+            pipeline.state_machine().clone(),
+            load_config.user_lifetime.iterations,
+            ThinkTime::None,
+            pipeline.template_context().clone(),
+        )
+        .starting_at(user.first_iteration)
+        .with_recorded_shared(shared)
+        .with_recorded_data(user.iterations)
+        .with_recorded_aborts(user.aborts);
+        match executor.run(&mut component) {
+            Ok(user_traces) => traces.extend(user_traces),
+            Err(e) => eprintln!(
+                "✗ User-{} failed: {}",
+                user_id,
+                secrets::redact(&format!("{:#}", e))
+            ),
         }
     }
 
+    println!("replayed {} action(s):", traces.len());
+    for trace in &traces {
+        println!(
+            "  - user={} iteration={} node={} -> {} action={} status={} detail={}",
+            trace.user_id,
+            trace.iteration + 1,
+            trace.node_id,
+            trace.next_node,
+            trace.action_id,
+            trace.status,
+            trace.detail
+        );
+    }
+    let assertions: AssertionSummary = traces.iter().flat_map(|t| &t.assertions).collect();
+    if !assertions.is_empty() {
+        println!("assertions:");
+        print!("{}", assertions);
+    }
     Ok(())
 }
 
+/// 命令行参数：`[--strict] [--var key=value]... [--edge-hits hits.json]
+/// [--journal run.ndjson | --replay run.ndjson] [--checkpoint state.json] [--resume state.json]
+/// [scenario.yaml]`
+struct CliArgs {
+    scenario_path: Option<PathBuf>,
+    mode: ParseMode,
+    overrides: Vec<(String, String)>,
+    /// 负载测试结束后写入各边命中次数（供 `Ntx graph --hits` 叠加）
+    edge_hits: Option<PathBuf>,
+    /// 将本次运行写入日志
+    journal: Option<PathBuf>,
+    /// 按日志重放，不访问网络
+    replay: Option<PathBuf>,
+    /// 负载测试期间定期写入检查点
+    checkpoint: Option<PathBuf>,
+    /// 从检查点继续负载测试
+    resume: Option<PathBuf>,
+}
+
+impl CliArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self> {
+        let mut parsed = Self {
+            scenario_path: None,
+            mode: ParseMode::Lenient,
+            overrides: Vec::new(),
+            edge_hits: None,
+            journal: None,
+            replay: None,
+            checkpoint: None,
+            resume: None,
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--strict" => parsed.mode = ParseMode::Strict,
+                "--var" => {
+                    let pair = args.next().context("--var expects key=value")?;
+                    parsed.overrides.push(parse_override(&pair)?);
+                }
+                "--edge-hits" => {
+                    let path = args.next().context("--edge-hits expects a file path")?;
+                    parsed.edge_hits = Some(PathBuf::from(path));
+                }
+                "--journal" => {
+                    let path = args.next().context("--journal expects a file path")?;
+                    parsed.journal = Some(PathBuf::from(path));
+                }
+                "--replay" => {
+                    let path = args.next().context("--replay expects a file path")?;
+                    parsed.replay = Some(PathBuf::from(path));
+                }
+                "--checkpoint" => {
+                    let path = args.next().context("--checkpoint expects a file path")?;
+                    parsed.checkpoint = Some(PathBuf::from(path));
+                }
+                "--resume" => {
+                    let path = args.next().context("--resume expects a checkpoint file")?;
+                    parsed.resume = Some(PathBuf::from(path));
+                }
+                _ => {
+                    if let Some(pair) = arg.strip_prefix("--var=") {
+                        parsed.overrides.push(parse_override(pair)?);
+                    } else if parsed.scenario_path.is_none() && !arg.starts_with("--") {
+                        parsed.scenario_path = Some(PathBuf::from(arg));
+                    } else {
+                        anyhow::bail!("unexpected argument: {}", arg);
+                    }
+                }
+            }
+        }
+        if parsed.journal.is_some() && parsed.replay.is_some() {
+            anyhow::bail!("--journal and --replay cannot be combined");
+        }
+        Ok(parsed)
+    }
+}
+
+/// 负载测试期间打印场景进度的间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(5);
+/// 负载测试期间写入检查点的间隔
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 按阶段调整用户数的间隔
+const STAGE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 负载测试的检查点选项
+struct CheckpointOptions {
+    /// 定期写入的检查点文件
+    path: Option<PathBuf>,
+    /// 从该检查点继续
+    resume: Option<Checkpoint>,
+}
+
+/// 所有用户共享的运行资源
+struct LoadRun {
+    state_machine: StateMachine,
+    template: TemplateContext,
+    iterations: usize,
+    think_time: ThinkTime,
+    pacing: Option<Duration>,
+    limits: IterationLimits,
+    feeders: Arc<FeederSet>,
+    shared: Arc<SharedStore>,
+    progress: Arc<ProgressBoard>,
+    journal: Option<Arc<Journal>>,
+    run_state: Arc<RunState>,
+    ip_manager: Arc<Mutex<IpPoolManager>>,
+    pool_id: String,
+    ip_binding_enabled: bool,
+    release_on_task_end: bool,
+    /// 同时运行的用户数上限；超出的用户排队等待空位
+    concurrency: Option<Arc<Semaphore>>,
+    /// 用户开始执行的速率
+    spawn_rate: Option<Arc<RateLimiter>>,
+    /// 所有用户合计的动作速率
+    action_rate: Option<Arc<RateLimiter>>,
+}
+
+/// 已启动的用户任务
+struct UserTask {
+    task: JoinHandle<()>,
+    retire: RetireSignal,
+    /// 已拿到并发空位；排队中的用户不算在运行
+    started: Arc<AtomicBool>,
+}
+
+impl UserTask {
+    /// 持有并发空位、仍在运行且未被要求退役
+    fn is_live(&self) -> bool {
+        self.started.load(Ordering::Acquire)
+            && !self.task.is_finished()
+            && !self.retire.is_retired()
+    }
+}
+
+impl LoadRun {
+    /// 启动一个用户，从第 `first_iteration` 次迭代开始
+    ///
+    /// 用户先排队等待并发空位（`permit` 为调用方已取得的空位），拿到空位后才分配
+    /// IP，排队的用户不占用 IP 池；`allocated_ip` 为检查点中恢复的 IP 时沿用。
+    fn spawn_user(
+        self: &Arc<Self>,
+        user_id: usize,
+        tenant_id: String,
+        allocated_ip: Option<IpAddr>,
+        first_iteration: usize,
+        permit: Option<OwnedSemaphorePermit>,
+    ) -> UserTask {
+        let retire = RetireSignal::new();
+        let started = Arc::new(AtomicBool::new(false));
+        let run = Arc::clone(self);
+        let user_retire = retire.clone();
+        let user_started = Arc::clone(&started);
+
+        // 启动用户任务
+        let task = tokio::spawn(async move {
+            // 先排队等待并发空位，再按创建速率放行；许可在用户结束时归还
+            let _permit = match (permit, &run.concurrency) {
+                (Some(permit), _) => Some(permit),
+                (None, Some(semaphore)) => Some(
+                    Arc::clone(semaphore)
+                        .acquire_owned()
+                        .await
+                        .expect("concurrency semaphore closed"),
+                ),
+                (None, None) => None,
+            };
+            user_started.store(true, Ordering::Release);
+            if let Some(limiter) = &run.spawn_rate {
+                sleep(limiter.reserve()).await;
+            }
+
+            let allocated_ip = match allocated_ip {
+                Some(ip) => Some(ip),
+                None => {
+                    let ip = run.allocate_ip(user_id, &tenant_id);
+                    run.run_state.set_allocated_ip(user_id, ip);
+                    ip
+                }
+            };
+            let user_ctx = UserContext {
+                id: user_id,
+                tenant_id,
+                allocated_ip,
+                created_at: Instant::now(),
+            };
+            let mut executor = run.executor(user_ctx, first_iteration, user_retire);
+
+            // 创建非阻塞 HTTP Action 组件：等待响应与思考时间时不占用运行时线程
+            let mut component = AsyncHttpActionComponent::new();
+
+            match executor.run_async(&mut component).await {
+                Ok(traces) => {
+                    println!(
+                        "✓ User-{} completed {} iterations, {} actions",
+                        user_id,
+                        run.iterations,
+                        traces.len()
+                    );
+                }
+                Err(e) => {
+                    // 失败时报告用户停在迭代的哪个位置
+                    let at = run
+                        .progress
+                        .user(user_id)
+                        .map(|p| format!(" at {}", p))
+                        .unwrap_or_default();
+                    eprintln!(
+                        "✗ User-{} failed{}: {}",
+                        user_id,
+                        at,
+                        secrets::redact(&format!("{:#}", e))
+                    );
+                }
+            }
+            run.run_state.finish_user(user_id);
+
+            // 释放 IP（如果需要）
+            let release_ip = run.ip_binding_enabled && !run.release_on_task_end;
+            if release_ip && let Some(ip) = allocated_ip {
+                let mut manager = run.ip_manager.lock().unwrap();
+                if let Err(e) = manager.release_ip(&run.pool_id, ip) {
+                    eprintln!(
+                        "⚠️  Failed to release IP {} for user-{}: {}",
+                        ip, user_id, e
+                    );
+                }
+            }
+        });
+        UserTask {
+            task,
+            retire,
+            started,
+        }
+    }
+
+    /// 创建用户执行器
+    fn executor(
+        &self,
+        user_ctx: UserContext,
+        first_iteration: usize,
+        retire: RetireSignal,
+    ) -> UserExecutor {
+        let mut executor = UserExecutor::new(
+            user_ctx,
+            self.state_machine.clone(),
+            self.iterations,
+            self.think_time,
+            self.template.clone(),
+        )
+        .with_feeders(Arc::clone(&self.feeders))
+        .with_shared_store(Arc::clone(&self.shared))
+        .with_progress(Arc::clone(&self.progress))
+        .with_run_state(Arc::clone(&self.run_state))
+        .with_iteration_limits(self.limits)
+        .with_retire_signal(retire)
+        .starting_at(first_iteration);
+        if let Some(journal) = &self.journal {
+            executor = executor.with_journal(Arc::clone(journal));
+        }
+        if let Some(limiter) = &self.action_rate {
+            executor = executor.with_action_rate_limit(Arc::clone(limiter));
+        }
+        if let Some(pacing) = self.pacing {
+            executor = executor.with_pacing(pacing);
+        }
+        executor
+    }
+
+    /// 为用户分配 IP（如果启用）；分配失败时不绑定 IP
+    fn allocate_ip(&self, user_id: usize, tenant_id: &str) -> Option<IpAddr> {
+        if !self.ip_binding_enabled {
+            return None;
+        }
+        let mut manager = self.ip_manager.lock().unwrap();
+        match manager.allocate_ip(&self.pool_id, tenant_id, &format!("user-{}", user_id)) {
+            Ok(ip) => Some(ip),
+            Err(e) => {
+                eprintln!("⚠️  Failed to allocate IP for user-{}: {}", user_id, e);
+                None
+            }
+        }
+    }
+
+    fn checkpoint(&self, elapsed: Duration) -> Checkpoint {
+        let ip_allocations = self.ip_manager.lock().unwrap().allocations();
+        self.run_state
+            .checkpoint(elapsed, ip_allocations, self.shared.values())
+    }
+}
+
 async fn run_load_test(
     pipeline: &SchedulerPipeline,
     load_config: &scheduler_core::dsl::LoadSection,
+    feeders: Arc<FeederSet>,
+    edge_hits_path: Option<&Path>,
+    journal: Option<Arc<Journal>>,
+    checkpoint: CheckpointOptions,
 ) -> Result<()> {
     use scheduler_core::dsl::IpBindingStrategy;
 
@@ -97,155 +565,301 @@ async fn run_load_test(
         }
     }
 
-    let ip_manager = Arc::new(Mutex::new(ip_manager));
-
-    // 准备用户生命周期参数
-    let iterations = load_config.user_lifetime.iterations;
-    let think_time = parse_duration(&load_config.user_lifetime.think_time)?;
-
-    // 收集所有执行痕迹
-    let all_traces = Arc::new(Mutex::new(Vec::new()));
+    // 从检查点恢复 IP 分配、共享变量与运行状态
+    let resume = checkpoint.resume.unwrap_or_default();
+    for allocation in &resume.ip_allocations {
+        ip_manager.restore_allocation(allocation)?;
+    }
+    let shared = Arc::new(SharedStore::from_values(resume.shared.clone()));
+    if let Some(journal) = &journal
+        && !resume.shared.is_empty()
+    {
+        journal
+            .record(&JournalEntry::SharedInit {
+                values: resume.shared.clone(),
+            })
+            .context("failed to write journal")?;
+    }
+    let run_state = Arc::new(RunState::from_checkpoint(&resume));
+
+    // 按 WBS 分组与权重汇总的运行进度，定期打印
+    let progress = Arc::new(ProgressBoard::new(pipeline.progress_model().clone()));
+    let reporter = {
+        let progress = Arc::clone(&progress);
+        tokio::spawn(async move {
+            loop {
+                sleep(PROGRESS_INTERVAL).await;
+                let (users, finished) = progress.counts();
+                if let Some(scenario) = progress.scenario() {
+                    println!(
+                        "📊 Progress: {:.1}% ({} users, {} finished)",
+                        scenario * 100.0,
+                        users,
+                        finished
+                    );
+                }
+            }
+        })
+    };
+
+    let run = Arc::new(LoadRun {
+        state_machine: pipeline.state_machine().clone(),
+        template: pipeline.template_context().clone(),
+        // 到达率模式下每个到达只执行一次迭代
+        iterations: match load_config.arrival_rate {
+            Some(_) => 1,
+            None => load_config.user_lifetime.iterations,
+        },
+        think_time: ThinkTime::parse(&load_config.user_lifetime.think_time)
+            .context("invalid user_lifetime.think_time")?,
+        pacing: load_config
+            .user_lifetime
+            .pacing
+            .as_deref()
+            .map(parse_duration)
+            .transpose()
+            .context("invalid user_lifetime.pacing")?,
+        limits: IterationLimits {
+            max_steps: load_config.user_lifetime.max_steps_per_iteration,
+            timeout: load_config
+                .user_lifetime
+                .iteration_timeout
+                .as_deref()
+                .map(parse_duration)
+                .transpose()?,
+        },
+        feeders,
+        shared: Arc::clone(&shared),
+        progress: Arc::clone(&progress),
+        journal,
+        run_state: Arc::clone(&run_state),
+        ip_manager: Arc::new(Mutex::new(ip_manager)),
+        pool_id: load_config.user_resources.ip_binding.pool_id.clone(),
+        ip_binding_enabled: load_config.user_resources.ip_binding.enabled,
+        release_on_task_end: matches!(
+            load_config.user_resources.ip_binding.strategy,
+            IpBindingStrategy::PerTask
+        ),
+        concurrency: load_config
+            .concurrency
+            .as_ref()
+            .map(|c| Arc::new(Semaphore::new(c.max_concurrent_users))),
+        spawn_rate: load_config
+            .concurrency
+            .as_ref()
+            .map(|c| RateLimiter::parse(&c.spawn_rate_limit, Some(1.0)))
+            .transpose()
+            .context("invalid load.concurrency.spawn_rate_limit")?
+            .map(Arc::new),
+        action_rate: load_config
+            .concurrency
+            .as_ref()
+            .and_then(|c| c.action_rate_limit.as_deref())
+            .map(|rate| RateLimiter::parse(rate, None))
+            .transpose()
+            .context("invalid load.concurrency.action_rate_limit")?
+            .map(Arc::new),
+    });
+
+    // 从检查点继续时，时间轴接着检查点写入时的位置
+    let start_time = Instant::now();
+    let offset = resume.elapsed;
+    let elapsed = move || offset + start_time.elapsed();
+
+    let checkpointer = checkpoint.path.clone().map(|path| {
+        let run = Arc::clone(&run);
+        tokio::spawn(async move {
+            loop {
+                sleep(CHECKPOINT_INTERVAL).await;
+                if let Err(e) = run.checkpoint(elapsed()).save(&path) {
+                    eprintln!("⚠️  {:#}", e);
+                }
+            }
+        })
+    });
 
     // 用户计数器
-    let mut user_id_counter = 0usize;
+    let mut user_id_counter = resume.last_user_id;
     let mut tasks = vec![];
 
-    println!("\n⏱️  Starting ramp-up...");
-    let start_time = Instant::now();
-
-    // 按阶段生成用户
-    for phase in &load_config.ramp_up.phases {
-        let target_time = Duration::from_secs(phase.at_second);
-        let elapsed = start_time.elapsed();
+    if !resume.users.is_empty() {
+        let pending: Vec<_> = resume
+            .users
+            .iter()
+            .filter(|(_, user)| !user.finished)
+            .collect();
+        println!(
+            "\n↻ Resuming at {:.0}s: {} users finished, {} to continue",
+            offset.as_secs_f64(),
+            resume.users.len() - pending.len(),
+            pending.len()
+        );
+        for (&user_id, user) in &resume.users {
+            if user.finished {
+                progress.finish_user(user_id);
+            }
+        }
+        for (&user_id, user) in pending {
+            tasks.push(run.spawn_user(
+                user_id,
+                user.tenant_id.clone(),
+                user.allocated_ip,
+                user.completed_iterations,
+                None,
+            ));
+        }
+    }
 
-        if elapsed < target_time {
-            sleep(target_time - elapsed).await;
+    if !load_config.stages.is_empty() {
+        // 按目标用户数启动新用户；目标下降时最新启动的用户完成当前迭代后退役，
+        // 完成全部迭代（或失败）的用户由新用户补上
+        let plan = StagePlan::new(&load_config.stages)?;
+        println!(
+            "\n⏱️  Starting stages: {} over {:.0}s...",
+            load_config.stages.len(),
+            plan.duration().as_secs_f64()
+        );
+        while let Some((target, tenant_id)) = plan.target_at(elapsed()) {
+            let mut live = tasks.iter().filter(|user| user.is_live()).count();
+            for user in tasks
+                .iter()
+                .rev()
+                .filter(|user| user.is_live())
+                .take(live.saturating_sub(target))
+            {
+                user.retire.retire();
+            }
+            while live < target {
+                // 先取得并发空位再启动用户；没有空位时等下一轮，不让用户排队
+                let permit = match &run.concurrency {
+                    Some(semaphore) => match Arc::clone(semaphore).try_acquire_owned() {
+                        Ok(permit) => Some(permit),
+                        Err(_) => break,
+                    },
+                    None => None,
+                };
+                user_id_counter += 1;
+                let user_id = user_id_counter;
+                run_state.add_user(user_id, UserRecord::new(tenant_id.to_string(), None));
+                tasks.push(run.spawn_user(user_id, tenant_id.to_string(), None, 0, permit));
+                live += 1;
+            }
+            sleep(STAGE_INTERVAL).await;
         }
 
+        // 最后一个阶段结束：其余用户完成当前迭代后退役
+        let live = tasks.iter().filter(|user| user.is_live()).count();
+        for user in &tasks {
+            user.retire.retire();
+        }
+        println!("\n✓ Stages complete, retiring {} users", live);
+    } else if let Some(arrival_rate) = &load_config.arrival_rate {
+        // 开放模型：按到达时刻生成用户，不等待之前的用户完成
+        let schedule = ArrivalSchedule::new(arrival_rate)?;
+        let duration = schedule.duration();
         println!(
-            "\n📊 Phase at {}s: Spawning {} users...",
-            phase.at_second, phase.spawn_users
+            "\n⏱️  Starting arrivals: ~{:.0} over {:.0}s...",
+            schedule.expected_arrivals(),
+            duration.as_secs_f64()
         );
+        let tenant_id = arrival_rate
+            .tenant_id
+            .clone()
+            .unwrap_or_else(|| "default-tenant".to_string());
+
+        // 从检查点继续时跳过已经过去的到达时刻
+        let mut arrivals = 0;
+        for at in schedule.skip_while(|at| *at < offset) {
+            let now = elapsed();
+            if now < at {
+                sleep(at - now).await;
+            }
 
-        // 为该阶段创建用户
-        for _ in 0..phase.spawn_users {
             user_id_counter += 1;
             let user_id = user_id_counter;
+            run_state.add_user(user_id, UserRecord::new(tenant_id.clone(), None));
+            tasks.push(run.spawn_user(user_id, tenant_id.clone(), None, 0, None));
+            arrivals += 1;
+        }
+        println!(
+            "\n✓ {} arrivals in {:.0}s",
+            arrivals,
+            duration.saturating_sub(offset).as_secs_f64()
+        );
+    } else {
+        println!("\n⏱️  Starting ramp-up...");
+    }
 
-            // 确定租户 ID
-            let tenant_id = phase
-                .tenant_id
-                .clone()
-                .unwrap_or_else(|| "default-tenant".to_string());
-
-            // 分配 IP（如果启用）
-            let allocated_ip = if load_config.user_resources.ip_binding.enabled {
-                let pool_id = &load_config.user_resources.ip_binding.pool_id;
-                let mut manager = ip_manager.lock().unwrap();
-
-                match manager.allocate_ip(pool_id, &tenant_id, &format!("user-{}", user_id)) {
-                    Ok(ip) => Some(ip),
-                    Err(e) => {
-                        eprintln!("⚠️  Failed to allocate IP for user-{}: {}", user_id, e);
-                        None
-                    }
-                }
-            } else {
-                None
-            };
-
-            // 创建用户上下文
-            let user_ctx = UserContext {
-                id: user_id,
-                tenant_id: tenant_id.clone(),
-                allocated_ip,
-                created_at: Instant::now(),
-            };
+    // 按阶段生成用户（跳过检查点中已启动的阶段）
+    let phases = &load_config.ramp_up.phases;
+    for (index, phase) in phases.iter().enumerate().skip(resume.phases_started) {
+        let target_time = Duration::from_secs(phase.at_second);
+        let now = elapsed();
 
-            // 创建用户执行器
-            let mut executor = UserExecutor::new(
-                user_ctx,
-                pipeline.scenario().workflows.clone(),
-                pipeline.scenario().actions.clone(),
-                iterations,
-                think_time,
-                pipeline.template_context().clone(),
-            );
+        if now < target_time {
+            sleep(target_time - now).await;
+        }
 
-            // 克隆需要的变量
-            let ip_manager_clone = Arc::clone(&ip_manager);
-            let all_traces_clone = Arc::clone(&all_traces);
-            let pool_id = load_config.user_resources.ip_binding.pool_id.clone();
-            let ip_binding_enabled = load_config.user_resources.ip_binding.enabled;
-            let release_on_task_end = matches!(
-                load_config.user_resources.ip_binding.strategy,
-                IpBindingStrategy::PerTask
-            );
+        println!(
+            "\n📊 Phase at {}s: Spawning {} users...",
+            phase.at_second, phase.spawn_users
+        );
 
-            // 启动用户任务
-            let task = tokio::spawn(async move {
-                // 创建 HTTP Action 组件
-                let mut component = HttpActionComponent::new();
-
-                match executor.run(&mut component) {
-                    Ok(traces) => {
-                        println!(
-                            "✓ User-{} completed {} iterations, {} actions",
-                            user_id,
-                            iterations,
-                            traces.len()
-                        );
-
-                        // 保存痕迹
-                        let mut all = all_traces_clone.lock().unwrap();
-                        all.extend(traces);
-                    }
-                    Err(e) => {
-                        eprintln!("✗ User-{} failed: {:#}", user_id, e);
-                    }
-                }
+        // 确定租户 ID
+        let tenant_id = phase
+            .tenant_id
+            .clone()
+            .unwrap_or_else(|| "default-tenant".to_string());
 
-                // 释放 IP（如果需要）
-                if ip_binding_enabled && !release_on_task_end {
-                    if let Some(ip) = allocated_ip {
-                        let mut manager = ip_manager_clone.lock().unwrap();
-                        if let Err(e) = manager.release_ip(&pool_id, ip) {
-                            eprintln!(
-                                "⚠️  Failed to release IP {} for user-{}: {}",
-                                ip, user_id, e
-                            );
-                        }
-                    }
-                }
-            });
+        // 为该阶段创建用户，全部登记后再启动，检查点不会只含半个阶段
+        let mut users = Vec::new();
+        for _ in 0..phase.spawn_users {
+            user_id_counter += 1;
+            users.push((user_id_counter, UserRecord::new(tenant_id.clone(), None)));
+        }
+        run_state.start_phase(index + 1, users.clone());
 
-            tasks.push(task);
+        for (user_id, user) in users {
+            tasks.push(run.spawn_user(user_id, user.tenant_id, None, 0, None));
         }
     }
 
     println!("\n⏳ Waiting for all users to complete...");
 
     // 等待所有用户任务完成
-    for task in tasks {
-        let _ = task.await;
+    for user in tasks {
+        let _ = user.task.await;
+    }
+    if let Some(journal) = run.journal.clone() {
+        tokio::task::spawn_blocking(move || journal.flush())
+            .await?
+            .context("failed to write journal")?;
+    }
+    reporter.abort();
+    if let Some(checkpointer) = checkpointer {
+        checkpointer.abort();
+    }
+    if let Some(path) = &checkpoint.path {
+        run.checkpoint(elapsed()).save(path)?;
+        println!("\n💾 Checkpoint written to {}", path.display());
     }
 
-    let total_duration = start_time.elapsed();
+    let total_duration = elapsed();
 
     println!("\n📈 Load Test Summary");
     println!("═══════════════════════════════════════");
     println!("Total users spawned: {}", user_id_counter);
     println!("Total duration: {:.2}s", total_duration.as_secs_f64());
 
-    let traces = all_traces.lock().unwrap();
-    println!("Total actions executed: {}", traces.len());
+    let metrics = run_state.metrics();
+    println!("Total actions executed: {}", metrics.actions());
 
-    if !traces.is_empty() {
+    if metrics.actions() > 0 {
         // 计算统计信息
-        let total_duration_ms: u64 = traces.iter().map(|t| t.duration_ms).sum();
-        let avg_duration = total_duration_ms as f64 / traces.len() as f64;
+        let total_duration_ms: u64 = metrics.durations_ms.iter().sum();
+        let avg_duration = total_duration_ms as f64 / metrics.actions() as f64;
 
-        let mut durations: Vec<u64> = traces.iter().map(|t| t.duration_ms).collect();
+        let mut durations = metrics.durations_ms.clone();
         durations.sort_unstable();
 
         let p50 = durations[durations.len() / 2];
@@ -259,13 +873,45 @@ async fn run_load_test(
         println!("  P99: {}ms", p99);
         println!("  Min: {}ms", durations[0]);
         println!("  Max: {}ms", durations[durations.len() - 1]);
+
+        // 每个断言的通过/失败次数
+        if !metrics.assertions.is_empty() {
+            println!("\nAssertions:");
+            print!("{}", metrics.assertions);
+        }
+
+        // 各工作流边的实际命中次数
+        println!("\nEdge Hits:");
+        print!("{}", metrics.edge_hits);
+        if let Some(path) = edge_hits_path {
+            std::fs::write(path, metrics.edge_hits.to_json())
+                .with_context(|| format!("failed to write edge hits: {}", path.display()))?;
+            println!("  (written to {})", path.display());
+        }
+    }
+
+    // 因超出迭代上限而中止的迭代
+    if metrics.aborted_iterations() > 0 {
+        println!("\nAborted Iterations: {}", metrics.aborted_iterations());
+        for (reason, count) in &metrics.aborted {
+            println!("  {}: {}", reason, count);
+        }
+    }
+
+    println!("\nProgress:");
+    print!("{}", progress);
+
+    if !shared.is_empty() {
+        println!("\nWorkbook Variables:");
+        for (name, value) in shared.snapshot() {
+            println!("  {} = {}", name, secrets::redact(&value));
+        }
     }
 
     // 显示 IP 池统计
-    if load_config.user_resources.ip_binding.enabled {
-        let pool_id = &load_config.user_resources.ip_binding.pool_id;
-        let manager = ip_manager.lock().unwrap();
-        if let Some(stats) = manager.get_stats(pool_id) {
+    if run.ip_binding_enabled {
+        let manager = run.ip_manager.lock().unwrap();
+        if let Some(stats) = manager.get_stats(&run.pool_id) {
             println!("\nIP Pool Statistics:");
             println!("  {}", stats);
         }
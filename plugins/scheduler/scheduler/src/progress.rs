@@ -0,0 +1,226 @@
+use indexmap::{IndexMap, IndexSet};
+use std::fmt;
+use std::sync::RwLock;
+
+use scheduler_core::wbs::ProgressModel;
+
+/// 运行进度看板
+///
+/// 记录每个用户的当前迭代与本次迭代已完成的动作节点，按 `ProgressModel`
+/// 的权重逐级汇总为迭代进度、用户进度与场景进度。由所有用户共享（以 `Arc` 传递）。
+#[derive(Debug)]
+pub struct ProgressBoard {
+    model: ProgressModel,
+    users: RwLock<IndexMap<usize, UserProgress>>,
+}
+
+/// 单个用户的进度快照
+#[derive(Debug, Clone, Default)]
+pub struct UserProgress {
+    /// 当前迭代（从 0 开始）
+    pub iteration: usize,
+    /// 总迭代次数（0 = 无限循环）
+    pub iterations: usize,
+    /// 当前迭代内的进度（0.0 ~ 1.0）
+    pub iteration_progress: f64,
+    /// 所有迭代均已完成
+    pub finished: bool,
+    completed: IndexSet<String>,
+}
+
+impl UserProgress {
+    /// 用户整体进度；无限循环的用户没有整体进度
+    pub fn overall(&self) -> Option<f64> {
+        if self.finished {
+            return Some(1.0);
+        }
+        if self.iterations == 0 {
+            return None;
+        }
+        Some(((self.iteration as f64 + self.iteration_progress) / self.iterations as f64).min(1.0))
+    }
+
+    /// 本次迭代已完成的动作节点
+    pub fn completed(&self) -> impl Iterator<Item = &str> {
+        self.completed.iter().map(String::as_str)
+    }
+}
+
+/// `iteration 2/3, 60%`
+impl fmt::Display for UserProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.finished {
+            return write!(f, "finished");
+        }
+        let total = if self.iterations == 0 {
+            "∞".to_string()
+        } else {
+            self.iterations.to_string()
+        };
+        write!(
+            f,
+            "iteration {}/{}, {:.0}%",
+            self.iteration + 1,
+            total,
+            self.iteration_progress * 100.0
+        )
+    }
+}
+
+impl ProgressBoard {
+    pub fn new(model: ProgressModel) -> Self {
+        Self {
+            model,
+            users: RwLock::new(IndexMap::new()),
+        }
+    }
+
+    pub fn model(&self) -> &ProgressModel {
+        &self.model
+    }
+
+    /// 用户开始一次迭代，迭代内进度清零
+    pub fn start_iteration(&self, user_id: usize, iteration: usize, iterations: usize) {
+        let mut users = self.users.write().expect("progress board lock poisoned");
+        let user = users.entry(user_id).or_default();
+        user.iteration = iteration;
+        user.iterations = iterations;
+        user.iteration_progress = 0.0;
+        user.completed.clear();
+    }
+
+    /// 记录一个完成的动作节点，返回更新后的迭代进度
+    pub fn complete_task(&self, user_id: usize, task_id: &str) -> f64 {
+        let mut users = self.users.write().expect("progress board lock poisoned");
+        let user = users.entry(user_id).or_default();
+        user.completed.insert(task_id.to_string());
+        user.iteration_progress = self
+            .model
+            .fraction(user.completed.iter().map(String::as_str));
+        user.iteration_progress
+    }
+
+    /// 到达终点：未走到的分支不再计入，迭代记为完成
+    pub fn finish_iteration(&self, user_id: usize) {
+        let mut users = self.users.write().expect("progress board lock poisoned");
+        users.entry(user_id).or_default().iteration_progress = 1.0;
+    }
+
+    /// 用户的所有迭代均已完成
+    pub fn finish_user(&self, user_id: usize) {
+        let mut users = self.users.write().expect("progress board lock poisoned");
+        users.entry(user_id).or_default().finished = true;
+    }
+
+    pub fn user(&self, user_id: usize) -> Option<UserProgress> {
+        let users = self.users.read().expect("progress board lock poisoned");
+        users.get(&user_id).cloned()
+    }
+
+    /// 用户 `user_id` 在分组 `group` 内的进度（本次迭代）
+    pub fn group(&self, user_id: usize, group: &str) -> Option<f64> {
+        let users = self.users.read().expect("progress board lock poisoned");
+        let user = users.get(&user_id)?;
+        self.model.group_fraction(group, user.completed())
+    }
+
+    /// 场景进度：有限迭代用户的整体进度平均值
+    pub fn scenario(&self) -> Option<f64> {
+        let users = self.users.read().expect("progress board lock poisoned");
+        let overall: Vec<f64> = users.values().filter_map(UserProgress::overall).collect();
+        if overall.is_empty() {
+            return None;
+        }
+        Some(overall.iter().sum::<f64>() / overall.len() as f64)
+    }
+
+    /// 已上线的用户数与已完成的用户数
+    pub fn counts(&self) -> (usize, usize) {
+        let users = self.users.read().expect("progress board lock poisoned");
+        let finished = users.values().filter(|user| user.finished).count();
+        (users.len(), finished)
+    }
+}
+
+/// 场景进度一行，之后每个未完成的用户一行
+impl fmt::Display for ProgressBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (total, finished) = self.counts();
+        match self.scenario() {
+            Some(progress) => writeln!(
+                f,
+                "  scenario: {:.1}% ({} users, {} finished)",
+                progress * 100.0,
+                total,
+                finished
+            )?,
+            None => writeln!(
+                f,
+                "  scenario: n/a ({} users, {} finished)",
+                total, finished
+            )?,
+        }
+        let users = self.users.read().expect("progress board lock poisoned");
+        for (user_id, user) in users.iter().filter(|(_, user)| !user.finished) {
+            writeln!(f, "  user {}: {}", user_id, user)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use scheduler_core::dsl::Scenario;
+    use scheduler_core::wbs::WbsTree;
+
+    #[test]
+    fn test_rolls_up_user_and_scenario_progress() {
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: progress
+actions:
+  actions:
+    - id: a
+      call: get
+workflows:
+  nodes:
+    - id: setup
+      type: group
+      weight: 2
+    - id: start
+      type: action
+      action: a
+      parent: setup
+      edges:
+        - to: browse
+    - id: browse
+      type: action
+      action: a
+      weight: 3
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let board = ProgressBoard::new(ProgressModel::from_wbs(&tree));
+
+        board.start_iteration(17, 1, 2);
+        assert_eq!(board.complete_task(17, "start"), 0.4);
+        assert_eq!(board.group(17, "setup"), Some(1.0));
+        assert_eq!(board.user(17).unwrap().to_string(), "iteration 2/2, 40%");
+        let overall = board.user(17).unwrap().overall().unwrap();
+        assert!((overall - 0.7).abs() < 1e-9, "{overall}");
+
+        board.start_iteration(18, 0, 0);
+        board.finish_iteration(17);
+        board.finish_user(17);
+        assert_eq!(board.scenario(), Some(1.0));
+        assert_eq!(board.counts(), (2, 1));
+        assert!(board.to_string().contains("user 18: iteration 1/∞, 0%"));
+    }
+}
@@ -0,0 +1,108 @@
+use anyhow::Result;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::utils::parse_rate;
+
+/// 令牌桶限速器（所有用户共享）
+///
+/// 令牌以 `rate` 个/秒补充，最多积累 `burst` 个。每次 [`RateLimiter::reserve`]
+/// 预订一个令牌并返回需要等待的时间；令牌不足时余额为负，后来者排在更后面，
+/// 按调用顺序放行。
+#[derive(Debug)]
+pub struct RateLimiter {
+    rate: f64,
+    burst: f64,
+    bucket: Mutex<Bucket>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl RateLimiter {
+    /// 桶初始为满
+    pub fn new(rate: f64, burst: f64) -> Self {
+        Self {
+            rate,
+            burst,
+            bucket: Mutex::new(Bucket {
+                tokens: burst,
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// 解析 `100/s` 形式的速率；`burst` 为 `None` 时允许积累一秒的令牌
+    pub fn parse(text: &str, burst: Option<f64>) -> Result<Self> {
+        let rate = parse_rate(text)?;
+        if rate == 0.0 {
+            anyhow::bail!("Rate must be positive: '{}'", text);
+        }
+        Ok(Self::new(rate, burst.unwrap_or(rate.max(1.0))))
+    }
+
+    /// 预订一个令牌，返回放行前需要等待的时间
+    pub fn reserve(&self) -> Duration {
+        self.reserve_at(Instant::now())
+    }
+
+    /// 阻塞当前线程直到放行
+    pub fn acquire(&self) {
+        std::thread::sleep(self.reserve());
+    }
+
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().expect("rate limiter lock poisoned");
+        let refill = now.saturating_duration_since(bucket.updated).as_secs_f64() * self.rate;
+        bucket.tokens = (bucket.tokens + refill).min(self.burst) - 1.0;
+        bucket.updated = bucket.updated.max(now);
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / self.rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_then_steady_rate() {
+        let limiter = RateLimiter::new(10.0, 2.0);
+        let start = Instant::now();
+        let waits: Vec<_> = (0..5).map(|_| limiter.reserve_at(start)).collect();
+        assert_eq!(
+            waits,
+            vec![
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(300),
+            ]
+        );
+
+        // 一秒后桶已补满（最多 `burst` 个）
+        let later = start + Duration::from_secs(1);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(later), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(later), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_parse_defaults_burst_to_one_second() {
+        let limiter = RateLimiter::parse("4/s", None).expect("valid rate");
+        let start = Instant::now();
+        let free = (0..6)
+            .filter(|_| limiter.reserve_at(start).is_zero())
+            .count();
+        assert_eq!(free, 4);
+        assert!(RateLimiter::parse("0/s", None).is_err());
+        assert!(RateLimiter::parse("fast", None).is_err());
+    }
+}
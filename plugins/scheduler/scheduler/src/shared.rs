@@ -0,0 +1,195 @@
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use scheduler_core::dsl::ExportMode;
+use scheduler_core::export::WorkbookWrite;
+
+/// 工作簿级共享变量存储
+///
+/// 保存 `scope: workbook` 导出的值，由所有用户共享（以 `Arc` 传递）。
+/// 写入语义由导出的 `mode` 决定：
+/// - `overwrite`：后写覆盖先写
+/// - `first_writer_wins`：只保留第一次写入，之后的写入被忽略
+/// - `append`：每次写入追加到列表，渲染为 JSON 数组
+///
+/// 每次生效的写入使版本号加一（初始为 0），运行日志据此还原各用户读到的值。
+#[derive(Debug, Default)]
+pub struct SharedStore {
+    values: RwLock<IndexMap<String, SharedValue>>,
+    /// 只在持有 `values` 写锁时递增，持有读锁时读到的版本与值一致
+    version: AtomicU64,
+}
+
+/// 共享变量的值；`append` 写入的是列表
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SharedValue {
+    Scalar(String),
+    List(Vec<String>),
+}
+
+impl SharedValue {
+    fn render(&self) -> String {
+        match self {
+            SharedValue::Scalar(value) => value.clone(),
+            SharedValue::List(items) => {
+                serde_json::to_string(items).expect("string list serializes")
+            }
+        }
+    }
+}
+
+impl SharedStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 由检查点中保存的值恢复
+    pub fn from_values(values: IndexMap<String, SharedValue>) -> Self {
+        Self {
+            values: RwLock::new(values),
+            version: AtomicU64::new(0),
+        }
+    }
+
+    /// 按 `mode` 写入一个值；返回该写入是否生效
+    pub fn write(&self, name: &str, value: String, mode: ExportMode) -> bool {
+        self.write_versioned(name, value, mode).is_some()
+    }
+
+    /// 按 `mode` 写入一个值；生效时返回写入后的版本号
+    fn write_versioned(&self, name: &str, value: String, mode: ExportMode) -> Option<u64> {
+        let mut values = self.values.write().expect("shared store lock poisoned");
+        match mode {
+            ExportMode::Overwrite => {
+                values.insert(name.to_string(), SharedValue::Scalar(value));
+            }
+            ExportMode::FirstWriterWins => {
+                if values.contains_key(name) {
+                    return None;
+                }
+                values.insert(name.to_string(), SharedValue::Scalar(value));
+            }
+            ExportMode::Append => {
+                let entry = values
+                    .entry(name.to_string())
+                    .or_insert_with(|| SharedValue::List(Vec::new()));
+                match entry {
+                    SharedValue::List(items) => items.push(value),
+                    SharedValue::Scalar(previous) => {
+                        *entry = SharedValue::List(vec![std::mem::take(previous), value]);
+                    }
+                }
+            }
+        }
+        Some(self.version.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+
+    /// 写入一次动作产生的所有工作簿级导出，返回生效的写入及其版本号
+    pub fn apply<'a>(&self, writes: &'a [WorkbookWrite]) -> Vec<(u64, &'a WorkbookWrite)> {
+        writes
+            .iter()
+            .filter_map(|write| {
+                self.write_versioned(&write.name, write.value.clone(), write.mode)
+                    .map(|version| (version, write))
+            })
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<String> {
+        let values = self.values.read().expect("shared store lock poisoned");
+        values.get(name).map(SharedValue::render)
+    }
+
+    /// 当前所有值的快照，用于合并进模板上下文
+    pub fn snapshot(&self) -> IndexMap<String, String> {
+        let values = self.values.read().expect("shared store lock poisoned");
+        values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.render()))
+            .collect()
+    }
+
+    /// 当前所有值的快照及其版本号
+    pub fn versioned_snapshot(&self) -> (u64, IndexMap<String, String>) {
+        let values = self.values.read().expect("shared store lock poisoned");
+        let snapshot = values
+            .iter()
+            .map(|(name, value)| (name.clone(), value.render()))
+            .collect();
+        (self.version.load(Ordering::SeqCst), snapshot)
+    }
+
+    /// 当前所有值（保留列表结构），用于写入检查点
+    pub fn values(&self) -> IndexMap<String, SharedValue> {
+        self.values
+            .read()
+            .expect("shared store lock poisoned")
+            .clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values
+            .read()
+            .expect("shared store lock poisoned")
+            .is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_write_modes() {
+        let store = SharedStore::new();
+
+        assert!(store.write("auth.token", "a".into(), ExportMode::FirstWriterWins));
+        assert!(!store.write("auth.token", "b".into(), ExportMode::FirstWriterWins));
+        assert_eq!(store.get("auth.token").as_deref(), Some("a"));
+        // 被忽略的写入不改变版本号
+        assert_eq!(store.versioned_snapshot().0, 1);
+
+        store.write("probe.port", "8080".into(), ExportMode::Overwrite);
+        store.write("probe.port", "9090".into(), ExportMode::Overwrite);
+        assert_eq!(store.get("probe.port").as_deref(), Some("9090"));
+
+        store.write("order.id", "1".into(), ExportMode::Append);
+        store.write("order.id", "2".into(), ExportMode::Append);
+        assert_eq!(store.get("order.id").as_deref(), Some(r#"["1","2"]"#));
+
+        // 追加到已有标量时，标量成为列表的第一个元素
+        store.write("probe.port", "7070".into(), ExportMode::Append);
+        assert_eq!(
+            store.get("probe.port").as_deref(),
+            Some(r#"["9090","7070"]"#)
+        );
+    }
+
+    #[test]
+    fn test_concurrent_writers() {
+        let store = Arc::new(SharedStore::new());
+        let handles: Vec<_> = (0..8)
+            .map(|i| {
+                let store = Arc::clone(&store);
+                std::thread::spawn(move || {
+                    store.write("order.id", i.to_string(), ExportMode::Append);
+                    store.write("auth.token", i.to_string(), ExportMode::FirstWriterWins)
+                })
+            })
+            .collect();
+        let winners = handles
+            .into_iter()
+            .map(|handle| handle.join().expect("writer thread"))
+            .filter(|won| *won)
+            .count();
+
+        assert_eq!(winners, 1);
+        let ids: Vec<String> =
+            serde_json::from_str(&store.get("order.id").expect("appended")).expect("json list");
+        assert_eq!(ids.len(), 8);
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+use crate::utils::parse_duration;
+use scheduler_core::dsl::StageDef;
+
+/// 按阶段变化的目标用户数
+///
+/// 每个阶段在 `duration` 内把目标从上一阶段的 `target_users`（第一个阶段
+/// 之前为 0）线性调整到本阶段的值；目标不变即为稳态保持，目标下降即为
+/// ramp-down。
+#[derive(Debug, Clone)]
+pub struct StagePlan {
+    stages: Vec<Stage>,
+}
+
+#[derive(Debug, Clone)]
+struct Stage {
+    /// 本阶段开始的时间
+    start: Duration,
+    duration: Duration,
+    from_users: usize,
+    target_users: usize,
+    tenant_id: String,
+}
+
+impl StagePlan {
+    pub fn new(stages: &[StageDef]) -> Result<Self> {
+        let mut plan = Vec::with_capacity(stages.len());
+        let (mut start, mut from_users) = (Duration::ZERO, 0);
+        for (idx, stage) in stages.iter().enumerate() {
+            let duration = parse_duration(&stage.duration)
+                .with_context(|| format!("invalid load.stages[{}].duration", idx))?;
+            plan.push(Stage {
+                start,
+                duration,
+                from_users,
+                target_users: stage.target_users,
+                tenant_id: stage
+                    .tenant_id
+                    .clone()
+                    .unwrap_or_else(|| "default-tenant".to_string()),
+            });
+            start += duration;
+            from_users = stage.target_users;
+        }
+        Ok(Self { stages: plan })
+    }
+
+    /// 所有阶段的总时长
+    pub fn duration(&self) -> Duration {
+        self.stages
+            .last()
+            .map(|stage| stage.start + stage.duration)
+            .unwrap_or_default()
+    }
+
+    /// `elapsed` 时刻的目标用户数与新用户的租户；所有阶段结束后返回 `None`
+    pub fn target_at(&self, elapsed: Duration) -> Option<(usize, &str)> {
+        let stage = self
+            .stages
+            .iter()
+            .find(|stage| elapsed < stage.start + stage.duration)?;
+        let progress =
+            (elapsed.saturating_sub(stage.start)).as_secs_f64() / stage.duration.as_secs_f64();
+        let from = stage.from_users as f64;
+        let target = from + (stage.target_users as f64 - from) * progress;
+        Some((target.round() as usize, stage.tenant_id.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage(target_users: usize, duration: &str) -> StageDef {
+        StageDef {
+            target_users,
+            duration: duration.to_string(),
+            tenant_id: None,
+        }
+    }
+
+    #[test]
+    fn test_targets_ramp_hold_and_ramp_down() {
+        let plan = StagePlan::new(&[stage(10, "10s"), stage(10, "20s"), stage(0, "5s")])
+            .expect("valid stages");
+        let at = |secs: f64| {
+            plan.target_at(Duration::from_secs_f64(secs))
+                .map(|(users, _)| users)
+        };
+
+        assert_eq!(plan.duration(), Duration::from_secs(35));
+        assert_eq!(at(0.0), Some(0));
+        assert_eq!(at(5.0), Some(5));
+        assert_eq!(at(10.0), Some(10));
+        assert_eq!(at(29.9), Some(10));
+        assert_eq!(at(32.5), Some(5));
+        assert_eq!(at(35.0), None);
+    }
+
+    #[test]
+    fn test_zero_duration_stage_jumps() {
+        let plan = StagePlan::new(&[
+            StageDef {
+                tenant_id: Some("tenant-b".into()),
+                ..stage(4, "0s")
+            },
+            stage(4, "1m"),
+        ])
+        .expect("valid stages");
+        // 零时长阶段只改变下一阶段的起点，不会在其中启动用户
+        assert_eq!(plan.target_at(Duration::ZERO), Some((4, "default-tenant")));
+        assert!(StagePlan::new(&[stage(1, "soon")]).is_err());
+    }
+}
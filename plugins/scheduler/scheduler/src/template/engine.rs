@@ -0,0 +1,454 @@
+use indexmap::IndexMap;
+use scheduler_core::error::SchedulerError;
+use serde_json::Value as JsonValue;
+use serde_yaml::Value;
+
+use super::functions::Function;
+
+/// 解析后的模板
+///
+/// 模板只在编译时解析一次，之后可以针对不同的变量集合反复渲染。
+/// 占位符语法：
+/// - 变量：`{{user.id}}`、`{{ probe-get.token }}`
+/// - 函数：`{{uuid()}}`、`{{random_int(1, 100)}}`、`{{base64(user.name)}}`
+/// - 过滤器：`{{user.name | urlencode}}`、`{{login.token | default("none")}}`
+/// - 字面量：`"text"`、`'text'`、数字
+#[derive(Debug, Clone)]
+pub struct Template {
+    source: String,
+    segments: Vec<Segment>,
+}
+
+#[derive(Debug, Clone)]
+enum Segment {
+    Text(String),
+    Placeholder { raw: String, expr: Expr },
+}
+
+#[derive(Debug, Clone)]
+pub(super) enum Expr {
+    Literal(String),
+    Var(String),
+    Call { function: Function, args: Vec<Expr> },
+}
+
+/// 表达式求值失败的原因
+pub(super) enum EvalError {
+    /// 变量未定义
+    Unresolved(String),
+    /// 函数参数非法等
+    Failed(String),
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, SchedulerError> {
+        let invalid = |message: String| SchedulerError::InvalidTemplate {
+            template: source.to_string(),
+            message,
+        };
+
+        let mut segments = Vec::new();
+        let mut rest = source;
+        while let Some(start) = rest.find("{{") {
+            if start > 0 {
+                segments.push(Segment::Text(rest[..start].to_string()));
+            }
+            let after_open = &rest[start + 2..];
+            let end = after_open
+                .find("}}")
+                .ok_or_else(|| invalid("unclosed `{{`".to_string()))?;
+            let inner = &after_open[..end];
+            let expr = Parser::parse(inner).map_err(invalid)?;
+            segments.push(Segment::Placeholder {
+                raw: rest[start..start + 2 + end + 2].to_string(),
+                expr,
+            });
+            rest = &after_open[end + 2..];
+        }
+        if !rest.is_empty() {
+            segments.push(Segment::Text(rest.to_string()));
+        }
+
+        Ok(Self {
+            source: source.to_string(),
+            segments,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// 不含占位符的模板，渲染结果恒等于源文本
+    pub fn is_literal(&self) -> bool {
+        self.segments
+            .iter()
+            .all(|segment| matches!(segment, Segment::Text(_)))
+    }
+
+    /// 渲染模板
+    ///
+    /// `strict` 为 true 时未定义的变量是错误；否则保留原占位符文本。
+    pub fn render(
+        &self,
+        vars: &IndexMap<String, String>,
+        strict: bool,
+    ) -> Result<String, SchedulerError> {
+        let mut output = String::with_capacity(self.source.len());
+        for segment in &self.segments {
+            match segment {
+                Segment::Text(text) => output.push_str(text),
+                Segment::Placeholder { raw, expr } => match expr.eval(vars) {
+                    Ok(value) => output.push_str(&value),
+                    Err(EvalError::Unresolved(name)) if strict => {
+                        return Err(SchedulerError::UnresolvedVariable {
+                            name,
+                            template: self.source.clone(),
+                        });
+                    }
+                    Err(EvalError::Unresolved(_)) => output.push_str(raw),
+                    Err(EvalError::Failed(message)) => {
+                        return Err(SchedulerError::InvalidTemplate {
+                            template: self.source.clone(),
+                            message,
+                        });
+                    }
+                },
+            }
+        }
+        Ok(output)
+    }
+
+    /// 渲染为 YAML 值
+    ///
+    /// 模板恰好是单个占位符时保留值的类型：数字、布尔值以及 JSON 对象/数组
+    /// （如 jsonpath 导出的结构）分别还原为对应的 YAML 值；其余情况渲染为字符串。
+    pub fn render_value(
+        &self,
+        vars: &IndexMap<String, String>,
+        strict: bool,
+    ) -> Result<Value, SchedulerError> {
+        match self.segments.as_slice() {
+            [Segment::Placeholder { expr, .. }] => match expr.eval(vars) {
+                Ok(value) => Ok(typed_value(value)),
+                // 未解析 / 求值失败的情况与字符串渲染一致
+                Err(_) => self.render(vars, strict).map(Value::String),
+            },
+            _ => self.render(vars, strict).map(Value::String),
+        }
+    }
+}
+
+/// 将占位符的字符串结果还原为带类型的值；无法无损还原时保持字符串
+fn typed_value(raw: String) -> Value {
+    let typed = match serde_json::from_str::<JsonValue>(raw.trim()) {
+        // 数字需能原样往返，避免大整数被解析为有损的浮点数
+        Ok(JsonValue::Number(n)) if n.to_string() == raw.trim() => Some(JsonValue::Number(n)),
+        Ok(value @ (JsonValue::Bool(_) | JsonValue::Object(_) | JsonValue::Array(_))) => {
+            Some(value)
+        }
+        _ => None,
+    };
+    typed
+        .and_then(|value| serde_yaml::to_value(value).ok())
+        .unwrap_or(Value::String(raw))
+}
+
+impl Expr {
+    pub(super) fn eval(&self, vars: &IndexMap<String, String>) -> Result<String, EvalError> {
+        match self {
+            Expr::Literal(value) => Ok(value.clone()),
+            Expr::Var(name) => vars
+                .get(name)
+                .cloned()
+                .ok_or_else(|| EvalError::Unresolved(name.clone())),
+            Expr::Call { function, args } => function.call(args, vars),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Path(String),
+    Str(String),
+    Number(String),
+    LParen,
+    RParen,
+    Comma,
+    Pipe,
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    /// 解析 `{{` 与 `}}` 之间的表达式
+    fn parse(input: &str) -> Result<Expr, String> {
+        let mut parser = Self {
+            tokens: tokenize(input)?,
+            pos: 0,
+        };
+        parser.parse_expr()
+    }
+
+    fn parse_expr(&mut self) -> Result<Expr, String> {
+        if self.tokens.is_empty() {
+            return Err("empty placeholder".to_string());
+        }
+        let expr = self.parse_pipeline()?;
+        match self.tokens.get(self.pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?}", token)),
+        }
+    }
+
+    /// `primary ('|' filter)*`，过滤器以左侧的值为第一个参数
+    fn parse_pipeline(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_primary()?;
+        while self.eat(&Token::Pipe) {
+            let Some(Token::Path(name)) = self.next() else {
+                return Err("expected a filter name after `|`".to_string());
+            };
+            let mut args = vec![expr];
+            if self.eat(&Token::LParen) {
+                args.extend(self.parse_args()?);
+            }
+            expr = call(&name, args)?;
+        }
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Str(value)) | Some(Token::Number(value)) => Ok(Expr::Literal(value)),
+            Some(Token::Path(name)) => {
+                if self.eat(&Token::LParen) {
+                    let args = self.parse_args()?;
+                    call(&name, args)
+                } else {
+                    Ok(Expr::Var(name))
+                }
+            }
+            Some(token) => Err(format!("unexpected {:?}", token)),
+            None => Err("unexpected end of placeholder".to_string()),
+        }
+    }
+
+    /// 参数列表，左括号已被消费
+    fn parse_args(&mut self) -> Result<Vec<Expr>, String> {
+        let mut args = Vec::new();
+        if self.eat(&Token::RParen) {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.parse_pipeline()?);
+            if self.eat(&Token::RParen) {
+                return Ok(args);
+            }
+            if !self.eat(&Token::Comma) {
+                return Err("expected `,` or `)` in argument list".to_string());
+            }
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+fn call(name: &str, args: Vec<Expr>) -> Result<Expr, String> {
+    let function = Function::parse(name).ok_or_else(|| format!("unknown function `{}`", name))?;
+    function.check_arity(args.len())?;
+    Ok(Expr::Call { function, args })
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        match c {
+            c if c.is_whitespace() => i += 1,
+            '(' | ')' | ',' | '|' => {
+                tokens.push(match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    ',' => Token::Comma,
+                    _ => Token::Pipe,
+                });
+                i += 1;
+            }
+            '"' | '\'' => {
+                let quote = c;
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err("unterminated string literal".to_string()),
+                        Some(&ch) if ch == quote => break,
+                        Some('\\') => {
+                            i += 1;
+                            match chars.get(i) {
+                                Some(&escaped) => value.push(escaped),
+                                None => return Err("unterminated string literal".to_string()),
+                            }
+                        }
+                        Some(&ch) => value.push(ch),
+                    }
+                    i += 1;
+                }
+                tokens.push(Token::Str(value));
+                i += 1;
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push(Token::Number(chars[start..i].iter().collect()));
+            }
+            c if is_path_char(c) => {
+                let start = i;
+                while i < chars.len() && is_path_char(chars[i]) {
+                    i += 1;
+                }
+                tokens.push(Token::Path(chars[start..i].iter().collect()));
+            }
+            other => return Err(format!("unexpected character `{}`", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+/// 变量路径允许 `.`、`-`、`_`，如 `probe-get.status_code`
+fn is_path_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '.' | '-' | '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> IndexMap<String, String> {
+        IndexMap::from([
+            ("user.id".to_string(), "7".to_string()),
+            ("probe-get.ip".to_string(), "10.0.0.1".to_string()),
+            ("empty".to_string(), String::new()),
+        ])
+    }
+
+    fn render(source: &str, strict: bool) -> Result<String, SchedulerError> {
+        Template::parse(source)?.render(&vars(), strict)
+    }
+
+    #[test]
+    fn test_renders_variables_and_literals() {
+        assert_eq!(
+            render("http://{{probe-get.ip}}:{{ 8080 }}/u/{{ user.id }}", true).unwrap(),
+            "http://10.0.0.1:8080/u/7"
+        );
+        assert_eq!(render("no placeholders", true).unwrap(), "no placeholders");
+        assert!(Template::parse("plain").unwrap().is_literal());
+    }
+
+    #[test]
+    fn test_unresolved_variables() {
+        assert_eq!(
+            render("a={{missing.var}} b={{user.id}}", false).unwrap(),
+            "a={{missing.var}} b=7"
+        );
+        let err = render("a={{missing.var}}", true).unwrap_err();
+        assert!(
+            matches!(err, SchedulerError::UnresolvedVariable { ref name, .. } if name == "missing.var"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_filters_and_default() {
+        assert_eq!(
+            render(r#"{{ missing | default("anon") }}"#, true).unwrap(),
+            "anon"
+        );
+        assert_eq!(render("{{ empty | default(0) }}", true).unwrap(), "0");
+        assert_eq!(render("{{ user.id | default(0) }}", true).unwrap(), "7");
+        assert_eq!(
+            render("{{ 'a b&c' | urlencode }}", true).unwrap(),
+            "a%20b%26c"
+        );
+        assert_eq!(
+            render("{{ base64('user:pw') }}", true).unwrap(),
+            "dXNlcjpwdw=="
+        );
+        assert_eq!(
+            render(r#"{{ 'say "hi"' | json_escape }}"#, true).unwrap(),
+            r#"say \"hi\""#
+        );
+    }
+
+    #[test]
+    fn test_single_placeholder_keeps_type() {
+        let vars = IndexMap::from([
+            ("port".to_string(), "8080".to_string()),
+            ("ok".to_string(), "true".to_string()),
+            ("zip".to_string(), "01234".to_string()),
+            ("big".to_string(), "123456789012345678901234".to_string()),
+            ("data".to_string(), r#"{"items":[1,2]}"#.to_string()),
+        ]);
+        let value = |source: &str| {
+            Template::parse(source)
+                .unwrap()
+                .render_value(&vars, false)
+                .unwrap()
+        };
+
+        assert_eq!(value("{{ port }}"), Value::from(8080));
+        assert_eq!(value("{{ok}}"), Value::Bool(true));
+        assert_eq!(value("{{ zip }}"), Value::from("01234"));
+        assert_eq!(value("{{ big }}"), Value::from("123456789012345678901234"));
+        assert_eq!(value("{{ port }}/x"), Value::from("8080/x"));
+        assert_eq!(value("{{ missing }}"), Value::from("{{ missing }}"));
+        assert_eq!(
+            value("{{ data }}"),
+            serde_yaml::from_str::<Value>("items: [1, 2]").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for source in [
+            "{{ user.id",
+            "{{ }}",
+            "{{ nope() }}",
+            "{{ random_int(1) }}",
+            "{{ user.id | }}",
+            "{{ 'open }}",
+            "{{ a b }}",
+        ] {
+            assert!(
+                matches!(
+                    Template::parse(source),
+                    Err(SchedulerError::InvalidTemplate { .. })
+                ),
+                "{source}"
+            );
+        }
+    }
+}
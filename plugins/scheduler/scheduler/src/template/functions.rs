@@ -0,0 +1,210 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::Engine as _;
+use indexmap::IndexMap;
+
+use super::engine::{EvalError, Expr};
+
+/// 模板内置函数
+///
+/// 既可以作为函数调用（`{{base64(user.name)}}`），也可以作为过滤器
+/// （`{{user.name | base64}}`），过滤器左侧的值即第一个参数。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum Function {
+    Uuid,
+    RandomInt,
+    NowMs,
+    RandomString,
+    Base64,
+    UrlEncode,
+    JsonEscape,
+    Default,
+}
+
+impl Function {
+    pub(super) fn parse(name: &str) -> Option<Self> {
+        let function = match name {
+            "uuid" => Function::Uuid,
+            "random_int" => Function::RandomInt,
+            "now_ms" => Function::NowMs,
+            "random_string" => Function::RandomString,
+            "base64" => Function::Base64,
+            "urlencode" => Function::UrlEncode,
+            "json_escape" => Function::JsonEscape,
+            "default" => Function::Default,
+            _ => return None,
+        };
+        Some(function)
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Function::Uuid => "uuid",
+            Function::RandomInt => "random_int",
+            Function::NowMs => "now_ms",
+            Function::RandomString => "random_string",
+            Function::Base64 => "base64",
+            Function::UrlEncode => "urlencode",
+            Function::JsonEscape => "json_escape",
+            Function::Default => "default",
+        }
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Function::Uuid | Function::NowMs => 0,
+            Function::RandomString
+            | Function::Base64
+            | Function::UrlEncode
+            | Function::JsonEscape => 1,
+            Function::RandomInt | Function::Default => 2,
+        }
+    }
+
+    pub(super) fn check_arity(self, count: usize) -> Result<(), String> {
+        if count == self.arity() {
+            Ok(())
+        } else {
+            Err(format!(
+                "`{}` takes {} argument(s), got {}",
+                self.name(),
+                self.arity(),
+                count
+            ))
+        }
+    }
+
+    pub(super) fn call(
+        self,
+        args: &[Expr],
+        vars: &IndexMap<String, String>,
+    ) -> Result<String, EvalError> {
+        if self == Function::Default {
+            // 未定义或为空时使用回退值
+            return match args[0].eval(vars) {
+                Ok(value) if !value.is_empty() => Ok(value),
+                Ok(_) | Err(EvalError::Unresolved(_)) => args[1].eval(vars),
+                Err(err) => Err(err),
+            };
+        }
+
+        let values = args
+            .iter()
+            .map(|arg| arg.eval(vars))
+            .collect::<Result<Vec<_>, _>>()?;
+        match self {
+            Function::Uuid => Ok(uuid_v4()),
+            Function::NowMs => Ok(now_ms().to_string()),
+            Function::RandomInt => {
+                let low = self.integer(&values[0])?;
+                let high = self.integer(&values[1])?;
+                if low > high {
+                    return Err(EvalError::Failed(format!(
+                        "random_int: {} is greater than {}",
+                        low, high
+                    )));
+                }
+                Ok(fastrand::i64(low..=high).to_string())
+            }
+            Function::RandomString => {
+                let len = self.integer(&values[0])?;
+                let len = usize::try_from(len).map_err(|_| {
+                    EvalError::Failed(format!("random_string: invalid length {}", len))
+                })?;
+                Ok((0..len).map(|_| fastrand::alphanumeric()).collect())
+            }
+            Function::Base64 => Ok(base64::engine::general_purpose::STANDARD.encode(&values[0])),
+            Function::UrlEncode => Ok(url_encode(&values[0])),
+            Function::JsonEscape => {
+                let quoted = serde_json::to_string(&values[0]).expect("string serializes");
+                Ok(quoted[1..quoted.len() - 1].to_string())
+            }
+            Function::Default => unreachable!("handled above"),
+        }
+    }
+
+    fn integer(self, value: &str) -> Result<i64, EvalError> {
+        value.trim().parse().map_err(|_| {
+            EvalError::Failed(format!("{}: `{}` is not an integer", self.name(), value))
+        })
+    }
+}
+
+fn uuid_v4() -> String {
+    // 版本号 4，变体 10xx
+    let mut bits = fastrand::u128(..);
+    bits = (bits & !(0xf_u128 << 76)) | (0x4_u128 << 76);
+    bits = (bits & !(0x3_u128 << 62)) | (0x2_u128 << 62);
+    let hex = format!("{:032x}", bits);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+fn now_ms() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default()
+}
+
+/// 百分号编码，仅保留 RFC 3986 的非保留字符
+fn url_encode(input: &str) -> String {
+    let mut encoded = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::engine::Template;
+    use super::*;
+
+    fn render(source: &str) -> String {
+        Template::parse(source)
+            .expect("parse")
+            .render(&IndexMap::new(), true)
+            .expect("render")
+    }
+
+    #[test]
+    fn test_generators() {
+        let uuid = render("{{ uuid() }}");
+        assert_eq!(uuid.len(), 36);
+        assert_eq!(&uuid[14..15], "4");
+        assert!(matches!(&uuid[19..20], "8" | "9" | "a" | "b"), "{uuid}");
+        assert_ne!(uuid, render("{{ uuid() }}"));
+
+        for _ in 0..50 {
+            let n: i64 = render("{{ random_int(-2, 3) }}").parse().unwrap();
+            assert!((-2..=3).contains(&n));
+        }
+        let s = render("{{ random_string(12) }}");
+        assert_eq!(s.len(), 12);
+        assert!(s.chars().all(|c| c.is_ascii_alphanumeric()));
+        assert!(render("{{ now_ms() }}").parse::<u128>().unwrap() > 0);
+    }
+
+    #[test]
+    fn test_invalid_arguments_fail() {
+        for source in ["{{ random_int(5, 1) }}", "{{ random_string('x') }}"] {
+            let err = Template::parse(source)
+                .unwrap()
+                .render(&IndexMap::new(), false)
+                .unwrap_err();
+            assert!(err.to_string().contains("invalid template"), "{err}");
+        }
+    }
+}
@@ -0,0 +1,256 @@
+mod engine;
+mod functions;
+
+use indexmap::IndexMap;
+use scheduler_core::{
+    condition::{ConditionValue, VariableResolver},
+    dsl::ActionDef,
+    error::SchedulerError,
+    secrets::Secrets,
+    variables::Variables,
+    workbook::Workbook,
+};
+use serde_yaml::{Mapping, Value};
+
+pub use engine::Template;
+
+/// Stores template key/value pairs and renders YAML values by evaluating
+/// their `{{ ... }}` placeholders (variables, built-in functions, filters).
+///
+/// In strict mode an unresolved variable is an error; otherwise the
+/// placeholder is left in place.
+#[derive(Debug, Clone, Default)]
+pub struct TemplateContext {
+    vars: IndexMap<String, String>,
+    strict: bool,
+}
+
+impl TemplateContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn from_workbook(workbook: &Workbook) -> Self {
+        Self::from_workbook_with_variables(workbook, &Variables::default())
+    }
+
+    /// Scenario variables are available as `{{name}}`; resource properties
+    /// are rendered against them. A variable named like a resource property
+    /// (e.g. `resource.ip`) takes precedence over the property.
+    pub fn from_workbook_with_variables(workbook: &Workbook, variables: &Variables) -> Self {
+        Self::from_workbook_with_secrets(workbook, variables, &Secrets::default())
+    }
+
+    /// Like [`Self::from_workbook_with_variables`], with the resolved
+    /// `secrets:` also available as `{{name}}`.
+    pub fn from_workbook_with_secrets(
+        workbook: &Workbook,
+        variables: &Variables,
+        secrets: &Secrets,
+    ) -> Self {
+        let mut ctx = TemplateContext::new();
+        ctx.extend(&variables.values());
+        ctx.extend(&secrets.values());
+        for (resource_id, resource) in &workbook.resources {
+            for (prop, value) in &resource.spec.properties {
+                let key = format!("{}.{}", resource_id, prop);
+                if ctx.vars.contains_key(&key) {
+                    continue;
+                }
+                if let Some(raw) = value_to_string(value) {
+                    // 资源属性中无法解析的占位符原样保留
+                    let rendered = Template::parse(&raw)
+                        .and_then(|template| template.render(&ctx.vars, false))
+                        .unwrap_or(raw);
+                    ctx.vars.insert(key, rendered);
+                }
+            }
+        }
+        ctx
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.vars.insert(key.into(), value.into());
+    }
+
+    pub fn extend(&mut self, data: &IndexMap<String, String>) {
+        for (k, v) in data {
+            self.vars.insert(k.clone(), v.clone());
+        }
+    }
+
+    pub fn merged(&self, overrides: &IndexMap<String, String>) -> Self {
+        let mut merged = self.clone();
+        merged.extend(overrides);
+        merged
+    }
+
+    pub fn vars(&self) -> &IndexMap<String, String> {
+        &self.vars
+    }
+
+    pub fn render_action(&self, action: &ActionDef) -> Result<ActionDef, SchedulerError> {
+        ActionTemplate::compile(action)?.render(self)
+    }
+
+    pub fn render_value(&self, value: &Value) -> Result<Value, SchedulerError> {
+        ValueTemplate::compile(value)?.render(self)
+    }
+
+    pub fn render_str(&self, input: &str) -> Result<String, SchedulerError> {
+        Template::parse(input)?.render(&self.vars, self.strict)
+    }
+}
+
+/// An action whose `with` parameters have been parsed once and can be
+/// rendered repeatedly against different contexts.
+#[derive(Debug, Clone)]
+pub struct ActionTemplate {
+    action: ActionDef,
+    with: IndexMap<String, ValueTemplate>,
+}
+
+impl ActionTemplate {
+    pub fn compile(action: &ActionDef) -> Result<Self, SchedulerError> {
+        let with = action
+            .with
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), ValueTemplate::compile(value)?)))
+            .collect::<Result<_, SchedulerError>>()?;
+        Ok(Self {
+            action: action.clone(),
+            with,
+        })
+    }
+
+    pub fn render(&self, ctx: &TemplateContext) -> Result<ActionDef, SchedulerError> {
+        let mut rendered = self.action.clone();
+        for (key, template) in &self.with {
+            rendered.with.insert(key.clone(), template.render(ctx)?);
+        }
+        Ok(rendered)
+    }
+}
+
+/// A YAML value with its string scalars (and mapping keys) parsed.
+#[derive(Debug, Clone)]
+enum ValueTemplate {
+    Str(Template),
+    Seq(Vec<ValueTemplate>),
+    Map(Vec<(ValueTemplate, ValueTemplate)>),
+    Other(Value),
+}
+
+impl ValueTemplate {
+    fn compile(value: &Value) -> Result<Self, SchedulerError> {
+        Ok(match value {
+            Value::String(raw) => ValueTemplate::Str(Template::parse(raw)?),
+            Value::Sequence(seq) => {
+                ValueTemplate::Seq(seq.iter().map(Self::compile).collect::<Result<_, _>>()?)
+            }
+            Value::Mapping(map) => ValueTemplate::Map(
+                map.iter()
+                    .map(|(k, v)| Ok((Self::compile(k)?, Self::compile(v)?)))
+                    .collect::<Result<_, SchedulerError>>()?,
+            ),
+            other => ValueTemplate::Other(other.clone()),
+        })
+    }
+
+    fn render(&self, ctx: &TemplateContext) -> Result<Value, SchedulerError> {
+        Ok(match self {
+            ValueTemplate::Str(template) => template.render_value(&ctx.vars, ctx.strict)?,
+            ValueTemplate::Seq(items) => Value::Sequence(
+                items
+                    .iter()
+                    .map(|item| item.render(ctx))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ValueTemplate::Map(entries) => {
+                let mut rendered = Mapping::new();
+                for (k, v) in entries {
+                    rendered.insert(k.render_key(ctx)?, v.render(ctx)?);
+                }
+                Value::Mapping(rendered)
+            }
+            ValueTemplate::Other(value) => value.clone(),
+        })
+    }
+
+    /// Mapping keys always render as strings.
+    fn render_key(&self, ctx: &TemplateContext) -> Result<Value, SchedulerError> {
+        match self {
+            ValueTemplate::Str(template) => {
+                template.render(&ctx.vars, ctx.strict).map(Value::String)
+            }
+            other => other.render(ctx),
+        }
+    }
+}
+
+impl VariableResolver for TemplateContext {
+    fn resolve(&self, name: &str) -> Option<ConditionValue> {
+        self.vars.resolve(name)
+    }
+}
+
+fn value_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) => Some(s.clone()),
+        Value::Sequence(_) | Value::Mapping(_) | Value::Tagged(_) => serde_yaml::to_string(value)
+            .ok()
+            .map(|s| s.trim().to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_action_preserves_types() {
+        let action: ActionDef = serde_yaml::from_str(
+            r#"
+id: submit
+call: post
+with:
+  port: "{{ target_port }}"
+  url: "http://host:{{ target_port }}/api"
+  body:
+    user: "{{ user.id }}"
+    data: "{{ fetch-data.data }}"
+"#,
+        )
+        .unwrap();
+        let mut ctx = TemplateContext::new();
+        ctx.insert("target_port", "8080");
+        ctx.insert("user.id", "u-1");
+        ctx.insert("fetch-data.data", r#"[{"id":1}]"#);
+
+        let rendered = ctx.render_action(&action).unwrap();
+        assert_eq!(rendered.with["port"], Value::from(8080));
+        assert_eq!(rendered.with["url"], Value::from("http://host:8080/api"));
+        let body: serde_json::Value =
+            serde_yaml::from_value(rendered.with["body"].clone()).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"user": "u-1", "data": [{"id": 1}]})
+        );
+    }
+}
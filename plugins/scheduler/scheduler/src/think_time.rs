@@ -0,0 +1,146 @@
+use anyhow::{Context, Result};
+use std::time::Duration;
+
+use crate::utils::parse_duration;
+use scheduler_core::dsl::{ThinkTimeDef, ThinkTimeDistribution};
+
+/// 思考时间：固定值或按分布随机抽取
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ThinkTime {
+    #[default]
+    None,
+    Fixed(Duration),
+    Uniform {
+        min: Duration,
+        max: Duration,
+    },
+    Normal {
+        mean: Duration,
+        stddev: Duration,
+    },
+    Exponential {
+        mean: Duration,
+    },
+}
+
+impl ThinkTime {
+    /// 解析 `2s`、`1s..3s` 或 `distribution:` 形式的定义
+    pub fn parse(def: &ThinkTimeDef) -> Result<Self> {
+        let think_time = match def {
+            ThinkTimeDef::Duration(text) => match text.split_once("..") {
+                Some((min, max)) => Self::uniform(parse_duration(min)?, parse_duration(max)?)?,
+                None => Self::from(parse_duration(text)?),
+            },
+            ThinkTimeDef::Distribution(ThinkTimeDistribution::Uniform { min, max }) => {
+                Self::uniform(
+                    parse_duration(min).context("invalid uniform min")?,
+                    parse_duration(max).context("invalid uniform max")?,
+                )?
+            }
+            ThinkTimeDef::Distribution(ThinkTimeDistribution::Normal { mean, stddev }) => {
+                Self::Normal {
+                    mean: parse_duration(mean).context("invalid normal mean")?,
+                    stddev: parse_duration(stddev).context("invalid normal stddev")?,
+                }
+            }
+            ThinkTimeDef::Distribution(ThinkTimeDistribution::Exponential { mean }) => {
+                Self::Exponential {
+                    mean: parse_duration(mean).context("invalid exponential mean")?,
+                }
+            }
+        };
+        Ok(think_time)
+    }
+
+    fn uniform(min: Duration, max: Duration) -> Result<Self> {
+        if min > max {
+            anyhow::bail!("Think time range is empty: {:?}..{:?}", min, max);
+        }
+        Ok(Self::Uniform { min, max })
+    }
+
+    /// 抽取一次思考时间
+    pub fn sample(&self, rng: &mut fastrand::Rng) -> Duration {
+        match *self {
+            Self::None => Duration::ZERO,
+            Self::Fixed(duration) => duration,
+            Self::Uniform { min, max } => min + (max - min).mul_f64(rng.f64()),
+            Self::Normal { mean, stddev } => {
+                // Box-Muller 变换；负值截断为 0
+                let (u1, u2) = (1.0 - rng.f64(), rng.f64());
+                let z = (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos();
+                Duration::from_secs_f64((mean.as_secs_f64() + z * stddev.as_secs_f64()).max(0.0))
+            }
+            Self::Exponential { mean } => mean.mul_f64(-(1.0 - rng.f64()).ln()),
+        }
+    }
+}
+
+impl From<Duration> for ThinkTime {
+    fn from(duration: Duration) -> Self {
+        if duration.is_zero() {
+            Self::None
+        } else {
+            Self::Fixed(duration)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(yaml: &str) -> Result<ThinkTime> {
+        ThinkTime::parse(&serde_yaml::from_str(yaml).expect("valid think time"))
+    }
+
+    fn mean_secs(think_time: ThinkTime) -> f64 {
+        let mut rng = fastrand::Rng::with_seed(11);
+        (0..10_000)
+            .map(|_| think_time.sample(&mut rng).as_secs_f64())
+            .sum::<f64>()
+            / 10_000.0
+    }
+
+    #[test]
+    fn test_parse_forms() {
+        assert_eq!(parse("0s").unwrap(), ThinkTime::None);
+        assert_eq!(
+            parse("2s").unwrap(),
+            ThinkTime::Fixed(Duration::from_secs(2))
+        );
+        assert_eq!(
+            parse("500ms..3s").unwrap(),
+            ThinkTime::Uniform {
+                min: Duration::from_millis(500),
+                max: Duration::from_secs(3),
+            }
+        );
+        assert_eq!(
+            parse("{distribution: normal, mean: 2s, stddev: 500ms}").unwrap(),
+            ThinkTime::Normal {
+                mean: Duration::from_secs(2),
+                stddev: Duration::from_millis(500),
+            }
+        );
+        assert!(parse("3s..1s").is_err());
+        assert!(parse("{distribution: exponential, mean: soon}").is_err());
+    }
+
+    #[test]
+    fn test_samples_follow_the_distribution() {
+        let uniform = parse("1s..3s").unwrap();
+        let mut rng = fastrand::Rng::with_seed(3);
+        assert!((0..1000).all(|_| {
+            let pause = uniform.sample(&mut rng);
+            (Duration::from_secs(1)..=Duration::from_secs(3)).contains(&pause)
+        }));
+        assert!((mean_secs(uniform) - 2.0).abs() < 0.05);
+
+        let normal = parse("{distribution: normal, mean: 2s, stddev: 500ms}").unwrap();
+        assert!((mean_secs(normal) - 2.0).abs() < 0.05);
+
+        let exponential = parse("{distribution: exponential, mean: 1s}").unwrap();
+        assert!((mean_secs(exponential) - 1.0).abs() < 0.05);
+    }
+}
@@ -1,11 +1,25 @@
 use anyhow::{Context, Result};
 use indexmap::IndexMap;
+use std::collections::{HashMap, VecDeque};
 use std::net::IpAddr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{Poll, Wake, Waker};
+use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::TemplateContext;
-use scheduler_core::dsl::{ActionDef, ActionsSection, WorkflowNodeType, WorkflowSection};
-use scheduler_executor::{ActionComponent, ActionContext};
+use crate::journal::{Journal, JournalEntry, RecordedAbort, SharedReplay};
+use crate::rate_limit::RateLimiter;
+use crate::template::ActionTemplate;
+use crate::think_time::ThinkTime;
+use crate::{ProgressBoard, RunState, SharedStore, TemplateContext};
+use scheduler_core::data::FeederSet;
+use scheduler_core::dsl::ActionDef;
+use scheduler_core::error::SchedulerError;
+use scheduler_core::export::{ActionExports, AssertionResult, WorkbookWrite};
+use scheduler_core::secrets;
+use scheduler_core::state_machine::{StateMachine, StateNode, StateNodeKind};
+use scheduler_executor::{ActionComponent, ActionContext, ActionOutcome, AsyncActionComponent};
 
 /// 用户上下文
 ///
@@ -38,10 +52,47 @@ impl UserContext {
 pub struct ExecutionTrace {
     pub user_id: usize,
     pub iteration: usize,
+    /// 执行动作的工作流节点
+    pub node_id: String,
+    /// 动作完成后转移到的节点
+    pub next_node: String,
     pub action_id: String,
     pub status: String,
     pub detail: String,
     pub duration_ms: u64,
+    pub assertions: Vec<AssertionResult>,
+}
+
+/// 迭代被中止时执行跟踪的状态
+pub const ABORTED_STATUS: &str = "Aborted";
+
+/// 单次迭代的上限，防止重试环等循环无限执行
+///
+/// 在每个动作执行前检查；节点级的 `max_visits` 来自状态机节点
+#[derive(Debug, Clone, Copy, Default)]
+pub struct IterationLimits {
+    /// 每次迭代最多执行的动作数
+    pub max_steps: Option<usize>,
+    /// 每次迭代的墙钟时间上限
+    pub timeout: Option<Duration>,
+}
+
+/// 退役信号：置位后用户完成当前迭代即停止（按阶段调整用户数时使用）
+#[derive(Debug, Clone, Default)]
+pub struct RetireSignal(Arc<AtomicBool>);
+
+impl RetireSignal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn retire(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_retired(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
 }
 
 /// 用户执行器
@@ -49,15 +100,44 @@ pub struct ExecutionTrace {
 /// 负责执行用户的工作流，支持：
 /// - 多次迭代执行
 /// - 变量替换（{{user.allocated_ip}} 等）
-/// - Think time 控制
-/// - 执行跟踪
+/// - 测试数据注入（{{feeder_name.column}}）
+/// - 动作导出变量（{{action-id.name}}），`scope: workbook` 的导出在用户间共享
+/// - Think time 控制（迭代之间与节点之后，可按分布随机抽取）与迭代节奏（pacing）
+/// - 执行跟踪与进度汇报（`ProgressBoard`）
+/// - 运行日志记录与按日志重放（`Journal`）
+/// - 检查点（`RunState`）与从指定迭代继续
+/// - 迭代上限（`IterationLimits` 与节点 `max_visits`），超出时中止该迭代
+/// - 退役（`RetireSignal`）：完成当前迭代后停止
+/// - 动作限速（`RateLimiter`），所有用户共享
 pub struct UserExecutor {
     context: UserContext,
-    workflow: WorkflowSection,
-    actions: ActionsSection,
+    state_machine: StateMachine,
     iterations: usize,
-    think_time: Duration,
+    think_time: ThinkTime,
+    /// 相邻两次迭代开始的间隔；设置后代替 `think_time`
+    pacing: Option<Duration>,
+    rng: fastrand::Rng,
     template: TemplateContext,
+    feeders: Option<Arc<FeederSet>>,
+    shared: SharedAccess,
+    progress: Option<Arc<ProgressBoard>>,
+    journal: Option<Arc<Journal>>,
+    run_state: Option<Arc<RunState>>,
+    /// 第一次执行的迭代（从检查点恢复时跳过已完成的迭代）
+    first_iteration: usize,
+    limits: IterationLimits,
+    retire: Option<RetireSignal>,
+    action_rate: Option<Arc<RateLimiter>>,
+    /// 重放时按迭代顺序使用的数据行（代替 feeder）
+    recorded_data: Option<VecDeque<IndexMap<String, String>>>,
+    /// 重放时按日志中止的迭代（代替上限检查）
+    recorded_aborts: Option<IndexMap<usize, RecordedAbort>>,
+    /// 已编译的导出定义（按动作 ID 缓存）
+    exports: HashMap<String, ActionExports>,
+    /// 已解析的动作模板（按动作 ID 缓存）
+    templates: HashMap<String, ActionTemplate>,
+    /// 已解析的节点思考时间（按节点 ID 缓存）
+    pauses: HashMap<String, ThinkTime>,
 }
 
 impl UserExecutor {
@@ -65,36 +145,154 @@ impl UserExecutor {
     ///
     /// # Arguments
     /// * `context` - 用户上下文
-    /// * `workflow` - 工作流定义
-    /// * `actions` - 动作定义列表
+    /// * `state_machine` - 已编译的工作流状态机（边条件已解析）
     /// * `iterations` - 迭代次数（0 = 无限循环）
     /// * `think_time` - 每次迭代之间的等待时间
     pub fn new(
         context: UserContext,
-        workflow: WorkflowSection,
-        actions: ActionsSection,
+        state_machine: StateMachine,
         iterations: usize,
-        think_time: Duration,
+        think_time: ThinkTime,
         template: TemplateContext,
     ) -> Self {
         Self {
             context,
-            workflow,
-            actions,
+            state_machine,
             iterations,
             think_time,
+            pacing: None,
+            rng: fastrand::Rng::new(),
             template,
+            feeders: None,
+            shared: SharedAccess::default(),
+            progress: None,
+            journal: None,
+            run_state: None,
+            first_iteration: 0,
+            limits: IterationLimits::default(),
+            retire: None,
+            action_rate: None,
+            recorded_data: None,
+            recorded_aborts: None,
+            exports: HashMap::new(),
+            templates: HashMap::new(),
+            pauses: HashMap::new(),
         }
     }
 
+    /// 绑定数据 feeder（所有用户共享同一个 `FeederSet`）
+    pub fn with_feeders(mut self, feeders: Arc<FeederSet>) -> Self {
+        self.feeders = Some(feeders);
+        self
+    }
+
+    /// 绑定工作簿级共享变量存储（所有用户共享同一个 `SharedStore`）
+    ///
+    /// 未绑定时，`scope: workbook` 的导出只对当前用户可见
+    pub fn with_shared_store(mut self, shared: Arc<SharedStore>) -> Self {
+        self.shared.store = Some(shared);
+        self
+    }
+
+    /// 绑定进度看板：每个动作完成后更新本用户的迭代进度
+    pub fn with_progress(mut self, progress: Arc<ProgressBoard>) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// 绑定运行日志：记录迭代数据、动作派发、动作结果与所选转移
+    pub fn with_journal(mut self, journal: Arc<Journal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// 绑定运行状态：每次迭代完成后汇总指标，供检查点使用
+    pub fn with_run_state(mut self, run_state: Arc<RunState>) -> Self {
+        self.run_state = Some(run_state);
+        self
+    }
+
+    /// 从第 `iteration` 次迭代（从 0 开始）继续，之前的迭代视为已完成
+    pub fn starting_at(mut self, iteration: usize) -> Self {
+        self.first_iteration = iteration;
+        self
+    }
+
+    /// 每隔 `pacing` 开始一次迭代，与迭代本身耗时无关；迭代超出间隔时立即开始下一次
+    pub fn with_pacing(mut self, pacing: Duration) -> Self {
+        self.pacing = Some(pacing);
+        self
+    }
+
+    /// 设置单次迭代的步数与时间上限
+    pub fn with_iteration_limits(mut self, limits: IterationLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// 绑定退役信号：每次迭代开始前检查，置位后不再开始新的迭代
+    pub fn with_retire_signal(mut self, retire: RetireSignal) -> Self {
+        self.retire = Some(retire);
+        self
+    }
+
+    /// 绑定动作限速器：每个动作执行前预订一个令牌（等待时间不计入动作耗时）
+    pub fn with_action_rate_limit(mut self, limiter: Arc<RateLimiter>) -> Self {
+        self.action_rate = Some(limiter);
+        self
+    }
+
+    /// 重放：工作簿共享变量按日志中记录的写入与读取版本重建，不再读写共享存储
+    pub fn with_recorded_shared(mut self, shared: SharedReplay) -> Self {
+        self.shared.replay = Some(shared);
+        self
+    }
+
+    /// 重放：每次迭代依次使用日志中记录的数据行，用完即停止；节点思考时间不再等待
+    pub fn with_recorded_data(mut self, iterations: Vec<IndexMap<String, String>>) -> Self {
+        self.recorded_data = Some(iterations.into());
+        self
+    }
+
+    /// 重放：在日志记录的位置中止迭代，不再检查上限（时间上限不可重现）
+    pub fn with_recorded_aborts(mut self, aborts: IndexMap<usize, RecordedAbort>) -> Self {
+        self.recorded_aborts = Some(aborts);
+        self
+    }
+
     /// 执行用户的所有迭代
     ///
+    /// 动作与等待都阻塞当前线程
+    ///
     /// # Arguments
     /// * `component` - ActionComponent 实现，用于执行具体的动作
     ///
     /// # Returns
     /// 所有迭代的执行跟踪列表
     pub fn run<C: ActionComponent>(&mut self, component: &mut C) -> Result<Vec<ExecutionTrace>> {
+        let traces = block_on(self.drive::<BlockingClock, _>(&mut Blocking(component)))?;
+        // 同步执行返回时日志已落盘
+        if let Some(journal) = &self.journal {
+            journal.flush().context("failed to write journal")?;
+        }
+        Ok(traces)
+    }
+
+    /// 异步执行用户的所有迭代
+    ///
+    /// 等待动作、思考时间与限速时让出线程，大量用户可在少量运行时线程上并发执行
+    #[cfg(not(target_arch = "wasm32"))]
+    pub async fn run_async<C: AsyncActionComponent>(
+        &mut self,
+        component: &mut C,
+    ) -> Result<Vec<ExecutionTrace>> {
+        self.drive::<TokioClock, _>(component).await
+    }
+
+    async fn drive<K: Clock, C: AsyncActionComponent>(
+        &mut self,
+        component: &mut C,
+    ) -> Result<Vec<ExecutionTrace>> {
         let mut all_traces = Vec::new();
 
         let actual_iterations = if self.iterations == 0 {
@@ -109,29 +307,84 @@ impl UserExecutor {
             self.iterations.to_string()
         };
 
-        for iteration in 0..actual_iterations {
-            if iteration > 0 {
-                std::thread::sleep(self.think_time);
+        self.record(JournalEntry::UserStart {
+            user: self.context.id,
+            tenant_id: self.context.tenant_id.clone(),
+            allocated_ip: self.context.allocated_ip.map(|ip| ip.to_string()),
+            first_iteration: self.first_iteration,
+        })?;
+
+        // per_user 数据行在整个用户生命周期内保持不变
+        let Some(user_data) = (match self.recorded_data {
+            Some(_) => Some(IndexMap::new()),
+            None => {
+                self.draw_data(|feeders| feeders.user_vars(self.context.id.saturating_sub(1)))?
+            }
+        }) else {
+            return Ok(all_traces);
+        };
+
+        let retire = self.retire.clone();
+        let retired = || retire.as_ref().is_some_and(RetireSignal::is_retired);
+        let mut iteration_started: Option<Instant> = None;
+        for iteration in self.first_iteration..actual_iterations {
+            if let Some(started) = iteration_started
+                && !retired()
+            {
+                K::sleep(self.pause_between_iterations(iteration, started)).await;
+            }
+            if retired() {
+                println!(
+                    "[User-{}] Retired after {} iterations",
+                    self.context.id, iteration
+                );
+                break;
             }
 
+            let data = match &mut self.recorded_data {
+                Some(recorded) => recorded.pop_front(),
+                None => self
+                    .draw_data(|feeders| feeders.iteration_vars(iteration))?
+                    .map(|mut data| {
+                        data.extend(user_data.clone());
+                        data
+                    }),
+            };
+            let Some(data) = data else {
+                break;
+            };
+            self.record(JournalEntry::Iteration {
+                user: self.context.id,
+                iteration,
+                data: data.clone(),
+            })?;
+
             println!(
                 "[User-{}] Starting iteration {}/{}",
                 self.context.id,
                 iteration + 1,
                 iterations_label.as_str()
             );
+            if let Some(progress) = &self.progress {
+                progress.start_iteration(self.context.id, iteration, self.iterations);
+            }
 
             // 执行一次完整的 workflow
-            let mut iteration_traces =
-                self.execute_workflow(component, iteration)
-                    .with_context(|| {
-                        format!(
-                            "User {} iteration {} failed",
-                            self.context.id,
-                            iteration + 1
-                        )
-                    })?;
+            iteration_started = Some(Instant::now());
+            let mut iteration_traces = self
+                .execute_workflow::<K, _>(component, iteration, &data)
+                .await
+                .with_context(|| {
+                    format!(
+                        "User {} iteration {} failed",
+                        self.context.id,
+                        iteration + 1
+                    )
+                })?;
 
+            if let Some(run_state) = &self.run_state {
+                run_state.complete_iteration(self.context.id, &iteration_traces);
+            }
             all_traces.append(&mut iteration_traces);
         }
 
@@ -140,19 +393,72 @@ impl UserExecutor {
             self.context.id,
             iterations_label.as_str()
         );
+        if let Some(progress) = &self.progress {
+            progress.finish_user(self.context.id);
+        }
 
         Ok(all_traces)
     }
 
+    /// 上一次迭代（第 `iteration - 1` 次，开始于 `started`）结束后的等待时间
+    fn pause_between_iterations(&mut self, iteration: usize, started: Instant) -> Duration {
+        let Some(pacing) = self.pacing else {
+            return self.think_time.sample(&mut self.rng);
+        };
+        let elapsed = started.elapsed();
+        if elapsed > pacing {
+            println!(
+                "[User-{}] Iteration {} took {}ms, longer than pacing {}ms",
+                self.context.id,
+                iteration,
+                elapsed.as_millis(),
+                pacing.as_millis()
+            );
+        }
+        pacing.saturating_sub(elapsed)
+    }
+
+    fn record(&self, entry: JournalEntry) -> Result<()> {
+        if let Some(journal) = &self.journal {
+            journal.record(&entry).context("failed to write journal")?;
+        }
+        Ok(())
+    }
+
+    /// 从 feeder 取数据行；`unique` feeder 耗尽时返回 `None`，用户随之停止
+    fn draw_data(
+        &self,
+        draw: impl FnOnce(&FeederSet) -> Result<IndexMap<String, String>, SchedulerError>,
+    ) -> Result<Option<IndexMap<String, String>>> {
+        let Some(feeders) = &self.feeders else {
+            return Ok(Some(IndexMap::new()));
+        };
+        match draw(feeders) {
+            Ok(data) => Ok(Some(data)),
+            Err(SchedulerError::FeederExhausted(name)) => {
+                println!(
+                    "[User-{}] Data feeder '{}' exhausted, stopping",
+                    self.context.id, name
+                );
+                Ok(None)
+            }
+            Err(err) => Err(err.into()),
+        }
+    }
+
     /// 执行一次完整的 workflow
-    fn execute_workflow<C: ActionComponent>(
+    async fn execute_workflow<K: Clock, C: AsyncActionComponent>(
         &mut self,
         component: &mut C,
         iteration: usize,
+        data: &IndexMap<String, String>,
     ) -> Result<Vec<ExecutionTrace>> {
         let mut traces = Vec::new();
         let mut current_node = String::from("start");
-        let mut execution_context = IndexMap::new();
+        let mut execution_context = data.clone();
+        let started = Instant::now();
+        let mut steps = 0;
+        let mut visits: HashMap<String, usize> = HashMap::new();
 
         // 创建一个临时的 WbsTree 用于 ActionContext
         let temp_wbs = scheduler_core::wbs::WbsTree::new_empty();
@@ -167,56 +473,171 @@ impl UserExecutor {
         loop {
             // 查找当前节点
             let node = self
-                .workflow
+                .state_machine
                 .nodes
-                .iter()
-                .find(|n| n.id == current_node)
+                .get(&current_node)
                 .with_context(|| format!("Node '{}' not found", current_node))?;
 
-            match node.node_type {
-                WorkflowNodeType::Action => {
+            match node.kind {
+                StateNodeKind::Action => {
                     // 获取动作定义
-                    let action_id = node
+                    let action = node
                         .action
                         .as_ref()
                         .with_context(|| format!("Node '{}' has no action", current_node))?;
+                    let action_id = &action.id;
 
-                    let action = self
-                        .actions
-                        .actions
-                        .iter()
-                        .find(|a| &a.id == action_id)
-                        .with_context(|| format!("Action '{}' not found", action_id))?;
-
-                    // 替换变量
-                    let resolved_action = self.resolve_variables(action, &execution_context)?;
+                    // 超出本次迭代的上限时中止迭代
+                    let visit = visits.entry(current_node.clone()).or_default();
+                    *visit += 1;
+                    if let Some(reason) = self.abort_reason(node, iteration, steps, *visit, started)
+                    {
+                        self.record(JournalEntry::Abort {
+                            user: self.context.id,
+                            iteration,
+                            step: steps,
+                            task_id: current_node.clone(),
+                            reason: reason.clone(),
+                        })?;
+                        println!(
+                            "[User-{}] Iteration {} aborted: {}",
+                            self.context.id,
+                            iteration + 1,
+                            reason
+                        );
+                        traces.push(ExecutionTrace {
+                            user_id: self.context.id,
+                            iteration,
+                            node_id: current_node.clone(),
+                            next_node: String::new(),
+                            action_id: action_id.clone(),
+                            status: ABORTED_STATUS.to_string(),
+                            detail: reason,
+                            duration_ms: 0,
+                            assertions: Vec::new(),
+                        });
+                        break;
+                    }
+                    steps += 1;
+                    if let Some(limiter) = &self.action_rate {
+                        K::sleep(limiter.reserve()).await;
+                    }
 
-                    // 执行动作
+                    // 替换变量；模板错误（严格模式下的未定义变量等）只让该动作失败
                     let start = Instant::now();
-                    let mut action_ctx = ActionContext::new(&temp_wbs);
-                    let outcome = component
-                        .do_action(&resolved_action, &mut action_ctx)
-                        .with_context(|| format!("Action '{}' execution failed", action_id))?;
+                    let shared =
+                        self.shared
+                            .read(self.journal.as_deref(), self.context.id, iteration)?;
+                    let scope = self.scope(&execution_context, shared.as_ref());
+                    let rendered = compiled_template(&mut self.templates, action)
+                        .and_then(|template| template.render(&scope));
+                    let outcome = match rendered {
+                        Ok(resolved_action) => {
+                            self.record(JournalEntry::dispatch(
+                                Some(self.context.id),
+                                iteration,
+                                &current_node,
+                                &resolved_action,
+                            ))?;
+                            let mut action_ctx = ActionContext::new(&temp_wbs);
+                            let outcome = match component
+                                .do_action(&resolved_action, &mut action_ctx)
+                                .await
+                            {
+                                Ok(outcome) => outcome,
+                                Err(err) => {
+                                    self.record(JournalEntry::action_error(
+                                        Some(self.context.id),
+                                        iteration,
+                                        &current_node,
+                                        action_id,
+                                        &err,
+                                    ))?;
+                                    return Err(err.context(format!(
+                                        "Action '{}' execution failed",
+                                        action_id
+                                    )));
+                                }
+                            };
+                            self.record(JournalEntry::outcome(
+                                Some(self.context.id),
+                                iteration,
+                                &current_node,
+                                action_id,
+                                &outcome,
+                                &action_ctx.into_events(),
+                            ))?;
+                            outcome
+                        }
+                        Err(err) => {
+                            ActionOutcome::failure().with_detail(format!("template error: {}", err))
+                        }
+                    };
                     let duration = start.elapsed();
 
-                    // 记录跟踪
+                    // 提取导出变量，供后续动作与边条件使用；断言失败时动作记为失败
+                    let values = compiled_exports(&mut self.exports, action)?
+                        .evaluate(outcome.response.as_ref());
+                    execution_context.extend(values.vars);
+                    let published = self.shared.write(
+                        self.journal.as_deref(),
+                        self.context.id,
+                        iteration,
+                        &current_node,
+                        &values.workbook,
+                    )?;
+                    if !published {
+                        execution_context.extend(
+                            values
+                                .workbook
+                                .into_iter()
+                                .map(|write| (write.name, write.value)),
+                        );
+                    }
+                    let outcome = outcome.with_assertions(values.assertions);
+
+                    // 选择下一个节点
+                    let shared =
+                        self.shared
+                            .read(self.journal.as_deref(), self.context.id, iteration)?;
+                    let next_node =
+                        self.select_next_node(node, &execution_context, shared.as_ref())?;
+                    self.record(JournalEntry::Transition {
+                        user: Some(self.context.id),
+                        iteration,
+                        from: current_node.clone(),
+                        to: Some(next_node.clone()),
+                    })?;
+
+                    // 记录跟踪（含所走的边，供工作流图叠加命中次数）
                     traces.push(ExecutionTrace {
                         user_id: self.context.id,
                         iteration,
+                        node_id: current_node.clone(),
+                        next_node: next_node.clone(),
                         action_id: action_id.clone(),
                         status: format!("{:?}", outcome.status),
-                        detail: outcome.detail.unwrap_or_default(),
+                        detail: secrets::redact(&outcome.detail.unwrap_or_default()),
                         duration_ms: duration.as_millis() as u64,
+                        assertions: outcome.assertions,
                     });
+                    if let Some(progress) = &self.progress {
+                        progress.complete_task(self.context.id, &current_node);
+                    }
 
-                    // TODO: 从 ActionContext 获取输出并更新上下文
-                    // 当前简化实现：不保存动作输出
+                    // 节点级思考时间（重放时不等待）
+                    if self.recorded_data.is_none() {
+                        let pause = node_think_time(&mut self.pauses, node)?;
+                        K::sleep(pause.sample(&mut self.rng)).await;
+                    }
 
-                    // 选择下一个节点
-                    current_node = self.select_next_node(node, &execution_context)?;
+                    current_node = next_node;
                 }
-                WorkflowNodeType::End => {
+                StateNodeKind::End => {
                     // 到达终点
+                    if let Some(progress) = &self.progress {
+                        progress.finish_iteration(self.context.id);
+                    }
                     break;
                 }
             }
@@ -225,76 +646,285 @@ impl UserExecutor {
         Ok(traces)
     }
 
-    /// 替换动作中的变量
+    /// 执行节点 `node` 的动作前检查迭代上限，超出时返回中止原因
+    ///
+    /// `steps` 为本次迭代已执行的动作数，`visit` 为本次是第几次访问该节点
+    fn abort_reason(
+        &self,
+        node: &StateNode,
+        iteration: usize,
+        steps: usize,
+        visit: usize,
+        started: Instant,
+    ) -> Option<String> {
+        if let Some(aborts) = &self.recorded_aborts {
+            return aborts
+                .get(&iteration)
+                .filter(|abort| abort.step == steps && abort.task_id == node.id)
+                .map(|abort| abort.reason.clone());
+        }
+        if let Some(max_steps) = self.limits.max_steps
+            && steps >= max_steps
+        {
+            return Some(format!("max_steps_per_iteration ({}) exceeded", max_steps));
+        }
+        if let Some(max_visits) = node.max_visits
+            && visit > max_visits
+        {
+            return Some(format!(
+                "max_visits ({}) of node '{}' exceeded",
+                max_visits, node.id
+            ));
+        }
+        if let Some(timeout) = self.limits.timeout
+            && started.elapsed() >= timeout
+        {
+            return Some(format!("iteration_timeout ({:?}) exceeded", timeout));
+        }
+        None
+    }
+
+    /// 合并模板变量、工作簿共享变量与用户上下文（后者优先）
     ///
-    /// 支持的变量：
+    /// 可用的变量：
     /// - {{user.id}} - 用户 ID
     /// - {{user.allocated_ip}} - 用户分配的 IP
     /// - {{tenant.id}} - 租户 ID
+    /// - {{feeder.column}} - 当前数据行
     /// - {{action.property}} - 之前动作的输出
-    fn resolve_variables(
+    fn scope(
         &self,
-        action: &ActionDef,
         context: &IndexMap<String, String>,
-    ) -> Result<ActionDef> {
-        let merged_ctx = self.template.merged(context);
-        Ok(merged_ctx.render_action(action))
+        shared: Option<&IndexMap<String, String>>,
+    ) -> TemplateContext {
+        let mut merged = self.template.clone();
+        if let Some(shared) = shared {
+            merged.extend(shared);
+        }
+        merged.extend(context);
+        merged
     }
 
     /// 根据条件选择下一个节点
+    ///
+    /// 按声明顺序评估每条转移的触发条件（已在构建状态机时编译），
+    /// 返回第一个满足条件的目标节点。
     fn select_next_node(
         &self,
-        node: &scheduler_core::dsl::WorkflowNode,
+        node: &StateNode,
         context: &IndexMap<String, String>,
+        shared: Option<&IndexMap<String, String>>,
     ) -> Result<String> {
-        let merged_ctx = self.template.merged(context);
-        for edge in &node.edges {
-            // 如果没有条件，直接选择
-            if edge.trigger.is_none() {
-                return Ok(edge.to.clone());
-            }
+        let merged_ctx = self.scope(context, shared);
+        node.transitions
+            .iter()
+            .find(|transition| transition.trigger.is_satisfied(&merged_ctx))
+            .map(|transition| transition.to.clone())
+            .with_context(|| format!("No matching edge found for node '{}'", node.id))
+    }
+}
 
-            // 评估条件（简化版：只支持 "true" 或简单的相等判断）
-            if let Some(trigger) = &edge.trigger {
-                if let Some(condition) = &trigger.condition {
-                    if condition == "true" || self.evaluate_condition(condition, &merged_ctx) {
-                        return Ok(edge.to.clone());
-                    }
-                }
+/// 取动作的已解析模板，首次使用时解析
+fn compiled_template<'a>(
+    cache: &'a mut HashMap<String, ActionTemplate>,
+    action: &ActionDef,
+) -> Result<&'a ActionTemplate, SchedulerError> {
+    if !cache.contains_key(&action.id) {
+        cache.insert(action.id.clone(), ActionTemplate::compile(action)?);
+    }
+    Ok(&cache[&action.id])
+}
+
+/// 工作簿共享变量的读写
+///
+/// 绑定运行日志时，每次生效的写入连同版本号记入日志；读取时看到的版本与上一次
+/// 不同也记入日志。重放时不访问共享存储，每次读取由 `SharedReplay` 按记录的版本
+/// 重建，因此各用户可以依次重放而看到与记录时相同的值。
+#[derive(Default)]
+struct SharedAccess {
+    store: Option<Arc<SharedStore>>,
+    replay: Option<SharedReplay>,
+    /// 已读取的次数
+    reads: usize,
+    /// 上一次记入日志的版本
+    recorded_version: Option<u64>,
+}
+
+impl SharedAccess {
+    /// 读取当前共享变量；未绑定共享存储时返回 `None`
+    fn read(
+        &mut self,
+        journal: Option<&Journal>,
+        user: usize,
+        iteration: usize,
+    ) -> Result<Option<IndexMap<String, String>>> {
+        if let Some(replay) = &mut self.replay {
+            return Ok(Some(replay.next_snapshot()));
+        }
+        let Some(store) = &self.store else {
+            return Ok(None);
+        };
+        let (version, snapshot) = store.versioned_snapshot();
+        let read = self.reads;
+        self.reads += 1;
+        if let Some(journal) = journal
+            && self.recorded_version != Some(version)
+        {
+            self.recorded_version = Some(version);
+            journal
+                .record(&JournalEntry::SharedRead {
+                    user,
+                    iteration,
+                    read,
+                    version,
+                })
+                .context("failed to write journal")?;
+        }
+        Ok(Some(snapshot))
+    }
+
+    /// 发布 `scope: workbook` 的导出；未绑定共享存储时返回 `false`，
+    /// 由调用方写入用户自己的上下文
+    fn write(
+        &mut self,
+        journal: Option<&Journal>,
+        user: usize,
+        iteration: usize,
+        task_id: &str,
+        writes: &[WorkbookWrite],
+    ) -> Result<bool> {
+        if self.replay.is_some() {
+            // 记录的写入已包含在重放的历史中
+            return Ok(true);
+        }
+        let Some(store) = &self.store else {
+            return Ok(false);
+        };
+        for (version, write) in store.apply(writes) {
+            if let Some(journal) = journal {
+                journal
+                    .record(&JournalEntry::SharedWrite {
+                        user,
+                        iteration,
+                        task_id: task_id.to_string(),
+                        version,
+                        name: write.name.clone(),
+                        value: write.value.clone(),
+                        mode: write.mode,
+                    })
+                    .context("failed to write journal")?;
             }
         }
+        Ok(true)
+    }
+}
+
+/// 等待方式：同步执行时阻塞线程，异步执行时交给 tokio 定时器
+trait Clock {
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+}
+
+/// 在调用 `sleep` 时阻塞线程，返回已完成的 future
+struct BlockingClock;
 
-        anyhow::bail!("No matching edge found for node '{}'", node.id)
+impl Clock for BlockingClock {
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        thread::sleep(duration);
+        std::future::ready(())
     }
+}
 
-    /// 简单的条件评估
-    ///
-    /// 支持格式：{{variable}} == value
-    fn evaluate_condition(&self, condition: &str, context: &TemplateContext) -> bool {
-        // 简化实现：只支持 "true" 和基本的相等比较
-        if condition == "true" {
-            return true;
-        }
+#[cfg(not(target_arch = "wasm32"))]
+struct TokioClock;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Clock for TokioClock {
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+}
+
+/// 把同步组件包装成异步接口：动作在调用时执行，返回已完成的 future
+struct Blocking<'a, C>(&'a mut C);
 
-        // 替换变量后评估
-        let resolved = context.render_str(condition);
+impl<C: ActionComponent> AsyncActionComponent for Blocking<'_, C> {
+    fn init(&mut self) -> impl Future<Output = Result<()>> + Send {
+        std::future::ready(self.0.init())
+    }
 
-        // 简单的相等判断（如 "200 == 200"）
-        if let Some(pos) = resolved.find("==") {
-            let left = resolved[..pos].trim();
-            let right = resolved[pos + 2..].trim();
-            return left == right;
+    fn do_action(
+        &mut self,
+        action: &ActionDef,
+        ctx: &mut ActionContext<'_>,
+    ) -> impl Future<Output = Result<ActionOutcome>> + Send {
+        std::future::ready(self.0.do_action(action, ctx))
+    }
+
+    fn release(&mut self) -> impl Future<Output = Result<()>> + Send {
+        std::future::ready(self.0.release())
+    }
+}
+
+/// 在当前线程上驱动 future 直到完成；挂起时停放线程，直到 waker 将其唤醒
+fn block_on<F: Future>(future: F) -> F::Output {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = std::task::Context::from_waker(&waker);
+    let mut future = std::pin::pin!(future);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
         }
+    }
+}
+
+/// 唤醒时解除 `block_on` 所在线程的停放
+struct ThreadWaker(thread::Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
 
-        false
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
     }
 }
 
+/// 取节点的思考时间，首次使用时解析
+fn node_think_time(cache: &mut HashMap<String, ThinkTime>, node: &StateNode) -> Result<ThinkTime> {
+    if let Some(think_time) = cache.get(&node.id) {
+        return Ok(*think_time);
+    }
+    let think_time = match &node.think_time {
+        Some(def) => ThinkTime::parse(def)
+            .with_context(|| format!("invalid think_time of node '{}'", node.id))?,
+        None => ThinkTime::None,
+    };
+    cache.insert(node.id.clone(), think_time);
+    Ok(think_time)
+}
+
+/// 取动作的已编译导出定义，首次使用时编译
+fn compiled_exports<'a>(
+    cache: &'a mut HashMap<String, ActionExports>,
+    action: &ActionDef,
+) -> Result<&'a ActionExports> {
+    if !cache.contains_key(&action.id) {
+        cache.insert(action.id.clone(), ActionExports::compile(action)?);
+    }
+    Ok(&cache[&action.id])
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use scheduler_core::dsl::{TriggerDef, WorkflowEdge, WorkflowNode};
-    use scheduler_executor::{ActionOutcome, ActionStatus};
+    use scheduler_core::dsl::{
+        ActionsSection, Scenario, TriggerDef, WorkbookSection, WorkflowEdge, WorkflowNode,
+        WorkflowNodeType, WorkflowSection,
+    };
+    use scheduler_core::wbs::WbsTree;
+    use scheduler_executor::ActionResponse;
     use serde_yaml::Value;
 
     // 简单的测试 ActionComponent
@@ -310,10 +940,7 @@ mod tests {
             _action: &ActionDef,
             _ctx: &mut ActionContext<'_>,
         ) -> Result<ActionOutcome> {
-            Ok(ActionOutcome {
-                status: ActionStatus::Success,
-                detail: Some("Executed".to_string()),
-            })
+            Ok(ActionOutcome::success().with_detail("Executed"))
         }
 
         fn release(&mut self) -> Result<()> {
@@ -348,24 +975,1174 @@ mod tests {
 
         let context =
             UserContext::new(1, "tenant-a".to_string(), Some("10.0.1.1".parse().unwrap()));
-        let workflow = WorkflowSection { nodes: vec![] };
-        let actions = ActionsSection { actions: vec![] };
+        let state_machine = StateMachine::from_wbs(&WbsTree::new_empty());
 
         let executor = UserExecutor::new(
             context,
-            workflow,
-            actions,
+            state_machine,
             1,
-            Duration::from_secs(0),
+            ThinkTime::None,
             TemplateContext::new(),
         );
 
         let mut exec_ctx = IndexMap::new();
         exec_ctx.insert("user.allocated_ip".to_string(), "10.0.1.1".to_string());
 
-        let resolved = executor.resolve_variables(&action, &exec_ctx).unwrap();
+        let resolved = executor
+            .scope(&exec_ctx, None)
+            .render_action(&action)
+            .unwrap();
         let url = resolved.with.get("url").unwrap().as_str().unwrap();
 
         assert_eq!(url, "http://10.0.1.1:8080");
     }
+
+    fn edge(to: &str, condition: &str) -> WorkflowEdge {
+        WorkflowEdge {
+            to: to.into(),
+            trigger: Some(TriggerDef {
+                condition: Some(condition.into()),
+            }),
+            label: None,
+        }
+    }
+
+    fn action_node(id: &str, action: &str, edges: Vec<WorkflowEdge>) -> WorkflowNode {
+        WorkflowNode {
+            id: id.into(),
+            node_type: WorkflowNodeType::Action,
+            parent: None,
+            weight: None,
+            max_visits: None,
+            think_time: None,
+            action: Some(action.into()),
+            edges,
+        }
+    }
+
+    #[test]
+    fn test_conditions_select_branch() {
+        let action = |id: &str| ActionDef {
+            id: id.into(),
+            call: "get".into(),
+            with: IndexMap::new(),
+            export: vec![],
+        };
+        let scenario = Scenario {
+            version: "1.0".into(),
+            name: "branching".into(),
+            include: vec![],
+            variables: Default::default(),
+            secrets: Default::default(),
+            data: None,
+            workbook: WorkbookSection::default(),
+            actions: ActionsSection {
+                actions: vec![action("health-check"), action("fetch"), action("recover")],
+            },
+            workflows: WorkflowSection {
+                nodes: vec![
+                    action_node(
+                        "start",
+                        "health-check",
+                        vec![
+                            edge("fetch-node", "{{health-check.status_code == 200}}"),
+                            edge("recover-node", "{{health-check.status_code != 200}}"),
+                        ],
+                    ),
+                    action_node("fetch-node", "fetch", vec![edge("end", "true")]),
+                    action_node("recover-node", "recover", vec![edge("end", "true")]),
+                    WorkflowNode {
+                        id: "end".into(),
+                        node_type: WorkflowNodeType::End,
+                        parent: None,
+                        weight: None,
+                        max_visits: None,
+                        think_time: None,
+                        action: None,
+                        edges: vec![],
+                    },
+                ],
+            },
+            load: None,
+        };
+        scenario.validate().expect("valid scenario");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+
+        let mut template = TemplateContext::new();
+        template.insert("health-check.status_code", "503");
+
+        let mut executor = UserExecutor::new(
+            UserContext::new(1, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            1,
+            ThinkTime::None,
+            template,
+        );
+
+        let traces = executor.run(&mut TestComponent).expect("run workflow");
+        let executed: Vec<_> = traces.iter().map(|t| t.action_id.as_str()).collect();
+        assert_eq!(executed, vec!["health-check", "recover"]);
+    }
+
+    struct RecordingComponent {
+        urls: Vec<String>,
+        response: Option<ActionResponse>,
+    }
+
+    impl ActionComponent for RecordingComponent {
+        fn init(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        fn do_action(
+            &mut self,
+            action: &ActionDef,
+            _ctx: &mut ActionContext<'_>,
+        ) -> Result<ActionOutcome> {
+            let url = action.with.get("url").and_then(Value::as_str).unwrap_or("");
+            self.urls.push(url.to_string());
+            let outcome = ActionOutcome::success();
+            Ok(match &self.response {
+                Some(response) => outcome.with_response(response.clone()),
+                None => outcome,
+            })
+        }
+
+        fn release(&mut self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_feeder_rows_are_injected_until_exhausted() {
+        use scheduler_core::data::Feeder;
+        use scheduler_core::dsl::{FeederAssign, FeederStrategy};
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: fed
+actions:
+  actions:
+    - id: login
+      call: post
+      with:
+        url: "http://host/{{account.name}}/{{item.sku}}"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: login
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let row = |key: &str, value: &str| IndexMap::from([(key.to_string(), value.to_string())]);
+        let feeders = FeederSet::new(vec![
+            Feeder::new(
+                "account",
+                FeederStrategy::Sequential,
+                FeederAssign::PerUser,
+                vec![row("name", "alice"), row("name", "bob")],
+            ),
+            Feeder::new(
+                "item",
+                FeederStrategy::Unique,
+                FeederAssign::PerIteration,
+                vec![row("sku", "A"), row("sku", "B")],
+            ),
+        ]);
+
+        let mut executor = UserExecutor::new(
+            UserContext::new(2, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            3,
+            ThinkTime::None,
+            TemplateContext::new(),
+        )
+        .with_feeders(Arc::new(feeders));
+
+        let mut component = RecordingComponent {
+            urls: Vec::new(),
+            response: None,
+        };
+        let traces = executor.run(&mut component).expect("run workflow");
+        assert_eq!(traces.len(), 2);
+        assert_eq!(
+            component.urls,
+            vec!["http://host/bob/A", "http://host/bob/B"]
+        );
+    }
+
+    #[test]
+    fn test_exports_feed_later_actions_and_conditions() {
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: exported
+actions:
+  actions:
+    - id: login
+      call: post
+      with:
+        url: "http://host/login"
+      export:
+        - type: status
+          name: status_code
+        - type: jsonpath
+          name: token
+          expr: "$.session.token"
+        - type: header
+          name: trace
+          expr: X-Trace-Id
+          default: "none"
+    - id: fetch
+      call: get
+      with:
+        url: "http://host/items?token={{login.token}}&trace={{login.trace}}"
+    - id: retry
+      call: get
+      with:
+        url: "http://host/retry"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: login
+      edges:
+        - to: fetch-node
+          trigger:
+            condition: "{{login.status_code == 201}}"
+        - to: retry-node
+    - id: fetch-node
+      type: action
+      action: fetch
+      edges:
+        - to: end
+    - id: retry-node
+      type: action
+      action: retry
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+
+        let mut executor = UserExecutor::new(
+            UserContext::new(1, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            1,
+            ThinkTime::None,
+            TemplateContext::new(),
+        );
+        let mut component = RecordingComponent {
+            urls: Vec::new(),
+            response: Some(ActionResponse {
+                status_code: Some(201),
+                body: r#"{"session": {"token": "t-1"}}"#.to_string(),
+                ..Default::default()
+            }),
+        };
+        let traces = executor.run(&mut component).expect("run workflow");
+
+        assert_eq!(
+            component.urls,
+            vec![
+                "http://host/login",
+                "http://host/items?token=t-1&trace=none"
+            ]
+        );
+        let taken: Vec<_> = traces
+            .iter()
+            .map(|t| (t.node_id.as_str(), t.next_node.as_str()))
+            .collect();
+        assert_eq!(taken, vec![("start", "fetch-node"), ("fetch-node", "end")]);
+    }
+
+    #[test]
+    fn test_replay_reproduces_recorded_traces() {
+        use crate::journal::Replay;
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: replayed
+actions:
+  actions:
+    - id: login
+      call: post
+      with:
+        url: "http://host/{{account.name}}"
+      export:
+        - type: status
+          name: status_code
+        - type: assert
+          name: created
+          status: [201]
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: login
+      edges:
+        - to: end
+          trigger:
+            condition: "{{login.status_code == 201}}"
+        - to: retry
+    - id: retry
+      type: action
+      action: login
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let path =
+            std::env::temp_dir().join(format!("scheduler-user-journal-{}", std::process::id()));
+        let executor = |data: Option<Vec<IndexMap<String, String>>>| {
+            let executor = UserExecutor::new(
+                UserContext::new(4, "tenant-a".to_string(), None),
+                StateMachine::from_wbs(&tree),
+                2,
+                ThinkTime::None,
+                TemplateContext::new(),
+            );
+            match data {
+                Some(data) => executor.with_recorded_data(data),
+                None => executor,
+            }
+        };
+        // `duration_ms` 是墙钟时间，不参与比较
+        let summarize = |traces: &[ExecutionTrace]| -> Vec<_> {
+            traces
+                .iter()
+                .map(|t| {
+                    (
+                        t.iteration,
+                        t.node_id.clone(),
+                        t.next_node.clone(),
+                        t.status.clone(),
+                        t.detail.clone(),
+                        t.assertions.clone(),
+                    )
+                })
+                .collect()
+        };
+
+        let feeders = FeederSet::new(vec![scheduler_core::data::Feeder::new(
+            "account",
+            scheduler_core::dsl::FeederStrategy::Sequential,
+            scheduler_core::dsl::FeederAssign::PerIteration,
+            vec![
+                IndexMap::from([("name".to_string(), "alice".to_string())]),
+                IndexMap::from([("name".to_string(), "bob".to_string())]),
+            ],
+        )]);
+        let mut component = RecordingComponent {
+            urls: Vec::new(),
+            response: Some(ActionResponse {
+                status_code: Some(500),
+                ..Default::default()
+            }),
+        };
+        let traces = executor(None)
+            .with_feeders(Arc::new(feeders))
+            .with_journal(Arc::new(Journal::create(&path).expect("create journal")))
+            .run(&mut component)
+            .expect("record run");
+
+        let mut replay = Replay::load(&path).expect("load journal");
+        std::fs::remove_file(&path).ok();
+        let user = replay.users.shift_remove(&4).expect("recorded user");
+        let mut replayed = user.component;
+        let replayed_traces = executor(Some(user.iterations))
+            .run(&mut replayed)
+            .expect("replay run");
+
+        assert_eq!(component.urls.len(), 4);
+        assert_eq!(traces[0].next_node, "retry");
+        assert_eq!(summarize(&replayed_traces), summarize(&traces));
+        assert_eq!(replayed.remaining(), 0);
+    }
+
+    #[test]
+    fn test_iteration_limits_abort_retry_cycles() {
+        use crate::journal::Replay;
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: retry-cycle
+actions:
+  actions:
+    - id: probe
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      edges:
+        - to: retry
+    - id: retry
+      type: action
+      action: probe
+      max_visits: 3
+      edges:
+        - to: retry
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let executor = |limits: IterationLimits| {
+            UserExecutor::new(
+                UserContext::new(1, "tenant-a".to_string(), None),
+                StateMachine::from_wbs(&tree),
+                2,
+                ThinkTime::None,
+                TemplateContext::new(),
+            )
+            .with_iteration_limits(limits)
+        };
+        let aborted = |traces: &[ExecutionTrace]| -> Vec<_> {
+            traces
+                .iter()
+                .filter(|t| t.status == ABORTED_STATUS)
+                .map(|t| (t.iteration, t.node_id.clone(), t.detail.clone()))
+                .collect()
+        };
+
+        // 节点访问次数：retry 执行 3 次后中止，下一次迭代照常开始
+        let path =
+            std::env::temp_dir().join(format!("scheduler-user-abort-{}", std::process::id()));
+        let run_state = Arc::new(RunState::new());
+        run_state.start_phase(
+            1,
+            vec![(1, crate::UserRecord::new("tenant-a".into(), None))],
+        );
+        let traces = executor(IterationLimits::default())
+            .with_journal(Arc::new(Journal::create(&path).expect("create journal")))
+            .with_run_state(Arc::clone(&run_state))
+            .run(&mut TestComponent)
+            .expect("run workflow");
+        let reason = "max_visits (3) of node 'retry' exceeded".to_string();
+        assert_eq!(traces.len(), 10);
+        assert_eq!(
+            aborted(&traces),
+            vec![
+                (0, "retry".to_string(), reason.clone()),
+                (1, "retry".to_string(), reason.clone()),
+            ]
+        );
+        let metrics = run_state.metrics();
+        assert_eq!(metrics.actions(), 8);
+        assert_eq!(metrics.aborted, IndexMap::from([(reason, 2)]));
+        assert_eq!(metrics.edge_hits.get("retry", ""), 0);
+
+        // 重放在记录的位置中止
+        let mut replay = Replay::load(&path).expect("load journal");
+        std::fs::remove_file(&path).ok();
+        let user = replay.users.shift_remove(&1).expect("recorded user");
+        let mut replayed = user.component;
+        let replayed_traces = UserExecutor::new(
+            UserContext::new(1, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            2,
+            ThinkTime::None,
+            TemplateContext::new(),
+        )
+        .with_recorded_data(user.iterations)
+        .with_recorded_aborts(user.aborts)
+        .run(&mut replayed)
+        .expect("replay run");
+        assert_eq!(aborted(&replayed_traces), aborted(&traces));
+        assert_eq!(replayed.remaining(), 0);
+
+        // 步数上限先于节点访问次数生效
+        let traces = executor(IterationLimits {
+            max_steps: Some(2),
+            timeout: None,
+        })
+        .run(&mut TestComponent)
+        .expect("run workflow");
+        assert_eq!(traces.len(), 6);
+        assert_eq!(
+            aborted(&traces)[0],
+            (
+                0,
+                "retry".to_string(),
+                "max_steps_per_iteration (2) exceeded".to_string()
+            )
+        );
+
+        // 时间上限在每个动作执行前检查
+        let traces = executor(IterationLimits {
+            max_steps: None,
+            timeout: Some(Duration::ZERO),
+        })
+        .run(&mut TestComponent)
+        .expect("run workflow");
+        assert_eq!(
+            aborted(&traces),
+            vec![
+                (
+                    0,
+                    "start".to_string(),
+                    "iteration_timeout (0ns) exceeded".to_string()
+                ),
+                (
+                    1,
+                    "start".to_string(),
+                    "iteration_timeout (0ns) exceeded".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_replay_of_resumed_user_keeps_iteration_numbers() {
+        use crate::journal::Replay;
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: resumed
+actions:
+  actions:
+    - id: probe
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      edges:
+        - to: retry
+    - id: retry
+      type: action
+      action: probe
+      max_visits: 2
+      edges:
+        - to: retry
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let executor = || {
+            UserExecutor::new(
+                UserContext::new(1, "tenant-a".to_string(), None),
+                StateMachine::from_wbs(&tree),
+                4,
+                ThinkTime::None,
+                TemplateContext::new(),
+            )
+        };
+        let aborted = |traces: &[ExecutionTrace]| -> Vec<_> {
+            traces
+                .iter()
+                .filter(|t| t.status == ABORTED_STATUS)
+                .map(|t| (t.iteration, t.node_id.clone()))
+                .collect()
+        };
+
+        // `--resume` 续跑：前两次迭代已完成，从第 2 次开始
+        let path =
+            std::env::temp_dir().join(format!("scheduler-user-resumed-{}", std::process::id()));
+        let traces = executor()
+            .starting_at(2)
+            .with_journal(Arc::new(Journal::create(&path).expect("create journal")))
+            .run(&mut TestComponent)
+            .expect("run workflow");
+        assert_eq!(
+            aborted(&traces),
+            vec![(2, "retry".to_string()), (3, "retry".to_string())]
+        );
+
+        let mut replay = Replay::load(&path).expect("load journal");
+        std::fs::remove_file(&path).ok();
+        let user = replay.users.shift_remove(&1).expect("recorded user");
+        assert_eq!(user.first_iteration, 2);
+        let mut replayed = user.component;
+        let replayed_traces = executor()
+            .starting_at(user.first_iteration)
+            .with_recorded_data(user.iterations)
+            .with_recorded_aborts(user.aborts)
+            .run(&mut replayed)
+            .expect("replay run");
+        let summary = |traces: &[ExecutionTrace]| -> Vec<_> {
+            traces
+                .iter()
+                .map(|t| (t.iteration, t.node_id.clone(), t.status.clone()))
+                .collect()
+        };
+        assert_eq!(summary(&replayed_traces), summary(&traces));
+        assert_eq!(replayed.remaining(), 0);
+    }
+
+    #[test]
+    fn test_retired_user_finishes_its_iteration() {
+        struct RetiringComponent(RetireSignal);
+
+        impl ActionComponent for RetiringComponent {
+            fn init(&mut self) -> Result<()> {
+                Ok(())
+            }
+
+            fn do_action(
+                &mut self,
+                _action: &ActionDef,
+                _ctx: &mut ActionContext<'_>,
+            ) -> Result<ActionOutcome> {
+                self.0.retire();
+                Ok(ActionOutcome::success())
+            }
+
+            fn release(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: retired
+actions:
+  actions:
+    - id: probe
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      edges:
+        - to: again
+    - id: again
+      type: action
+      action: probe
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let retire = RetireSignal::new();
+
+        // 第一个动作即收到退役信号：本次迭代照常走完，之后不再开始新的迭代
+        let traces = UserExecutor::new(
+            UserContext::new(1, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            0,
+            ThinkTime::None,
+            TemplateContext::new(),
+        )
+        .with_retire_signal(retire.clone())
+        .run(&mut RetiringComponent(retire))
+        .expect("run workflow");
+        let executed: Vec<_> = traces
+            .iter()
+            .map(|t| (t.iteration, t.node_id.as_str()))
+            .collect();
+        assert_eq!(executed, vec![(0, "start"), (0, "again")]);
+    }
+
+    #[test]
+    fn test_node_think_time_and_pacing() {
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: paced
+actions:
+  actions:
+    - id: probe
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      think_time: 30ms
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let state_machine = StateMachine::from_wbs(&WbsTree::build(&scenario).expect("build tree"));
+        let executor = || {
+            UserExecutor::new(
+                UserContext::new(1, "tenant-a".to_string(), None),
+                state_machine.clone(),
+                3,
+                ThinkTime::None,
+                TemplateContext::new(),
+            )
+        };
+
+        // 每次迭代在动作之后暂停 30ms
+        let started = Instant::now();
+        executor().run(&mut TestComponent).expect("run workflow");
+        assert!(started.elapsed() >= Duration::from_millis(90));
+
+        // 每 100ms 开始一次迭代：第三次迭代在 200ms 时开始
+        let started = Instant::now();
+        executor()
+            .with_pacing(Duration::from_millis(100))
+            .run(&mut TestComponent)
+            .expect("run workflow");
+        assert!(started.elapsed() >= Duration::from_millis(230));
+    }
+
+    #[tokio::test]
+    async fn test_async_users_share_one_thread() {
+        struct SlowComponent;
+
+        impl AsyncActionComponent for SlowComponent {
+            async fn init(&mut self) -> Result<()> {
+                Ok(())
+            }
+
+            async fn do_action(
+                &mut self,
+                _action: &ActionDef,
+                _ctx: &mut ActionContext<'_>,
+            ) -> Result<ActionOutcome> {
+                tokio::time::sleep(Duration::from_millis(50)).await;
+                Ok(ActionOutcome::success())
+            }
+
+            async fn release(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: concurrent
+actions:
+  actions:
+    - id: probe
+      call: get
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: probe
+      think_time: 50ms
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let state_machine = StateMachine::from_wbs(&WbsTree::build(&scenario).expect("build tree"));
+
+        // 单线程运行时上的 500 个用户：每个用户 2 次迭代 × (50ms 动作 + 50ms 思考时间)，
+        // 共享一个运行日志
+        let path =
+            std::env::temp_dir().join(format!("scheduler-user-async-{}", std::process::id()));
+        let journal = Arc::new(Journal::create(&path).expect("create journal"));
+        let started = Instant::now();
+        let users: Vec<_> = (1..=500)
+            .map(|id| {
+                let mut executor = UserExecutor::new(
+                    UserContext::new(id, "tenant-a".to_string(), None),
+                    state_machine.clone(),
+                    2,
+                    ThinkTime::None,
+                    TemplateContext::new(),
+                )
+                .with_journal(Arc::clone(&journal));
+                tokio::spawn(async move { executor.run_async(&mut SlowComponent).await })
+            })
+            .collect();
+        for user in users {
+            let traces = user.await.expect("join user").expect("run workflow");
+            assert_eq!(traces.len(), 2);
+        }
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        tokio::task::spawn_blocking(move || journal.flush())
+            .await
+            .expect("join flush")
+            .expect("flush journal");
+        let replay = crate::Replay::load(&path).expect("load journal");
+        std::fs::remove_file(&path).ok();
+        assert_eq!(replay.users.len(), 500);
+        assert!(replay.users.values().all(|user| user.iterations.len() == 2));
+    }
+
+    #[test]
+    fn test_block_on_waits_for_wakeup() {
+        /// 第一次轮询时挂起，由另一个线程稍后唤醒
+        struct Delayed {
+            woken: Arc<AtomicBool>,
+        }
+
+        impl Future for Delayed {
+            type Output = &'static str;
+
+            fn poll(
+                self: std::pin::Pin<&mut Self>,
+                cx: &mut std::task::Context<'_>,
+            ) -> Poll<Self::Output> {
+                if self.woken.load(Ordering::SeqCst) {
+                    return Poll::Ready("done");
+                }
+                let woken = Arc::clone(&self.woken);
+                let waker = cx.waker().clone();
+                thread::spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    woken.store(true, Ordering::SeqCst);
+                    waker.wake();
+                });
+                Poll::Pending
+            }
+        }
+
+        let woken = Arc::new(AtomicBool::new(false));
+        assert_eq!(block_on(Delayed { woken }), "done");
+    }
+
+    #[test]
+    fn test_failed_assertion_fails_the_action() {
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: asserted
+actions:
+  actions:
+    - id: login
+      call: post
+      export:
+        - type: assert
+          name: ok
+          status: [200, 201]
+        - type: assert
+          name: has-token
+          expr: "$.token"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: login
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+
+        let mut executor = UserExecutor::new(
+            UserContext::new(1, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            1,
+            ThinkTime::None,
+            TemplateContext::new(),
+        );
+        let mut component = RecordingComponent {
+            urls: Vec::new(),
+            response: Some(ActionResponse {
+                status_code: Some(201),
+                body: "{}".to_string(),
+                ..Default::default()
+            }),
+        };
+        let traces = executor.run(&mut component).expect("run workflow");
+
+        let trace = &traces[0];
+        assert_eq!(trace.status, "Failed");
+        assert_eq!(
+            trace.detail,
+            "assertion `login.has-token` failed: `$.token` matched nothing"
+        );
+        let passed: Vec<_> = trace.assertions.iter().map(|r| r.passed()).collect();
+        assert_eq!(passed, vec![true, false]);
+    }
+
+    #[test]
+    fn test_strict_templates_fail_unresolved_actions() {
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: strict
+actions:
+  actions:
+    - id: fetch
+      call: get
+      with:
+        url: "http://host/{{ missing.id }}"
+    - id: report
+      call: post
+      with:
+        url: "http://host/{{ missing.id | default('none') }}?t={{ user.id | urlencode }}"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: fetch
+      edges:
+        - to: report
+    - id: report
+      type: action
+      action: report
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+
+        let mut executor = UserExecutor::new(
+            UserContext::new(3, "tenant-a".to_string(), None),
+            StateMachine::from_wbs(&tree),
+            1,
+            ThinkTime::None,
+            TemplateContext::new().with_strict(true),
+        );
+        let mut component = RecordingComponent {
+            urls: Vec::new(),
+            response: None,
+        };
+        let traces = executor.run(&mut component).expect("run workflow");
+
+        // 未解析的动作不会被执行，工作流继续
+        assert_eq!(component.urls, vec!["http://host/none?t=3"]);
+        assert_eq!(traces[0].status, "Failed");
+        assert!(
+            traces[0]
+                .detail
+                .contains("unresolved variable `missing.id`"),
+            "{}",
+            traces[0].detail
+        );
+        assert_eq!(traces[1].status, "Success");
+    }
+
+    #[test]
+    fn test_workbook_exports_are_shared_between_users() {
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: shared
+actions:
+  actions:
+    - id: login
+      call: post
+      with:
+        url: "http://host/login"
+      export:
+        - type: jsonpath
+          name: token
+          expr: "$.token"
+          scope: workbook
+          mode: first_writer_wins
+    - id: fetch
+      call: get
+      with:
+        url: "http://host/items?token={{login.token}}"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: login
+      edges:
+        - to: fetch-node
+    - id: fetch-node
+      type: action
+      action: fetch
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let shared = Arc::new(SharedStore::new());
+
+        let mut urls = Vec::new();
+        for (user_id, token) in [(1, "first"), (2, "second")] {
+            let mut executor = UserExecutor::new(
+                UserContext::new(user_id, "tenant-a".to_string(), None),
+                StateMachine::from_wbs(&tree),
+                1,
+                ThinkTime::None,
+                TemplateContext::new(),
+            )
+            .with_shared_store(Arc::clone(&shared));
+            let mut component = RecordingComponent {
+                urls: Vec::new(),
+                response: Some(ActionResponse {
+                    status_code: Some(200),
+                    body: format!(r#"{{"token": "{}"}}"#, token),
+                    ..Default::default()
+                }),
+            };
+            executor.run(&mut component).expect("run workflow");
+            urls.push(component.urls[1].clone());
+        }
+
+        assert_eq!(
+            urls,
+            vec![
+                "http://host/items?token=first",
+                "http://host/items?token=first"
+            ]
+        );
+        assert_eq!(shared.get("login.token").as_deref(), Some("first"));
+    }
+
+    #[test]
+    fn test_replay_rebuilds_shared_state_in_recorded_order() {
+        /// 记录渲染后的 URL，结果取自日志
+        struct ReplaySpy {
+            inner: crate::ReplayComponent,
+            urls: Vec<String>,
+        }
+
+        impl ActionComponent for ReplaySpy {
+            fn init(&mut self) -> Result<()> {
+                Ok(())
+            }
+
+            fn do_action(
+                &mut self,
+                action: &ActionDef,
+                ctx: &mut ActionContext<'_>,
+            ) -> Result<ActionOutcome> {
+                let url = action.with.get("url").and_then(Value::as_str).unwrap_or("");
+                self.urls.push(url.to_string());
+                self.inner.do_action(action, ctx)
+            }
+
+            fn release(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let scenario = Scenario::from_yaml_str(
+            r#"
+version: "1.0"
+name: shared
+actions:
+  actions:
+    - id: login
+      call: post
+      with:
+        url: "http://host/login"
+      export:
+        - type: jsonpath
+          name: token
+          expr: "$.token"
+          scope: workbook
+          mode: first_writer_wins
+        - type: jsonpath
+          name: last
+          expr: "$.who"
+          scope: workbook
+    - id: fetch
+      call: get
+      with:
+        url: "http://host/items?token={{login.token}}&last={{login.last}}"
+workflows:
+  nodes:
+    - id: start
+      type: action
+      action: login
+      edges:
+        - to: fetch-node
+    - id: fetch-node
+      type: action
+      action: fetch
+      edges:
+        - to: end
+    - id: end
+      type: end
+"#,
+        )
+        .expect("parse");
+        let tree = WbsTree::build(&scenario).expect("build tree");
+        let executor = |user_id: usize| {
+            UserExecutor::new(
+                UserContext::new(user_id, "tenant-a".to_string(), None),
+                StateMachine::from_wbs(&tree),
+                1,
+                ThinkTime::None,
+                TemplateContext::new(),
+            )
+        };
+
+        // 记录：两个用户共享一个存储，用户 1 先完成
+        let path =
+            std::env::temp_dir().join(format!("scheduler-user-shared-{}", std::process::id()));
+        let journal = Arc::new(Journal::create(&path).expect("create journal"));
+        let shared = Arc::new(SharedStore::new());
+        let mut recorded = IndexMap::new();
+        for (user_id, who) in [(1, "first"), (2, "second")] {
+            let mut component = RecordingComponent {
+                urls: Vec::new(),
+                response: Some(ActionResponse {
+                    status_code: Some(200),
+                    body: format!(r#"{{"token": "{who}", "who": "{who}"}}"#),
+                    ..Default::default()
+                }),
+            };
+            executor(user_id)
+                .with_shared_store(Arc::clone(&shared))
+                .with_journal(Arc::clone(&journal))
+                .run(&mut component)
+                .expect("record run");
+            recorded.insert(user_id, component.urls[1].clone());
+        }
+        assert_eq!(
+            recorded[&1],
+            "http://host/items?token=first&last=first".to_string()
+        );
+        assert_eq!(
+            recorded[&2],
+            "http://host/items?token=first&last=second".to_string()
+        );
+
+        // 倒序重放：每个用户仍看到记录时的共享变量
+        let mut replay = crate::Replay::load(&path).expect("load journal");
+        std::fs::remove_file(&path).ok();
+        for user_id in [2, 1] {
+            let user = replay.users.shift_remove(&user_id).expect("recorded user");
+            let shared = user.shared_replay(&replay.shared);
+            let mut spy = ReplaySpy {
+                inner: user.component,
+                urls: Vec::new(),
+            };
+            executor(user_id)
+                .with_recorded_shared(shared)
+                .with_recorded_data(user.iterations)
+                .run(&mut spy)
+                .expect("replay run");
+            assert_eq!(spy.urls[1], recorded[&user_id]);
+        }
+    }
 }
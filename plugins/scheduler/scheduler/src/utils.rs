@@ -45,6 +45,40 @@ pub fn parse_duration(s: &str) -> Result<Duration> {
     }
 }
 
+/// 解析速率字符串为每秒次数
+///
+/// 支持的格式：
+/// - "50/s" - 每秒
+/// - "600/m" - 每分钟
+///
+/// # Examples
+/// ```
+/// assert_eq!(parse_rate("50/s").unwrap(), 50.0);
+/// assert_eq!(parse_rate("30/m").unwrap(), 0.5);
+/// ```
+pub fn parse_rate(s: &str) -> Result<f64> {
+    let s = s.trim();
+
+    let (count, per_secs) = if let Some(count) = s.strip_suffix("/s") {
+        (count, 1.0)
+    } else if let Some(count) = s.strip_suffix("/m") {
+        (count, 60.0)
+    } else {
+        anyhow::bail!(
+            "Invalid rate format: '{}'. Expected format: <number>/s|m",
+            s
+        )
+    };
+    let count: f64 = count
+        .trim()
+        .parse()
+        .with_context(|| format!("Invalid rate value: {}", count))?;
+    if !(count.is_finite() && count >= 0.0) {
+        anyhow::bail!("Rate must be a non-negative number: '{}'", s);
+    }
+    Ok(count / per_secs)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -87,4 +121,14 @@ mod tests {
         assert!(parse_duration("1").is_err());
         assert!(parse_duration("s").is_err());
     }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("50/s").unwrap(), 50.0);
+        assert_eq!(parse_rate(" 0.5 /s").unwrap(), 0.5);
+        assert_eq!(parse_rate("30/m").unwrap(), 0.5);
+        assert!(parse_rate("50").is_err());
+        assert!(parse_rate("-1/s").is_err());
+        assert!(parse_rate("fast/s").is_err());
+    }
 }
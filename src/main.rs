@@ -1,8 +1,18 @@
-use std::{env, fs};
+use std::{env, fs, path::Path};
 
 use anyhow::{Context, Result};
+use scheduler_core::{
+    data::GUEST_DATA_DIR,
+    dsl::{ParseMode, Scenario},
+    graph::{EdgeHits, Graph, GraphFormat},
+    secrets,
+    variables::{Variables, parse_override},
+    wbs::WbsTree,
+};
 use wasmtime::{Config, Engine, Store, component::ResourceTable};
-use wasmtime_wasi::{WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView, p2::add_to_linker_sync};
+use wasmtime_wasi::{
+    DirPerms, FilePerms, WasiCtx, WasiCtxBuilder, WasiCtxView, WasiView, p2::add_to_linker_sync,
+};
 
 wasmtime::component::bindgen!({
     path: "plugins/scheduler/scheduler/wit/world.wit",
@@ -24,13 +34,63 @@ impl WasiView for HostState {
 }
 
 fn main() -> Result<()> {
+    let mut args = env::args().skip(1).peekable();
+
+    // `Ntx schema` 输出场景 DSL 的 JSON Schema；`--write` 则在仓库根目录下重新生成
+    // plugins/scheduler/res/scenario.schema.json（core-libs 的测试会比对该文件）
+    if args.peek().map(String::as_str) == Some("schema") {
+        args.next();
+        let schema = Scenario::json_schema();
+        match args.next().as_deref() {
+            None => print!("{schema}"),
+            Some("--write") => {
+                let schema_path = "plugins/scheduler/res/scenario.schema.json";
+                fs::write(schema_path, schema)
+                    .with_context(|| format!("写入 Schema 文件失败: {schema_path}"))?;
+                println!("已更新 {schema_path}");
+            }
+            Some(arg) => anyhow::bail!("无法识别的参数: {arg}"),
+        }
+        return Ok(());
+    }
+
+    // `Ntx graph [--format dot|mermaid] [--hits hits.json] scenario.yaml` 输出工作流图
+    if args.peek().map(String::as_str) == Some("graph") {
+        args.next();
+        return print_graph(args);
+    }
+
+    // 其余参数：`[--var key=value]... [scenario.yaml]`
+    let mut scenario_arg = None;
+    let mut overrides = Vec::new();
+    while let Some(arg) = args.next() {
+        if arg == "--var" {
+            let pair = args.next().context("--var 需要 key=value 参数")?;
+            overrides.push(parse_override(&pair)?);
+        } else if let Some(pair) = arg.strip_prefix("--var=") {
+            overrides.push(parse_override(pair)?);
+        } else if scenario_arg.is_none() {
+            scenario_arg = Some(arg);
+        } else {
+            anyhow::bail!("无法识别的参数: {arg}");
+        }
+    }
+
     let default_scenario = "plugins/scheduler/res/simple_scenario.yaml";
-    let scenario_path = env::args()
-        .nth(1)
-        .unwrap_or_else(|| default_scenario.to_string());
+    let scenario_path = scenario_arg.unwrap_or_else(|| default_scenario.to_string());
     let scenario = fs::read_to_string(&scenario_path)
         .with_context(|| format!("读取场景文件失败: {scenario_path}"))?;
 
+    // 变量优先级：--var > NTX_VAR_* > 场景默认值；生效值以 NTX_VAR_* 传给组件
+    // 同时解析 `include`，被包含文件缺失、成环或 id 冲突在启动组件前即报错
+    let parsed =
+        Scenario::from_yaml_str_at(&scenario, Path::new(&scenario_path), ParseMode::Lenient)
+            .with_context(|| format!("解析场景文件失败: {scenario_path}"))?;
+    let variables = Variables::resolve(&parsed, env::vars(), &overrides)?;
+    if !variables.is_empty() {
+        print!("场景变量:\n{variables}");
+    }
+
     let mut config = Config::new();
     config.wasm_component_model(true);
 
@@ -38,11 +98,30 @@ fn main() -> Result<()> {
     let mut linker = wasmtime::component::Linker::new(&engine);
     add_to_linker_sync(&mut linker)?;
 
+    // 场景所在目录以 `.` 预打开，组件据此解析 `include`
+    let scenario_dir = Path::new(&scenario_path)
+        .parent()
+        .filter(|dir| !dir.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."));
+    // `env` 密钥只转发场景声明的环境变量，由组件解析并在输出中遮蔽
+    let secret_env: Vec<(String, String)> = secrets::env_names(&parsed)
+        .filter_map(|name| Some((name.to_string(), env::var(name).ok()?)))
+        .collect();
+    let mut wasi = WasiCtxBuilder::new();
+    wasi.inherit_stdio()
+        .inherit_network()
+        .envs(&variables.to_env())
+        .envs(&secret_env)
+        .preopened_dir(scenario_dir, ".", DirPerms::READ, FilePerms::READ)
+        .with_context(|| format!("预打开场景目录失败: {}", scenario_dir.display()))?;
+    // 数据目录（data.dir，相对场景文件）以 GUEST_DATA_DIR 预打开，供 feeder 读取
+    if let Some(data) = &parsed.data {
+        let data_dir = scenario_dir.join(&data.dir);
+        wasi.preopened_dir(&data_dir, GUEST_DATA_DIR, DirPerms::READ, FilePerms::READ)
+            .with_context(|| format!("预打开数据目录失败: {}", data_dir.display()))?;
+    }
     let host_state = HostState {
-        wasi: WasiCtxBuilder::new()
-            .inherit_stdio()
-            .inherit_network()
-            .build(),
+        wasi: wasi.build(),
         table: ResourceTable::default(),
     };
     let mut store = Store::new(&engine, host_state);
@@ -72,3 +151,38 @@ fn main() -> Result<()> {
 
     Ok(())
 }
+
+/// 将场景工作流渲染为 DOT 或 Mermaid；`--hits` 叠加一次运行的边命中次数
+fn print_graph(mut args: impl Iterator<Item = String>) -> Result<()> {
+    let mut format = GraphFormat::Dot;
+    let mut hits_path = None;
+    let mut scenario_arg = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = args.next().context("--format 需要 dot 或 mermaid")?;
+                format = value.parse().map_err(anyhow::Error::msg)?;
+            }
+            "--hits" => hits_path = Some(args.next().context("--hits 需要文件路径")?),
+            _ if scenario_arg.is_none() && !arg.starts_with("--") => scenario_arg = Some(arg),
+            _ => anyhow::bail!("无法识别的参数: {arg}"),
+        }
+    }
+
+    let scenario_path = scenario_arg.context("graph 需要场景文件路径")?;
+    let input = fs::read_to_string(&scenario_path)
+        .with_context(|| format!("读取场景文件失败: {scenario_path}"))?;
+    let scenario =
+        Scenario::from_yaml_str_at(&input, Path::new(&scenario_path), ParseMode::Lenient)
+            .with_context(|| format!("解析场景文件失败: {scenario_path}"))?;
+    let mut graph = Graph::from_wbs(&WbsTree::build(&scenario)?);
+    if let Some(path) = hits_path {
+        let raw =
+            fs::read_to_string(&path).with_context(|| format!("读取命中次数文件失败: {path}"))?;
+        let hits =
+            EdgeHits::from_json(&raw).with_context(|| format!("解析命中次数文件失败: {path}"))?;
+        graph = graph.with_hits(&hits);
+    }
+    print!("{}", graph.render(format));
+    Ok(())
+}